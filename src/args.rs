@@ -0,0 +1,215 @@
+use std::path::Path;
+
+// Archivo pedido por linea de comandos, con la posicion inicial del cursor (base 1)
+pub struct FileArg {
+  pub path: String,
+  pub line: Option<usize>,
+  pub col: Option<usize>,
+}
+
+#[derive(Default)]
+pub struct Args {
+  pub files: Vec<FileArg>,
+  pub dir: Option<String>,    // directorio a explorar (argumento que es una carpeta)
+  pub cwd: Option<String>,    // --cwd: directorio de trabajo del proceso
+  pub config: Option<String>, // --config: archivo de configuracion alternativo
+  pub readonly: bool,
+  pub help: bool,
+  pub version: bool,
+}
+
+pub const USAGE: &str = "\
+Uso: camelCaseEditor [opciones] [archivo[:linea[:columna]] | +linea archivo | directorio]...
+
+Opciones:
+  -r, --readonly          Abrir los archivos en modo solo lectura
+  -c, --config <archivo>  Usar otro archivo de configuracion
+  -C, --cwd <directorio>  Cambiar el directorio de trabajo antes de abrir
+  -h, --help              Mostrar esta ayuda
+  -V, --version           Mostrar la version
+
+Ejemplos:
+  camelCaseEditor src/main.rs
+  camelCaseEditor src/main.rs:120:5
+  camelCaseEditor +40 README.md
+  camelCaseEditor ../proyecto";
+
+pub fn parse_args(raw: impl Iterator<Item = String>) -> Result<Args, String> {
+  let mut args = Args::default();
+  let mut raw = raw.peekable();
+  let mut pending_line: Option<usize> = None;
+  let mut only_files = false;
+  // archivos y carpetas con su +N; se clasifican al final, cuando ya se conoce --cwd
+  let mut positional: Vec<(String, Option<usize>)> = Vec::new();
+
+  while let Some(arg) = raw.next() {
+    if !only_files && arg.starts_with('-') && arg.len() > 1 {
+      // --opcion=valor
+      let (name, inline_value) = match arg.split_once('=') {
+        Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value.to_string())),
+        _ => (arg.clone(), None),
+      };
+
+      let mut value = |name: &str| {
+        inline_value.clone()
+          .or_else(|| raw.next())
+          .ok_or_else(|| format!("falta el valor de {}", name))
+      };
+
+      match name.as_str() {
+        "--" => only_files = true,
+        "-h" | "--help" => args.help = true,
+        "-V" | "--version" => args.version = true,
+        "-r" | "--readonly" => args.readonly = true,
+        "-c" | "--config" => args.config = Some(value(&name)?),
+        "-C" | "--cwd" => args.cwd = Some(value(&name)?),
+        _ => return Err(format!("opcion desconocida: {}", arg)),
+      }
+      continue;
+    }
+
+    // +N: linea para el siguiente archivo
+    if !only_files && let Some(number) = arg.strip_prefix('+') {
+      let line = number.parse().map_err(|_| format!("linea invalida: {}", arg))?;
+      pending_line = Some(line);
+      continue;
+    }
+
+    positional.push((arg, pending_line.take()));
+  }
+
+  if let Some(line) = pending_line {
+    return Err(format!("+{} sin archivo", line));
+  }
+
+  // las rutas relativas son relativas al directorio de --cwd
+  let base = Path::new(args.cwd.as_deref().unwrap_or("."));
+  for (arg, line) in positional {
+    if line.is_none() && base.join(&arg).is_dir() {
+      args.dir = Some(arg);
+      continue;
+    }
+
+    let mut file = split_position(&arg, base);
+    if line.is_some() {
+      file.line = line;
+    }
+    args.files.push(file);
+  }
+
+  Ok(args)
+}
+
+// "ruta:linea:col" -> FileArg. Si la ruta tal cual existe (dentro de `base`) se respeta
+// (nombres con ':' o rutas de Windows como C:\...)
+fn split_position(arg: &str, base: &Path) -> FileArg {
+  let plain = FileArg { path: arg.to_string(), line: None, col: None };
+  if base.join(arg).exists() {
+    return plain;
+  }
+
+  let mut parts = arg.rsplitn(3, ':');
+  let last = parts.next();
+  let middle = parts.next();
+  let rest = parts.next();
+
+  match (rest, middle, last) {
+    (Some(path), Some(line), Some(col)) if !path.is_empty() => {
+      match (line.parse(), col.parse()) {
+        (Ok(line), Ok(col)) => FileArg { path: path.to_string(), line: Some(line), col: Some(col) },
+        // solo "ruta:linea" donde la ruta tiene ':'
+        (_, Ok(line)) => FileArg { path: format!("{}:{}", path, middle.unwrap_or_default()), line: Some(line), col: None },
+        _ => plain,
+      }
+    }
+    (None, Some(path), Some(line)) if !path.is_empty() => match line.parse() {
+      Ok(line) => FileArg { path: path.to_string(), line: Some(line), col: None },
+      Err(_) => plain,
+    },
+    _ => plain,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(args: &[&str]) -> Result<Args, String> {
+    parse_args(args.iter().map(|a| a.to_string()))
+  }
+
+  // (ruta, linea, columna) de cada archivo
+  fn files(args: &Args) -> Vec<(&str, Option<usize>, Option<usize>)> {
+    args.files.iter().map(|f| (f.path.as_str(), f.line, f.col)).collect()
+  }
+
+  #[test]
+  fn split_position_line_and_col() {
+    let base = Path::new("no-existe");
+    let file = split_position("src/main.rs:120:5", base);
+    assert_eq!((file.path.as_str(), file.line, file.col), ("src/main.rs", Some(120), Some(5)));
+
+    let file = split_position("src/main.rs:120", base);
+    assert_eq!((file.path.as_str(), file.line, file.col), ("src/main.rs", Some(120), None));
+  }
+
+  #[test]
+  fn split_position_keeps_colons_that_are_not_numbers() {
+    let base = Path::new("no-existe");
+    let file = split_position("a:b:7", base);
+    assert_eq!((file.path.as_str(), file.line, file.col), ("a:b", Some(7), None));
+
+    let file = split_position("notas:hoy", base);
+    assert_eq!((file.path.as_str(), file.line, file.col), ("notas:hoy", None, None));
+
+    let file = split_position(":3", base);
+    assert_eq!((file.path.as_str(), file.line, file.col), (":3", None, None));
+  }
+
+  #[test]
+  fn split_position_respects_existing_names() {
+    let dir = std::env::temp_dir().join("camelCaseEditor-test-split-position");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("a:2"), "").unwrap();
+
+    let file = split_position("a:2", &dir);
+    assert_eq!((file.path.as_str(), file.line, file.col), ("a:2", None, None));
+  }
+
+  #[test]
+  fn parse_args_options_and_files() {
+    let args = parse(&["-r", "--config=otro.toml", "+40", "README.md", "x.rs:3:2"]).unwrap();
+    assert!(args.readonly);
+    assert_eq!(args.config.as_deref(), Some("otro.toml"));
+    assert_eq!(files(&args), vec![("README.md", Some(40), None), ("x.rs", Some(3), Some(2))]);
+
+    // después de "--" todo es archivo
+    let args = parse(&["--", "-r", "+3"]).unwrap();
+    assert!(!args.readonly);
+    assert_eq!(files(&args), vec![("-r", None, None), ("+3", None, None)]);
+  }
+
+  #[test]
+  fn parse_args_errors() {
+    assert!(parse(&["--nada"]).is_err());
+    assert!(parse(&["--config"]).is_err());
+    assert!(parse(&["+x", "a.rs"]).is_err());
+    assert!(parse(&["a.rs", "+3"]).is_err());
+  }
+
+  #[test]
+  fn parse_args_classifies_relative_to_cwd() {
+    let dir = std::env::temp_dir().join("camelCaseEditor-test-parse-cwd");
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    let cwd = dir.to_string_lossy().to_string();
+
+    // --cwd después de la carpeta igual se aplica
+    let args = parse(&["sub", "--cwd", &cwd]).unwrap();
+    assert_eq!(args.dir.as_deref(), Some("sub"));
+    assert!(args.files.is_empty());
+
+    let args = parse(&["sub"]).unwrap();
+    assert_eq!(args.dir, None);
+    assert_eq!(files(&args), vec![("sub", None, None)]);
+  }
+}
@@ -0,0 +1,181 @@
+use crate::CursorPos;
+use crate::document::Document;
+use crate::multicursor::{ selections, set_selections };
+use crate::selection::{ Selection, delete_selection };
+use crate::utils::{ char_to_byte_idx, col_to_char, display_col, line_len_chars };
+
+// Selección en bloque (por columnas): Alt + Shift + Arriba / Abajo la empieza y, ya
+// empezada, Alt + Shift + Izq / Der la ensancha. Se guarda en columnas de pantalla y se
+// traduce a un cursor con su selección por linea (ver multicursor.rs), así copiar,
+// cortar, escribir y borrar funcionan en cada linea del bloque. Las columnas pueden
+// pasar el final de las lineas cortas: al escribir se rellenan con espacios.
+
+#[derive(Clone, Copy)]
+pub struct Block {
+  anchor: (usize, usize), // (linea, columna) donde empezó
+  cursor: (usize, usize), // (linea, columna) del extremo que se mueve
+}
+
+impl Block {
+  // columnas de la izquierda y la derecha del bloque
+  fn columns(&self) -> (usize, usize) {
+    (self.anchor.1.min(self.cursor.1), self.anchor.1.max(self.cursor.1))
+  }
+}
+
+// Mueve el extremo del bloque (empezándolo en el cursor si no había) y selecciona
+pub fn extend(doc: &mut Document, code: crossterm::event::KeyCode, tab_width: usize) {
+  use crossterm::event::KeyCode;
+
+  let last_line = doc.lines.len() - 1;
+  let cursor = doc.cursor;
+  let col = display_col(&doc.lines[cursor.y], cursor.x, tab_width);
+  let block = doc.block.get_or_insert(Block { anchor: (cursor.y, col), cursor: (cursor.y, col) });
+  match code {
+    KeyCode::Up => block.cursor.0 = block.cursor.0.saturating_sub(1),
+    KeyCode::Down => block.cursor.0 = (block.cursor.0 + 1).min(last_line),
+    KeyCode::Left => block.cursor.1 = block.cursor.1.saturating_sub(1),
+    KeyCode::Right => block.cursor.1 += 1,
+    _ => {}
+  }
+
+  let block = *block;
+  let (left, right) = block.columns();
+  let (top, bottom) = (block.anchor.0.min(block.cursor.0), block.anchor.0.max(block.cursor.0));
+  let mut all: Vec<Selection> = (top..=bottom)
+    .map(|y| {
+      let a = CursorPos { x: col_to_char(&doc.lines[y], left, tab_width), y };
+      let b = CursorPos { x: col_to_char(&doc.lines[y], right, tab_width), y };
+      let (start, end) = if block.cursor.1 >= block.anchor.1 { (a, b) } else { (b, a) };
+      Selection { cursor: end, start: if a == b { None } else { Some(start) } }
+    })
+    .collect();
+
+  let primary = block.cursor.0 - top;
+  set_selections(doc, &mut all, primary);
+}
+
+// Antes de escribir en el bloque: las lineas que no llegan a su columna izquierda se
+// rellenan con espacios, para que lo escrito quede alineado
+pub fn pad(doc: &mut Document, tab_width: usize) {
+  let Some(block) = doc.block else { return };
+  let (left, _) = block.columns();
+
+  let mut all = selections(doc);
+  for sel in all.iter_mut() {
+    let line = &mut doc.lines[sel.cursor.y];
+    let len = line_len_chars(line);
+    let width = display_col(line, len, tab_width);
+    if width >= left {
+      continue;
+    }
+
+    line.push_str(&" ".repeat(left - width));
+    let new_len = line_len_chars(line);
+    for pos in std::iter::once(&mut sel.cursor).chain(sel.start.iter_mut()) {
+      if pos.x == len {
+        pos.x = new_len;
+      }
+    }
+  }
+  // el principal sigue siendo el de la linea del extremo que se mueve
+  let primary = all.iter().position(|s| s.cursor.y == block.cursor.0).unwrap_or(0);
+  set_selections(doc, &mut all, primary);
+}
+
+// Pega texto copiado de un bloque como bloque: cada linea en la misma columna de las
+// lineas siguientes, rellenando las cortas y agregando las que falten al final
+pub fn paste(lines: &mut Vec<String>, cursor: &mut CursorPos, selection: &mut Option<CursorPos>, text: &str, tab_width: usize) {
+  if selection.is_some() {
+    delete_selection(lines, cursor, selection);
+  }
+
+  let col = display_col(&lines[cursor.y], cursor.x, tab_width);
+  let first = cursor.y;
+  for (i, part) in text.split('\n').enumerate() {
+    let y = first + i;
+    if y == lines.len() {
+      lines.push(String::new());
+    }
+
+    let width = display_col(&lines[y], line_len_chars(&lines[y]), tab_width);
+    if width < col && !part.is_empty() {
+      lines[y].push_str(&" ".repeat(col - width));
+    }
+    let x = col_to_char(&lines[y], col, tab_width);
+    let byte_idx = char_to_byte_idx(&lines[y], x);
+    lines[y].insert_str(byte_idx, part);
+    *cursor = CursorPos { x: x + part.chars().count(), y };
+  }
+
+  *selection = None;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crossterm::event::KeyCode;
+  use crate::multicursor::selected_text;
+
+  fn doc(lines: &[&str], x: usize, y: usize) -> Document {
+    let mut doc = Document::new();
+    doc.lines = lines.iter().map(|l| l.to_string()).collect();
+    doc.cursor = CursorPos { x, y };
+    doc
+  }
+
+  fn extend_by(doc: &mut Document, keys: &[KeyCode]) {
+    for &key in keys {
+      extend(doc, key, 4);
+    }
+  }
+
+  #[test]
+  fn extend_selects_columns_on_every_line() {
+    let mut doc = doc(&["abcdef", "\tx", "ab", "abcdef"], 1, 0);
+    extend_by(&mut doc, &[KeyCode::Down, KeyCode::Down, KeyCode::Down, KeyCode::Right, KeyCode::Right]);
+    // el tab ocupa las columnas 0 a 3: esa linea queda vacía, y la corta hasta su final
+    assert_eq!(selected_text(&doc, true).as_deref(), Some("bc\n\nb\nbc"));
+    assert_eq!(selected_text(&doc, false).as_deref(), Some("bc\nb\nbc"));
+    // el principal es el extremo que se mueve
+    assert_eq!((doc.cursor.x, doc.cursor.y), (3, 3));
+  }
+
+  #[test]
+  fn extend_to_the_left_keeps_the_anchor() {
+    let mut doc = doc(&["abcdef", "abcdef"], 3, 0);
+    extend_by(&mut doc, &[KeyCode::Down, KeyCode::Left, KeyCode::Left]);
+    assert_eq!(selected_text(&doc, true).as_deref(), Some("bc\nbc"));
+    assert_eq!(doc.cursor.x, 1);
+    assert_eq!(doc.selection_start.map(|s| s.x), Some(3));
+  }
+
+  #[test]
+  fn pad_fills_short_lines() {
+    let mut doc = doc(&["abcd", "a", "abcd"], 3, 0);
+    extend_by(&mut doc, &[KeyCode::Down, KeyCode::Down]);
+    pad(&mut doc, 4);
+    assert_eq!(doc.lines, ["abcd", "a  ", "abcd"]);
+    assert!(selections(&doc).iter().all(|s| s.cursor.x == 3));
+    assert_eq!((doc.cursor.x, doc.cursor.y), (3, 2));
+  }
+
+  #[test]
+  fn pad_keeps_the_primary_cursor() {
+    // el bloque crece hacia arriba: el principal queda en la primera linea
+    let mut doc = doc(&["abcd", "a", "abcd"], 3, 2);
+    extend_by(&mut doc, &[KeyCode::Up, KeyCode::Up]);
+    pad(&mut doc, 4);
+    assert_eq!((doc.cursor.x, doc.cursor.y), (3, 0));
+  }
+
+  #[test]
+  fn paste_as_block() {
+    let mut lines = vec!["ab".to_string(), "".to_string()];
+    let mut cursor = CursorPos { x: 1, y: 0 };
+    let mut selection = None;
+    paste(&mut lines, &mut cursor, &mut selection, "12\n34\n\n56", 4);
+    assert_eq!(lines, ["a12b", " 34", "", " 56"]);
+    assert_eq!((cursor.x, cursor.y), (3, 3));
+  }
+}
@@ -0,0 +1,262 @@
+use crate::CursorPos;
+use crate::document::Document;
+use crate::syntax::Scope;
+
+// Pareja del paréntesis, corchete o llave junto al cursor, para resaltarla y saltar a
+// ella (Ctrl + ]), y los paréntesis sin pareja de las lineas visibles. Con resaltado de
+// sintaxis no se cuentan los que están dentro de strings o comentarios, salvo que el
+// del cursor también esté en uno.
+
+const PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+// hasta cuántas lineas se busca la pareja; más lejos no se sabe si la tiene
+const MAX_SCAN_LINES: usize = 2000;
+
+// último unmatched(): hash del texto, primera y última linea, y lo encontrado
+pub type UnmatchedCache = Option<(u64, usize, usize, Vec<CursorPos>)>;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum BracketMatch {
+  Matched(CursorPos, CursorPos), // el del cursor y su pareja
+  Unmatched(CursorPos),
+}
+
+// resultado de buscar la pareja
+enum Pair {
+  Found(CursorPos),
+  Missing, // se llegó al principio o al final del documento sin encontrarla
+  Unknown, // se cortó la búsqueda antes (MAX_SCAN_LINES)
+}
+
+// El paréntesis debajo del cursor o, si no hay, el de justo antes
+pub fn at_cursor(doc: &Document) -> Option<BracketMatch> {
+  let cursor = doc.cursor;
+  let chars: Vec<char> = doc.lines[cursor.y].chars().collect();
+  let x = [Some(cursor.x), cursor.x.checked_sub(1)].into_iter()
+    .flatten()
+    .find(|&x| chars.get(x).is_some_and(|&c| is_bracket(c)))?;
+
+  let pos = CursorPos { x, y: cursor.y };
+  match find_pair(doc, pos, chars[x]) {
+    Pair::Found(other) => Some(BracketMatch::Matched(pos, other)),
+    Pair::Missing => Some(BracketMatch::Unmatched(pos)),
+    Pair::Unknown => None,
+  }
+}
+
+// Paréntesis de código (no de strings ni comentarios) sin pareja entre las lineas
+// `first` y `last`. Los que podrían tenerla a más de MAX_SCAN_LINES no se marcan.
+// Se dibuja en cada tecla: solo se vuelve a buscar si cambió el texto o las lineas.
+pub fn unmatched(doc: &Document, first: usize, last: usize) -> Vec<CursorPos> {
+  let hash = doc.text_hash();
+  let mut cache = doc.brackets_cache.borrow_mut();
+  match &*cache {
+    Some((cached, a, b, found)) if (*cached, *a, *b) == (hash, first, last) => found.clone(),
+    _ => {
+      let found = scan_unmatched(doc, first, last);
+      *cache = Some((hash, first, last, found.clone()));
+      found
+    }
+  }
+}
+
+fn scan_unmatched(doc: &Document, first: usize, last: usize) -> Vec<CursorPos> {
+  let lines = &doc.lines;
+  let last = last.min(lines.len() - 1);
+  let from = first.saturating_sub(MAX_SCAN_LINES);
+  let to = (last + MAX_SCAN_LINES).min(lines.len() - 1);
+  let scopes = scopes(doc, from, to);
+  let visible = |p: &CursorPos| (first..=last).contains(&p.y);
+
+  // aperturas pendientes, una pila por tipo
+  let mut open: [Vec<CursorPos>; 3] = Default::default();
+  let mut found = Vec::new();
+  for (y, line) in lines.iter().enumerate().take(to + 1).skip(from) {
+    for (x, c) in line.chars().enumerate() {
+      if !is_bracket(c) || class(scope_at(&scopes, from, CursorPos { x, y })) != Scope::Plain {
+        continue;
+      }
+      let pos = CursorPos { x, y };
+      if let Some(kind) = PAIRS.iter().position(|&(o, _)| o == c) {
+        open[kind].push(pos);
+      } else if let Some(kind) = PAIRS.iter().position(|&(_, cl)| cl == c)
+        && open[kind].pop().is_none()
+        && from == 0
+        && visible(&pos)
+      {
+        found.push(pos);
+      }
+    }
+  }
+  if to == lines.len() - 1 {
+    found.extend(open.into_iter().flatten().filter(visible));
+  }
+  found
+}
+
+// Ctrl + ]: lleva el cursor a la pareja. Devuelve false si no hay.
+pub fn jump(doc: &mut Document) -> bool {
+  let Some(BracketMatch::Matched(_, other)) = at_cursor(doc) else {
+    return false;
+  };
+  doc.cursor = other;
+  doc.selection_start = None;
+  true
+}
+
+fn find_pair(doc: &Document, pos: CursorPos, bracket: char) -> Pair {
+  let lines = &doc.lines;
+  let Some(&(open, close)) = PAIRS.iter().find(|&&(open, close)| bracket == open || bracket == close) else {
+    return Pair::Missing;
+  };
+  let forward = bracket == open;
+
+  let first = if forward { pos.y } else { pos.y.saturating_sub(MAX_SCAN_LINES) };
+  let last = if forward { (pos.y + MAX_SCAN_LINES).min(lines.len() - 1) } else { pos.y };
+  let scopes = scopes(doc, first, last);
+  let class_at = |p: CursorPos| class(scope_at(&scopes, first, p));
+  let class = class_at(pos);
+
+  // desde el paréntesis del cursor (incluido) hacia su pareja
+  let line_chars = |y: usize| lines[y].chars().enumerate().map(move |(x, c)| (CursorPos { x, y }, c));
+  let positions: Box<dyn Iterator<Item = (CursorPos, char)>> = if forward {
+    Box::new((first..=last).flat_map(line_chars).skip(pos.x))
+  } else {
+    let after = lines[pos.y].chars().count() - 1 - pos.x;
+    Box::new((first..=last).rev().flat_map(move |y| line_chars(y).collect::<Vec<_>>().into_iter().rev()).skip(after))
+  };
+
+  let mut depth = 0;
+  for (p, c) in positions {
+    if (c != open && c != close) || class_at(p) != class {
+      continue;
+    }
+    if c == bracket {
+      depth += 1;
+    } else {
+      depth -= 1;
+      if depth == 0 {
+        return Pair::Found(p);
+      }
+    }
+  }
+
+  let whole = if forward { last == lines.len() - 1 } else { first == 0 };
+  if whole { Pair::Missing } else { Pair::Unknown }
+}
+
+fn is_bracket(c: char) -> bool {
+  PAIRS.iter().any(|&(open, close)| c == open || c == close)
+}
+
+// scopes de las lineas `first` a `last` (vacío sin resaltado de sintaxis)
+fn scopes(doc: &Document, first: usize, last: usize) -> Vec<Vec<Scope>> {
+  match &doc.syntax {
+    Some(syntax) => syntax.highlight_lines(&doc.lines, first, last - first + 1, &mut doc.highlight_cache.borrow_mut()),
+    None => Vec::new(),
+  }
+}
+
+fn scope_at(scopes: &[Vec<Scope>], first: usize, p: CursorPos) -> Scope {
+  scopes.get(p.y - first).and_then(|s| s.get(p.x)).copied().unwrap_or(Scope::Plain)
+}
+
+// strings y comentarios aparte, todo lo demás es código
+fn class(scope: Scope) -> Scope {
+  match scope {
+    Scope::String | Scope::Comment => scope,
+    _ => Scope::Plain,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn doc(path: &str, lines: &[&str], x: usize, y: usize) -> Document {
+    let mut doc = Document::new();
+    doc.lines = lines.iter().map(|l| l.to_string()).collect();
+    doc.syntax = crate::syntax::detect(Some(path), None);
+    doc.cursor = CursorPos { x, y };
+    doc
+  }
+
+  type Point = (usize, usize);
+
+  // (x, y) del paréntesis del cursor y de su pareja, si la tiene
+  fn pair(doc: &Document) -> Option<(Point, Option<Point>)> {
+    at_cursor(doc).map(|found| match found {
+      BracketMatch::Matched(pos, other) => ((pos.x, pos.y), Some((other.x, other.y))),
+      BracketMatch::Unmatched(pos) => ((pos.x, pos.y), None),
+    })
+  }
+
+  fn unmatched_in(doc: &Document, first: usize, last: usize) -> Vec<(usize, usize)> {
+    unmatched(doc, first, last).iter().map(|p| (p.x, p.y)).collect()
+  }
+
+  #[test]
+  fn pair_forward_and_backward() {
+    let lines = ["f(a[0], {", "  b", "})"];
+    assert_eq!(pair(&doc("a.txt", &lines, 1, 0)), Some(((1, 0), Some((1, 2)))));
+    assert_eq!(pair(&doc("a.txt", &lines, 0, 2)), Some(((0, 2), Some((8, 0)))));
+    // con el cursor justo después también vale
+    assert_eq!(pair(&doc("a.txt", &lines, 6, 0)), Some(((5, 0), Some((3, 0)))));
+    assert_eq!(pair(&doc("a.txt", &lines, 1, 1)), None);
+  }
+
+  #[test]
+  fn strings_and_comments_are_apart() {
+    let lines = ["f(\")\", x) // )"];
+    assert_eq!(pair(&doc("a.rs", &lines, 1, 0)), Some(((1, 0), Some((8, 0)))));
+    // el de adentro del string busca su pareja en el string
+    assert_eq!(pair(&doc("a.rs", &lines, 3, 0)), Some(((3, 0), None)));
+    // sin resaltado todos cuentan
+    assert_eq!(pair(&doc("a.txt", &lines, 1, 0)), Some(((1, 0), Some((3, 0)))));
+  }
+
+  #[test]
+  fn jump_to_pair() {
+    let mut doc = doc("a.txt", &["(x) y"], 0, 0);
+    assert!(jump(&mut doc));
+    assert_eq!(doc.cursor.x, 2);
+    doc.cursor.x = 4;
+    assert!(!jump(&mut doc));
+  }
+
+  #[test]
+  fn unmatched_brackets_in_view() {
+    let doc = doc("a.rs", &["fn a() {", "  b(]\"(\";", "  c(", "}"], 0, 0);
+    // el ']' no cierra el '(' de antes, así que los dos quedan sin pareja
+    assert_eq!(unmatched_in(&doc, 0, 3), [(4, 1), (3, 1), (3, 2)]);
+    assert_eq!(unmatched_in(&doc, 2, 2), [(3, 2)]);
+  }
+
+  #[test]
+  fn unmatched_is_cached_until_the_text_changes() {
+    let mut doc = doc("a.rs", &["f(x", "y"], 0, 0);
+    assert_eq!(unmatched_in(&doc, 0, 1), [(1, 0)]);
+    assert!(doc.brackets_cache.borrow().is_some());
+    // el mismo texto y las mismas lineas no se vuelven a buscar
+    doc.brackets_cache.borrow_mut().as_mut().unwrap().3.clear();
+    assert!(unmatched_in(&doc, 0, 1).is_empty());
+    assert_eq!(unmatched_in(&doc, 0, 0), [(1, 0)]);
+
+    doc.lines[1].push(')');
+    assert!(unmatched_in(&doc, 0, 0).is_empty());
+  }
+
+  #[test]
+  fn far_pairs_are_not_known() {
+    let mut lines = vec!["("];
+    lines.extend(std::iter::repeat_n("x", MAX_SCAN_LINES + 5));
+    lines.push(")");
+    let last = lines.len() - 1;
+
+    assert_eq!(pair(&doc("a.txt", &lines, 0, 0)), None);
+    assert_eq!(pair(&doc("a.txt", &lines, 0, last)), None);
+    let doc = doc("a.txt", &lines, 0, 0);
+    assert!(unmatched_in(&doc, 0, 0).is_empty());
+    assert!(unmatched_in(&doc, last, last).is_empty());
+  }
+}
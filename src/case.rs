@@ -0,0 +1,265 @@
+use crate::CursorPos;
+use crate::moves::is_subword_boundary;
+use crate::utils::char_to_byte_idx;
+use crate::word::{ CharClass, char_class };
+
+// Conversión entre convenciones de nombres: parseHTTPResponse2 <-> parse_http_response_2.
+// Se aplica a la selección (cada linea por separado) o a la palabra del cursor.
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Case {
+  Camel,
+  Pascal,
+  Snake,
+  Kebab,
+  ScreamingSnake,
+  Title,
+  Upper,
+  Lower,
+}
+
+// orden de las opciones del menú de Ctrl + K, C
+pub const CASES: [Case; 8] = [
+  Case::Camel,
+  Case::Pascal,
+  Case::Snake,
+  Case::Kebab,
+  Case::ScreamingSnake,
+  Case::Title,
+  Case::Upper,
+  Case::Lower,
+];
+
+// estilos por los que rota Alt + C
+const CYCLE: [Case; 5] = [Case::Camel, Case::Pascal, Case::Snake, Case::ScreamingSnake, Case::Kebab];
+
+impl Case {
+  pub fn name(&self) -> &'static str {
+    match self {
+      Case::Camel => "camelCase",
+      Case::Pascal => "PascalCase",
+      Case::Snake => "snake_case",
+      Case::Kebab => "kebab-case",
+      Case::ScreamingSnake => "SCREAMING_SNAKE_CASE",
+      Case::Title => "Title Case",
+      Case::Upper => "MAYUSCULAS",
+      Case::Lower => "minusculas",
+    }
+  }
+
+  // Convierte un texto; los '_' y '-' del principio y del final se conservan (_privado)
+  pub fn apply(&self, text: &str) -> String {
+    match self {
+      Case::Upper => return text.to_uppercase(),
+      Case::Lower => return text.to_lowercase(),
+      _ => {}
+    }
+
+    let is_edge = |c: char| c == '_' || c == '-';
+    let body = text.trim_start_matches(is_edge);
+    let prefix = &text[..text.len() - body.len()];
+    let body = body.trim_end_matches(is_edge);
+    let suffix = &text[prefix.len() + body.len()..];
+
+    let words = split_words(body);
+    let joined = match self {
+      Case::Camel => words.iter().enumerate()
+        .map(|(i, w)| if i == 0 { w.to_lowercase() } else { capitalize(w) })
+        .collect::<String>(),
+      Case::Pascal => words.iter().map(|w| capitalize(w)).collect::<String>(),
+      Case::Snake => words.iter().map(|w| w.to_lowercase()).collect::<Vec<_>>().join("_"),
+      Case::Kebab => words.iter().map(|w| w.to_lowercase()).collect::<Vec<_>>().join("-"),
+      Case::ScreamingSnake => words.iter().map(|w| w.to_uppercase()).collect::<Vec<_>>().join("_"),
+      Case::Title => words.iter().map(|w| capitalize(w)).collect::<Vec<_>>().join(" "),
+      Case::Upper | Case::Lower => unreachable!(),
+    };
+
+    format!("{}{}{}", prefix, joined, suffix)
+  }
+
+  // estilo en el que parece estar escrito un texto
+  fn detect(text: &str) -> Case {
+    let has_lower = text.chars().any(|c| c.is_lowercase());
+    if text.contains('_') {
+      if has_lower { Case::Snake } else { Case::ScreamingSnake }
+    } else if text.contains('-') {
+      Case::Kebab
+    } else if text.chars().next().is_some_and(|c| c.is_uppercase()) {
+      Case::Pascal
+    } else {
+      Case::Camel
+    }
+  }
+
+  // el estilo siguiente en la rotación de Alt + C
+  fn next_for(text: &str) -> Case {
+    let current = Case::detect(text.trim_matches(|c| c == '_' || c == '-'));
+    let index = CYCLE.iter().position(|c| *c == current).unwrap_or(0);
+    CYCLE[(index + 1) % CYCLE.len()]
+  }
+}
+
+// Palabras de un identificador o frase: corta en lo que no es letra ni número, al pasar
+// de minúscula a mayúscula, entre letras y números, y antes de la última mayúscula de
+// una sigla seguida de minúscula (HTTPResponse -> HTTP, Response)
+pub fn split_words(text: &str) -> Vec<String> {
+  let chars: Vec<char> = text.chars().collect();
+  let mut words = Vec::new();
+  let mut current = String::new();
+
+  for (i, &c) in chars.iter().enumerate() {
+    if !c.is_alphanumeric() {
+      if !current.is_empty() {
+        words.push(std::mem::take(&mut current));
+      }
+      continue;
+    }
+
+    if !current.is_empty() && is_subword_boundary(&chars, i) {
+      words.push(std::mem::take(&mut current));
+    }
+    current.push(c);
+  }
+
+  if !current.is_empty() {
+    words.push(current);
+  }
+  words
+}
+
+fn capitalize(word: &str) -> String {
+  let mut chars = word.chars();
+  match chars.next() {
+    Some(first) => first.to_uppercase().chain(chars.flat_map(|c| c.to_lowercase())).collect(),
+    None => String::new(),
+  }
+}
+
+// '-' también, para tomar entero un identificador kebab-case
+fn is_word_char(c: char) -> bool {
+  c == '-' || char_class(c, &[]) == CharClass::Word
+}
+
+// Aplica `case` a la selección o a la palabra del cursor. Devuelve el estilo usado,
+// o None si no había nada que convertir.
+pub fn convert(lines: &mut [String], cursor: &mut CursorPos, selection: &mut Option<CursorPos>, case: Option<Case>) -> Option<Case> {
+  // tramos (linea, desde, hasta) en chars
+  let ranges: Vec<(usize, usize, usize)> = match *selection {
+    Some(start) if start.y != cursor.y || start.x != cursor.x => {
+      let (first, last) = if (start.y, start.x) <= (cursor.y, cursor.x) { (start, *cursor) } else { (*cursor, start) };
+      (first.y..=last.y)
+        .map(|y| {
+          let from = if y == first.y { first.x } else { 0 };
+          let to = if y == last.y { last.x } else { lines[y].chars().count() };
+          (y, from, to)
+        })
+        .collect()
+    }
+    _ => {
+      let chars: Vec<char> = lines[cursor.y].chars().collect();
+      let mut from = cursor.x.min(chars.len());
+      while from > 0 && is_word_char(chars[from - 1]) {
+        from -= 1;
+      }
+      let mut to = cursor.x.min(chars.len());
+      while to < chars.len() && is_word_char(chars[to]) {
+        to += 1;
+      }
+      vec![(cursor.y, from, to)]
+    }
+  };
+
+  let (y, from, to) = *ranges.iter().find(|(_, from, to)| from < to)?;
+  let first_text: String = lines[y].chars().skip(from).take(to - from).collect();
+  let case = case.unwrap_or_else(|| Case::next_for(&first_text));
+
+  for &(y, from, to) in &ranges {
+    let line = &mut lines[y];
+    let a = char_to_byte_idx(line, from);
+    let b = char_to_byte_idx(line, to);
+    let converted = case.apply(&line[a..b]);
+    let new_to = from + converted.chars().count();
+    line.replace_range(a..b, &converted);
+
+    // el extremo que estaba al final del tramo se mueve con el texto
+    for pos in std::iter::once(&mut *cursor).chain(selection.iter_mut()) {
+      if pos.y == y && pos.x >= to {
+        pos.x = pos.x - to + new_to;
+      } else if pos.y == y && pos.x > from {
+        pos.x = pos.x.min(new_to);
+      }
+    }
+  }
+
+  Some(case)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn split_words_at_humps_acronyms_and_digits() {
+    assert_eq!(split_words("parseHTTPResponse2"), ["parse", "HTTP", "Response", "2"]);
+    assert_eq!(split_words("XMLHttpRequest"), ["XML", "Http", "Request"]);
+    assert_eq!(split_words("snake_case-and kebab"), ["snake", "case", "and", "kebab"]);
+    assert_eq!(split_words("año2024"), ["año", "2024"]);
+    assert!(split_words("__").is_empty());
+  }
+
+  #[test]
+  fn apply_every_case() {
+    let text = "parseHTTPResponse2";
+    let converted: Vec<String> = CASES.iter().map(|case| case.apply(text)).collect();
+    assert_eq!(converted, [
+      "parseHttpResponse2",
+      "ParseHttpResponse2",
+      "parse_http_response_2",
+      "parse-http-response-2",
+      "PARSE_HTTP_RESPONSE_2",
+      "Parse Http Response 2",
+      "PARSEHTTPRESPONSE2",
+      "parsehttpresponse2",
+    ]);
+  }
+
+  #[test]
+  fn apply_keeps_edge_underscores() {
+    assert_eq!(Case::Camel.apply("__private_value__"), "__privateValue__");
+    assert_eq!(Case::Snake.apply("-x-"), "-x-");
+  }
+
+  #[test]
+  fn alt_c_cycles_styles() {
+    let mut text = "fooBar".to_string();
+    let mut seen = Vec::new();
+    for _ in 0..5 {
+      text = Case::next_for(&text).apply(&text);
+      seen.push(text.clone());
+    }
+    assert_eq!(seen, ["FooBar", "foo_bar", "FOO_BAR", "foo-bar", "fooBar"]);
+  }
+
+  #[test]
+  fn convert_word_under_cursor() {
+    let mut lines = vec!["let my-value = 1;".to_string()];
+    let mut cursor = CursorPos { x: 6, y: 0 };
+    let mut selection = None;
+    assert!(convert(&mut lines, &mut cursor, &mut selection, Some(Case::ScreamingSnake)) == Some(Case::ScreamingSnake));
+    assert_eq!(lines[0], "let MY_VALUE = 1;");
+    assert_eq!(cursor.x, 6);
+
+    let mut cursor = CursorPos { x: 13, y: 0 };
+    assert!(convert(&mut lines, &mut cursor, &mut selection, None).is_none());
+  }
+
+  #[test]
+  fn convert_selection_over_lines() {
+    let mut lines = vec!["a fooBar".to_string(), "bazQux b".to_string()];
+    let mut cursor = CursorPos { x: 6, y: 1 };
+    let mut selection = Some(CursorPos { x: 2, y: 0 });
+    convert(&mut lines, &mut cursor, &mut selection, Some(Case::Snake));
+    assert_eq!(lines, ["a foo_bar", "baz_qux b"]);
+    assert_eq!((cursor.x, selection.map(|s| s.x)), (7, Some(2)));
+  }
+}
@@ -0,0 +1,147 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::file::app_dir;
+use crate::indent::Indent;
+use crate::theme::Theme;
+
+// Configuracion del usuario, leida de ~/.camelCaseEditor/config.toml
+// (o del archivo pasado con --config). Formato: una "clave = valor" por linea,
+// '#' para comentarios.
+#[derive(Clone)]
+pub struct Config {
+  pub indent_size: usize,         // espacios por nivel de indentacion
+  pub use_tabs: bool,             // indentar con tabs en vez de espacios
+  pub tab_width: usize,           // columnas entre tab stops al dibujar
+  pub auto_pairs: bool,           // auto-cierre de { [ ( ' "
+  pub line_numbers: LineNumbers,  // numeros de linea en el margen izquierdo
+  pub change_markers: bool,       // marcar lineas cambiadas desde el ultimo guardado
+  pub theme: Theme,               // colores de la interfaz y del resaltado
+  pub word_chars: String,         // puntuación que une palabras en todos los archivos
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LineNumbers {
+  Absolute,
+  Relative, // distancia a la linea del cursor (la del cursor muestra su numero)
+  Off,
+}
+
+impl LineNumbers {
+  pub fn next(self) -> Self {
+    match self {
+      LineNumbers::Absolute => LineNumbers::Relative,
+      LineNumbers::Relative => LineNumbers::Off,
+      LineNumbers::Off => LineNumbers::Absolute,
+    }
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      LineNumbers::Absolute => "absolutos",
+      LineNumbers::Relative => "relativos",
+      LineNumbers::Off => "ocultos",
+    }
+  }
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    Config {
+      indent_size: 2,
+      use_tabs: false,
+      tab_width: 4,
+      auto_pairs: true,
+      line_numbers: LineNumbers::Absolute,
+      change_markers: true,
+      theme: Theme::load("dark").unwrap_or_default(),
+      word_chars: String::new(),
+    }
+  }
+}
+
+impl Config {
+  pub fn default_path() -> PathBuf {
+    app_dir().join("config.toml")
+  }
+
+  // `path` = None usa el archivo por defecto, que puede no existir.
+  // Devuelve la configuracion y los avisos de lineas que no se entendieron.
+  pub fn load(path: Option<&str>) -> Result<(Config, Vec<String>), String> {
+    let content = match path {
+      Some(path) => fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?,
+      None => fs::read_to_string(Self::default_path()).unwrap_or_default(),
+    };
+
+    let mut config = Config::default();
+    let mut warnings = Vec::new();
+
+    for (n, raw_line) in content.lines().enumerate() {
+      let line = raw_line.split('#').next().unwrap_or("").trim();
+      if line.is_empty() || line.starts_with('[') {
+        continue;
+      }
+
+      let Some((key, value)) = line.split_once('=') else {
+        warnings.push(format!("linea {}: se esperaba clave = valor", n + 1));
+        continue;
+      };
+      let key = key.trim();
+      let value = value.trim().trim_matches('"');
+
+      if let Err(e) = config.set(key, value) {
+        warnings.push(format!("linea {}: {}", n + 1, e));
+      }
+    }
+
+    Ok((config, warnings))
+  }
+
+  fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+    match key {
+      "indent_size" => self.indent_size = parse_number(value)?.max(1),
+      "indent_style" => self.use_tabs = parse_indent_style(value)?,
+      "tab_width" => self.tab_width = parse_number(value)?.max(1),
+      "auto_pairs" => self.auto_pairs = parse_bool(value)?,
+      "line_numbers" => self.line_numbers = parse_line_numbers(value)?,
+      "change_markers" => self.change_markers = parse_bool(value)?,
+      "theme" => self.theme = Theme::load(value)?,
+      "word_chars" => self.word_chars = value.chars().filter(|c| !c.is_whitespace()).collect(),
+      _ => return Err(format!("clave desconocida '{}'", key)),
+    }
+    Ok(())
+  }
+
+  pub fn indent(&self) -> Indent {
+    Indent { use_tabs: self.use_tabs, size: self.indent_size }
+  }
+}
+
+fn parse_number(value: &str) -> Result<usize, String> {
+  value.parse().map_err(|_| format!("numero invalido '{}'", value))
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+  match value {
+    "true" | "si" | "1" => Ok(true),
+    "false" | "no" | "0" => Ok(false),
+    _ => Err(format!("se esperaba true o false, no '{}'", value)),
+  }
+}
+
+fn parse_indent_style(value: &str) -> Result<bool, String> {
+  match value {
+    "tabs" | "tab" => Ok(true),
+    "spaces" | "space" => Ok(false),
+    _ => Err(format!("se esperaba spaces o tabs, no '{}'", value)),
+  }
+}
+
+fn parse_line_numbers(value: &str) -> Result<LineNumbers, String> {
+  match value {
+    "absolute" | "true" => Ok(LineNumbers::Absolute),
+    "relative" => Ok(LineNumbers::Relative),
+    "none" | "false" => Ok(LineNumbers::Off),
+    _ => Err(format!("se esperaba absolute, relative o none, no '{}'", value)),
+  }
+}
@@ -1,5 +1,15 @@
-#[derive(Copy, Clone)]
+use crate::utils::line_len_chars;
+
+#[derive(Copy, Clone, PartialEq)]
 pub struct CursorPos {
   pub(crate) x: usize,
   pub(crate) y: usize,
-}
\ No newline at end of file
+}
+
+impl CursorPos {
+  // la misma posición, dentro del texto (por si se achicó desde que se guardó)
+  pub fn clamped(self, lines: &[String]) -> CursorPos {
+    let y = self.y.min(lines.len() - 1);
+    CursorPos { x: self.x.min(line_len_chars(&lines[y])), y }
+  }
+}
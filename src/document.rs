@@ -0,0 +1,363 @@
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{ Hash, Hasher };
+use std::io;
+use std::path::Path;
+
+use crate::block::Block;
+use crate::brackets::UnmatchedCache;
+use crate::cursor::CursorPos;
+use crate::config::Config;
+use crate::editorconfig::EditorConfig;
+use crate::file::{ FileFormat, open_file };
+use crate::indent::{ self, Indent };
+use crate::select::Expansion;
+use crate::selection::Selection;
+use crate::syntax::{ self, HighlightCache, Syntax };
+use crate::utils::{ grapheme_floor, line_len_chars };
+
+// celdas máximas de la tabla de diferencias (filas cambiadas x filas guardadas)
+const MAX_DIFF_CELLS: usize = 1_000_000;
+
+// Estado de una linea respecto del ultimo guardado
+#[derive(Clone, Copy, PartialEq)]
+pub enum LineChange {
+  Unchanged,
+  Modified,
+  Added,
+  DeletedAbove, // se borraron lineas justo antes de esta
+}
+
+// Un archivo abierto: texto, cursor, selección y scroll propios
+pub struct Document {
+  pub lines: Vec<String>,
+  pub saved: Vec<String>, // contenido al abrir/guardar, para saber si hay cambios
+  pub cursor: CursorPos,
+  pub selection_start: Option<CursorPos>,
+  pub others: Vec<Selection>, // cursores además del principal (ver multicursor.rs)
+  pub block: Option<Block>,   // selección en bloque que formó esos cursores
+  pub expansion: Expansion,   // pasos de Alt + = para volver con Alt + -
+  pub scroll_x: usize,
+  pub scroll_y: usize,
+  pub path: Option<String>,
+  pub readonly: bool,
+  pub syntax: Option<Syntax>,
+  pub highlight_cache: RefCell<HighlightCache>,
+  changes_cache: RefCell<Option<(u64, Vec<LineChange>)>>, // line_changes() y el hash del texto con que se calculó
+  pub brackets_cache: RefCell<UnmatchedCache>,
+  pub indent: Option<Indent>, // detectada al abrir o elegida por el usuario; None = la de la config
+  pub format: FileFormat,     // fin de linea, codificacion y salto final al guardar
+  pub editorconfig: EditorConfig,
+}
+
+// Lo que se escribe al guardar (ver Document::prepare_save)
+pub struct SavePlan {
+  editorconfig: EditorConfig,
+  pub format: FileFormat,
+  trimmed: Option<Vec<String>>, // las lineas sin espacios al final, si el .editorconfig lo pide
+}
+
+// fin de linea, codificacion y salto final del .editorconfig, si los define
+fn file_format(format: FileFormat, config: &EditorConfig) -> FileFormat {
+  FileFormat {
+    line_ending: config.line_ending.unwrap_or(format.line_ending),
+    charset: config.charset.unwrap_or(format.charset),
+    final_newline: config.final_newline.unwrap_or(format.final_newline),
+  }
+}
+
+impl Document {
+  pub fn new() -> Self {
+    Document {
+      lines: vec![String::new()],
+      saved: vec![String::new()],
+      cursor: CursorPos { x: 0, y: 0 },
+      selection_start: None,
+      others: Vec::new(),
+      block: None,
+      expansion: Expansion::default(),
+      scroll_x: 0,
+      scroll_y: 0,
+      path: None,
+      readonly: false,
+      syntax: None,
+      highlight_cache: RefCell::default(),
+      changes_cache: RefCell::default(),
+      brackets_cache: RefCell::default(),
+      indent: None,
+      format: FileFormat::default(),
+      editorconfig: EditorConfig::default(),
+    }
+  }
+
+  // si el archivo no existe se abre vacío y se crea al guardar
+  pub fn open(path: &str, config: &Config) -> io::Result<Self> {
+    let mut doc = Document::new();
+    doc.editorconfig = EditorConfig::resolve(path);
+    if Path::new(path).exists() {
+      (doc.lines, doc.format) = open_file(path, doc.editorconfig.charset)?;
+      doc.saved = doc.lines.clone();
+    }
+    doc.apply_file_format();
+    doc.path = Some(path.to_string());
+    doc.detect_syntax();
+    doc.indent = indent::detect(&doc.lines, doc.tab_width(config));
+    Ok(doc)
+  }
+
+  // indentación para Enter, Tab y Shift + Tab; .editorconfig manda sobre lo detectado
+  pub fn indent(&self, config: &Config) -> Indent {
+    let base = self.indent.unwrap_or(config.indent());
+    Indent {
+      use_tabs: self.editorconfig.use_tabs.unwrap_or(base.use_tabs),
+      size: self.editorconfig.indent_size.unwrap_or(base.size),
+    }
+  }
+
+  // la indentación elegida a mano deja de lado la del .editorconfig
+  pub fn set_indent(&mut self, indent: Indent) {
+    self.indent = Some(indent);
+    self.editorconfig.use_tabs = None;
+    self.editorconfig.indent_size = None;
+  }
+
+  pub fn tab_width(&self, config: &Config) -> usize {
+    self.editorconfig.tab_width.unwrap_or(config.tab_width)
+  }
+
+  // puntuación que une palabras al moverse con Ctrl + Flechas: la del lenguaje más la
+  // de la configuración
+  pub fn word_chars(&self, config: &Config) -> Vec<char> {
+    let language = self.syntax.map(|s| s.word_chars()).unwrap_or(&[]);
+    language.iter().copied().chain(config.word_chars.chars()).collect()
+  }
+
+  // columna de la guía de largo máximo de linea
+  pub fn max_line_length(&self) -> Option<usize> {
+    self.editorconfig.max_line_length
+  }
+
+  fn apply_file_format(&mut self) {
+    self.format = file_format(self.format, &self.editorconfig);
+  }
+
+  // Antes de guardar en `path`: vuelve a leer el .editorconfig (la ruta puede ser otra)
+  // y arma lo que se va a escribir. El documento no cambia hasta que se guarde bien.
+  pub fn prepare_save(&self, path: &str) -> SavePlan {
+    let editorconfig = EditorConfig::resolve(path);
+    let format = file_format(self.format, &editorconfig);
+    let trimmed = (editorconfig.trim_trailing_whitespace == Some(true))
+      .then(|| self.lines.iter().map(|line| line.trim_end().to_string()).collect());
+    SavePlan { editorconfig, format, trimmed }
+  }
+
+  // lineas a escribir según el plan
+  pub fn lines_to_save<'a>(&'a self, plan: &'a SavePlan) -> &'a [String] {
+    plan.trimmed.as_deref().unwrap_or(&self.lines)
+  }
+
+  // Después de guardar bien: el documento queda como lo que se escribió
+  pub fn mark_saved(&mut self, path: String, plan: SavePlan) {
+    self.editorconfig = plan.editorconfig;
+    self.format = plan.format;
+    if let Some(trimmed) = plan.trimmed {
+      self.lines = trimmed;
+      let clamp = |pos: &mut CursorPos, lines: &[String]| pos.x = pos.x.min(line_len_chars(&lines[pos.y]));
+      clamp(&mut self.cursor, &self.lines);
+      if let Some(start) = &mut self.selection_start {
+        clamp(start, &self.lines);
+      }
+      for other in &mut self.others {
+        clamp(&mut other.cursor, &self.lines);
+        if let Some(start) = &mut other.start {
+          clamp(start, &self.lines);
+        }
+      }
+    }
+
+    self.path = Some(path);
+    self.saved = self.lines.clone();
+    self.changes_cache = RefCell::default();
+    self.detect_syntax();
+  }
+
+  pub fn name(&self) -> String {
+    match &self.path {
+      Some(path) => Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.clone()),
+      None => "[sin nombre]".to_string(),
+    }
+  }
+
+  pub fn is_modified(&self) -> bool {
+    self.lines != self.saved
+  }
+
+  // Cambios de cada linea desde el ultimo guardado. Se recalculan solo si el texto
+  // cambió desde el último llamado (se dibuja en cada tecla, aunque no se edite).
+  pub fn line_changes(&self) -> Vec<LineChange> {
+    let hash = self.text_hash();
+    let mut cache = self.changes_cache.borrow_mut();
+    match &*cache {
+      Some((cached, changes)) if *cached == hash => changes.clone(),
+      _ => {
+        let changes = self.diff_saved();
+        *cache = Some((hash, changes.clone()));
+        changes
+      }
+    }
+  }
+
+  // para saber si el texto cambió desde que se llenó un cache
+  pub fn text_hash(&self) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    self.lines.hash(&mut hasher);
+    hasher.finish()
+  }
+
+  // Se saltean el principio y el final comunes y lo del medio se compara con LCS (si es
+  // muy grande, linea a linea)
+  fn diff_saved(&self) -> Vec<LineChange> {
+    let mut changes = vec![LineChange::Unchanged; self.lines.len()];
+
+    let prefix = self.lines.iter().zip(&self.saved).take_while(|(a, b)| a == b).count();
+    let max_suffix = self.lines.len().min(self.saved.len()) - prefix;
+    let suffix = self.lines.iter().rev().zip(self.saved.iter().rev())
+      .take(max_suffix)
+      .take_while(|(a, b)| a == b)
+      .count();
+
+    let current = &self.lines[prefix..self.lines.len() - suffix];
+    let saved = &self.saved[prefix..self.saved.len() - suffix];
+    let (n, m) = (current.len(), saved.len());
+
+    // lcs[i][j] = largo de la subsecuencia común más larga de current[i..] y saved[j..]
+    let lcs = if n * m <= MAX_DIFF_CELLS {
+      let mut table = vec![0u32; (n + 1) * (m + 1)];
+      for i in (0..n).rev() {
+        for j in (0..m).rev() {
+          table[i * (m + 1) + j] = if current[i] == saved[j] {
+            table[(i + 1) * (m + 1) + j + 1] + 1
+          } else {
+            table[(i + 1) * (m + 1) + j].max(table[i * (m + 1) + j + 1])
+          };
+        }
+      }
+      Some(table)
+    } else {
+      None
+    };
+    let at = |i: usize, j: usize| lcs.as_ref().map_or(0, |t| t[i * (m + 1) + j]);
+
+    // lineas guardadas que faltan y todavía no se atribuyeron a una modificación
+    let mut pending_removed = 0;
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+      if i < n && j < m && lcs.is_some() && current[i] == saved[j] && at(i, j) == at(i + 1, j + 1) + 1 {
+        if pending_removed > 0 {
+          changes[prefix + i] = LineChange::DeletedAbove;
+          pending_removed = 0;
+        }
+        i += 1;
+        j += 1;
+      } else if j < m && (i == n || (lcs.is_some() && at(i, j + 1) >= at(i + 1, j))) {
+        pending_removed += 1;
+        j += 1;
+      } else if lcs.is_none() && j < m && i < n {
+        // sin tabla: se emparejan en orden
+        changes[prefix + i] = LineChange::Modified;
+        i += 1;
+        j += 1;
+      } else {
+        changes[prefix + i] = if pending_removed > 0 {
+          pending_removed -= 1;
+          LineChange::Modified
+        } else {
+          LineChange::Added
+        };
+        i += 1;
+      }
+    }
+    if pending_removed > 0 && prefix + n < changes.len() {
+      changes[prefix + n] = LineChange::DeletedAbove;
+    }
+
+    changes
+  }
+
+  // documento sin archivo ni texto: se puede reemplazar al abrir otro
+  pub fn is_blank(&self) -> bool {
+    self.path.is_none() && self.lines.len() == 1 && self.lines[0].is_empty()
+  }
+
+  // lenguaje por la extension del archivo o el "#!" de la primera linea
+  pub fn detect_syntax(&mut self) {
+    self.syntax = syntax::detect(self.path.as_deref(), self.lines.first().map(|l| l.as_str()));
+    self.highlight_cache = RefCell::default();
+    self.brackets_cache = RefCell::default();
+  }
+
+  // mueve el cursor a linea/columna (base 0), ajustando a los limites del texto
+  pub fn go_to(&mut self, line: usize, col: usize) {
+    self.cursor.y = line.min(self.lines.len() - 1);
+    let line = &self.lines[self.cursor.y];
+    self.cursor.x = grapheme_floor(line, col.min(line_len_chars(line)));
+    self.selection_start = None;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn doc(saved: &[&str], lines: &[&str]) -> Document {
+    let mut doc = Document::new();
+    doc.saved = saved.iter().map(|l| l.to_string()).collect();
+    doc.lines = lines.iter().map(|l| l.to_string()).collect();
+    doc
+  }
+
+  // una letra por linea: . igual, M modificada, A agregada, D con lineas borradas arriba
+  fn marks(doc: &Document) -> String {
+    doc.line_changes().iter()
+      .map(|change| match change {
+        LineChange::Unchanged => '.',
+        LineChange::Modified => 'M',
+        LineChange::Added => 'A',
+        LineChange::DeletedAbove => 'D',
+      })
+      .collect()
+  }
+
+  #[test]
+  fn line_changes_unchanged() {
+    assert_eq!(marks(&doc(&["a", "b"], &["a", "b"])), "..");
+  }
+
+  #[test]
+  fn line_changes_added_and_modified() {
+    assert_eq!(marks(&doc(&["a", "b", "c"], &["a", "x", "b", "c"])), ".A..");
+    assert_eq!(marks(&doc(&["a", "b", "c"], &["a", "B", "c"])), ".M.");
+    assert_eq!(marks(&doc(&["a"], &["a", "b", "c"])), ".AA");
+  }
+
+  #[test]
+  fn line_changes_deleted() {
+    assert_eq!(marks(&doc(&["a", "b", "c"], &["a", "c"])), ".D");
+    assert_eq!(marks(&doc(&["a", "b", "c", "d"], &["a", "d"])), ".D");
+    // al borrar las últimas no queda linea donde marcarlo
+    assert_eq!(marks(&doc(&["a", "b"], &["a"])), ".");
+  }
+
+  #[test]
+  fn line_changes_follow_edits() {
+    let mut doc = doc(&["a", "b"], &["a", "b"]);
+    assert_eq!(marks(&doc), "..");
+    doc.lines[1].push('!');
+    assert_eq!(marks(&doc), ".M");
+    doc.lines[1].pop();
+    assert_eq!(marks(&doc), "..");
+  }
+}
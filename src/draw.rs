@@ -2,151 +2,393 @@ use std::io::{ Write, stdout };
 use crossterm::cursor::{ MoveTo };
 use crossterm::execute;
 use crossterm::terminal::{ Clear, ClearType };
-use crossterm::style::{ Color, ResetColor, SetBackgroundColor, SetForegroundColor };
+use unicode_width::UnicodeWidthStr;
 
 use crate::CursorPos;
+use crate::brackets::{ self, BracketMatch };
+use crate::Document;
+use crate::config::{ Config, LineNumbers };
+use crate::document::LineChange;
+use crate::layout::{ Layout, Rect, view_text_rect };
+use crate::panes::{ Panes, View };
 use crate::PopupMode;
-use crate::popup::draw_popup;
+use crate::input::TextInput;
+use crate::message::{ MessageLog, Severity };
+use crate::syntax::Scope;
+use crate::popup::{ draw_popup, fit };
+use crate::theme::{ Theme, reset };
+use crate::utils::{ display_col, grapheme_width, graphemes, line_len_chars };
 
 pub fn draw(
-  lines: &Vec<String>,
-  cursor: &CursorPos,
-  selection_start: Option<CursorPos>,
-  scroll_x: usize,
-  scroll_y: usize,
-  ui_lines: usize,
+  docs: &[Document],
+  panes: &Panes,
   popup: &Option<PopupMode>,
-  popup_input: &str,
+  popup_input: &TextInput,
+  messages: &MessageLog,
+  config: &Config,
   path: &str
 ) -> Result<(), Box<dyn std::error::Error>> {
-  let (term_width, term_height) = crossterm::terminal::size()?;
-  let term_width = term_width as usize;
-  let term_height = term_height as usize;
+  let view = panes.view();
+  let doc = &docs[view.doc];
+  let cursor = &doc.cursor;
+
+  let layout = Layout::current()?;
+  let theme = &config.theme;
 
   execute!(stdout(), Clear(ClearType::All))?;
 
-  execute!(stdout(), MoveTo(0, 0))?;
+  if let Some(title_row) = layout.title {
+    execute!(stdout(), MoveTo(0, title_row.y as u16))?;
+
+    let text = format!("camelCase Editor v{}  -  ctrl + H ayuda", env!("CARGO_PKG_VERSION"));
+    let left_padding: usize = (title_row.width.saturating_sub(text.len())) / 2;
+
+    theme.title.apply()?;
+    print!("{}", fit(&format!("{}{}", " ".repeat(left_padding), text), title_row.width));
+    reset()?;
+  }
+
+  // pestañas de los archivos abiertos, debajo del título
+  if let Some(tabs_row) = layout.tabs {
+    execute!(stdout(), MoveTo(0, tabs_row.y as u16))?;
+    draw_tabs(docs, view.doc, tabs_row.width, theme)?;
+  }
+
+  // paneles: cada vista en su rectángulo, con separador vertical entre columnas
+  let area = layout.text;
+  let mut cursor_screen = None;
+
+  for (view_idx, rect) in panes.layout(area) {
+    let focused = view_idx == panes.focused;
+    let pos = draw_view(&docs[panes.views[view_idx].doc], &panes.views[view_idx], rect, panes.is_split(), focused, config)?;
+    if focused {
+      cursor_screen = pos;
+    }
+
+    if rect.x + rect.width < area.x + area.width {
+      theme.border.apply()?;
+      for y in rect.y..rect.y + rect.height {
+        execute!(stdout(), MoveTo((rect.x + rect.width) as u16, y as u16))?;
+        print!("│");
+      }
+      reset()?;
+    }
+  }
+
+  if let Some(status_row) = layout.status {
+    execute!(stdout(), MoveTo(0, status_row.y as u16))?;
+    theme.status.apply()?;
+
+    let readonly_tag = if doc.readonly { " [solo lectura]" } else { "" };
+
+    let language = doc.syntax.map_or("Texto", |syntax| syntax.name());
+
+    let indent = doc.indent(config).name();
+
+    // columna de pantalla: un tab o un caracter ancho cuentan lo que ocupan
+    let column = display_col(&doc.lines[cursor.y], cursor.x, doc.tab_width(config)) + 1;
+
+    let status = format!(" Linea {} | Columna {} | Scroll X:{} Y:{} | {}{} | {} | {} | Path {}", cursor.y + 1, column, view.scroll_x, view.scroll_y, doc.name(), readonly_tag, language, indent, path);
+
+    print!("{}", fit(&status, status_row.width));
+
+    reset()?;
+  }
+
+  // barra de mensajes; sin lugar propio se muestra encima del estado
+  if let Some(message) = messages.current()
+    && let Some(row) = layout.message.or(layout.status) {
+    execute!(stdout(), MoveTo(0, row.y as u16))?;
+    let style = match message.severity {
+      Severity::Info => theme.message_info,
+      Severity::Warning => theme.message_warning,
+      Severity::Error => theme.message_error,
+    };
+    style.apply()?;
+    print!("{}", fit(&format!(" {}", message.text), row.width));
+    reset()?;
+  }
+
+  if let Some(mode) = popup {
+    let popup_data = mode.to_popup(popup_input, path, &layout);
+    if let Ok(Some((x, y))) = draw_popup(&popup_data, &layout, theme) {
+      execute!(stdout(), MoveTo(x, y))?;
+    }
+  }
+
+  if popup.is_none()
+    && let Some((x, y)) = cursor_screen {
+    execute!(stdout(), MoveTo(x, y))?;
+  }
+
+  stdout().flush()?;
+  Ok(())
+}
+
+// Dibuja una vista en su rectángulo; devuelve la posición del cursor en pantalla
+fn draw_view(doc: &Document, view: &View, rect: Rect, split: bool, focused: bool, config: &Config) -> std::io::Result<Option<(u16, u16)>> {
+  let lines = &doc.lines;
+  // los paneles no enfocados muestran el cursor y la selección de su vista
+  let (cursor, selection_start) = if focused { (&doc.cursor, doc.selection_start) } else { (&view.cursor, view.selection_start) };
+  let others = if focused { doc.others.as_slice() } else { &[] };
+  let scroll_x = view.scroll_x;
+  let scroll_y = view.scroll_y;
+  let theme = &config.theme;
+  let tab_width = doc.tab_width(config);
+  let ruler = doc.max_line_length();
+
+  // título del panel: resaltado si es el enfocado
+  if split && rect.height > 1 {
+    execute!(stdout(), MoveTo(rect.x as u16, rect.y as u16))?;
+    let style = if focused { theme.pane_focused } else { theme.pane_inactive };
+    style.apply()?;
+    let modified = if doc.is_modified() { "*" } else { "" };
+    let title: String = format!(" {}{}", doc.name(), modified).chars().take(rect.width).collect();
+    print!("{:<width$}", title, width = rect.width);
+    reset()?;
+  }
 
-  let text = format!("camelCase Editor v{}  -  ctrl + H ayuda", env!("CARGO_PKG_VERSION"));
-  let left_padding: usize = (term_width.saturating_sub(text.len())) / 2;
-  let right_padding: usize = term_width.saturating_sub(text.len()).saturating_sub(left_padding);
+  let text_rect = view_text_rect(rect, split);
+  let visible = text_rect.height;
 
-  execute!(stdout(),SetForegroundColor(Color::Black),SetBackgroundColor(Color::Red))?;
-  print!("{}{}{}", " ".repeat(left_padding), text, " ".repeat(right_padding));
-  execute!(stdout(), ResetColor)?;
+  // margen: marca de cambios + numero de linea; si no entra se omite
+  let gutter = gutter_width(doc, config);
+  let gutter = if gutter + 2 < text_rect.width { gutter } else { 0 };
+  let usable_width = text_rect.width.saturating_sub(2 + gutter);
+  let changes = if config.change_markers && gutter > 0 { doc.line_changes() } else { Vec::new() };
 
-  // línea vacía debajo
-  execute!(stdout(), MoveTo(0, 1))?;
-  print!("");
+  // scopes de las lineas visibles
+  let highlighted = match doc.syntax {
+    Some(syntax) => syntax.highlight_lines(lines, scroll_y, visible, &mut doc.highlight_cache.borrow_mut()),
+    None => Vec::new(),
+  };
 
-  let visible = term_height.saturating_sub(ui_lines + 1);
-  let usable_width = term_width.saturating_sub(2);
+  // paréntesis junto al cursor y su pareja (o marcado si no tiene), y los visibles sin pareja
+  let bracket = if focused { brackets::at_cursor(doc) } else { None };
+  let unmatched = if visible > 0 { brackets::unmatched(doc, scroll_y, scroll_y + visible - 1) } else { Vec::new() };
+
+  // sin lugar para los indicadores < > no se dibuja el texto
+  if text_rect.width < 2 {
+    return Ok(None);
+  }
 
   for screen_y in 0..visible {
     let line_idx = scroll_y + screen_y;
-    let draw_y = ui_lines + screen_y;
+    let draw_y = text_rect.y + screen_y;
 
-    execute!(stdout(), MoveTo(0, draw_y as u16))?;
+    execute!(stdout(), MoveTo(text_rect.x as u16, draw_y as u16))?;
 
     if line_idx >= lines.len() {
-      print!("{}{}", " ".repeat(1 + usable_width), " ");
+      print!("{} ", " ".repeat(gutter + 1 + usable_width));
       continue;
     }
 
+    if gutter > 0 {
+      draw_gutter(line_idx, cursor.y, changes.get(line_idx).copied(), gutter, config)?;
+    }
     let line = &lines[line_idx];
 
-    let chars: Vec<char> = line.chars().collect();
-    let total_chars = chars.len();
+    // una celda por columna de pantalla: los tabs se expanden hasta el próximo tab stop y
+    // los caracteres anchos (中, emoji) dejan celdas None detrás de la primera
+    let mut cells: Vec<(usize, Option<String>)> = Vec::new();
+    for (char_idx, g) in graphemes(line) {
+      let width = grapheme_width(g, cells.len(), tab_width);
+      if g == "\t" {
+        cells.extend(std::iter::repeat_n((char_idx, Some(" ".to_string())), width));
+      } else {
+        cells.push((char_idx, Some(visible_grapheme(g))));
+        cells.extend(std::iter::repeat_n((char_idx, None), width - 1));
+      }
+    }
+    let total_cols = cells.len();
+
+    // cursores además del principal en esta linea (el de la terminal es el principal)
+    let other_cursors: Vec<usize> = others.iter().filter(|o| o.cursor.y == line_idx).map(|o| o.cursor.x).collect();
+
+    let scopes = highlighted.get(screen_y).map(|s| s.as_slice()).unwrap_or_default();
 
     let left_hidden = scroll_x > 0;
-    let right_hidden = scroll_x + usable_width < total_chars;
+    let right_hidden = scroll_x + usable_width < total_cols;
 
     // indicador izquierda
     if left_hidden {
-      execute!(stdout(),SetForegroundColor(Color::Black),SetBackgroundColor(Color::DarkRed))?;
-      print!("{}", '<' );
-      execute!(stdout(), ResetColor)?;
+      theme.scroll_marker.apply()?;
+      print!("<");
+      reset()?;
     }
     else{
-      print!("{}", ' ' );
+      print!(" ");
     }
 
     // contenido visible
-    for i in 0..usable_width {
-      let char_idx = scroll_x + i;
-
-      if char_idx >= total_chars {
-        print!(" ");
+    let mut i = 0;
+    while i < usable_width {
+      let Some((char_idx, text)) = cells.get(scroll_x + i) else {
+        // cursor al final de la linea
+        if scroll_x + i == total_cols && other_cursors.contains(&line_len_chars(line)) {
+          theme.cursor.apply()?;
+          print!(" ");
+          reset()?;
+        // guía del largo máximo de linea (.editorconfig max_line_length)
+        } else if ruler == Some(scroll_x + i) {
+          theme.ruler.apply()?;
+          print!("│");
+          reset()?;
+        } else {
+          print!(" ");
+        }
+        i += 1;
         continue;
-      }
+      };
+      let char_idx = *char_idx;
 
-      let c = chars[char_idx];
+      // un caracter ancho cortado por el borde izquierdo o derecho se dibuja con espacios
+      let width = 1 + cells[scroll_x + i + 1..].iter().take_while(|(_, t)| t.is_none()).count();
+      let shown = match text {
+        Some(text) if i + width <= usable_width => text.clone(),
+        Some(_) => " ".repeat(usable_width - i),
+        None => " ".to_string(),
+      };
+      i += if text.is_some() { width.min(usable_width - i) } else { 1 };
 
       let pos = CursorPos { x: char_idx, y: line_idx };
 
-      if let Some(start) = selection_start.as_ref() {
-        if crate::selection::is_selected(pos, *start, *cursor) {
-          execute!(stdout(),SetForegroundColor(Color::Black),SetBackgroundColor(Color::DarkGrey))?;
-          print!("{}", c);
-          execute!(stdout(), ResetColor)?;
-        } else {
-          print!("{}", c);
+      let selected = selection_start.as_ref()
+        .is_some_and(|start| crate::selection::is_selected(pos, *start, *cursor))
+        || others.iter().any(|o| o.start.is_some_and(|start| crate::selection::is_selected(pos, start, o.cursor)));
+      let scope = scopes.get(char_idx).copied().unwrap_or(Scope::Plain);
+      let style = if other_cursors.contains(&char_idx) && text.is_some() {
+        theme.cursor
+      } else if selected {
+        theme.selection
+      } else {
+        match bracket {
+          Some(BracketMatch::Matched(a, b)) if pos == a || pos == b => theme.bracket_match,
+          Some(BracketMatch::Unmatched(a)) if pos == a => theme.bracket_unmatched,
+          _ if unmatched.contains(&pos) => theme.bracket_unmatched,
+          _ => theme.scope(scope),
         }
+      };
+
+      if style.is_plain() {
+        print!("{}", shown);
       } else {
-        print!("{}", c);
+        style.apply()?;
+        print!("{}", shown);
+        reset()?;
       }
     }
 
     // indicador derecha
     if right_hidden {
-      execute!(stdout(),SetForegroundColor(Color::Black),SetBackgroundColor(Color::DarkRed))?;
-      print!("{}", '>' );
-      execute!(stdout(), ResetColor)?;
+      theme.scroll_marker.apply()?;
+      print!(">");
+      reset()?;
     }
     else{
-      print!("{}", ' ' );
+      print!(" ");
     }
   }
 
-  let status_y = term_height - 1;
-
-  // limpiar toda la linea del status
-  execute!(stdout(), MoveTo(0, status_y as u16))?;
-  print!("{}", " ".repeat(term_width));
-  execute!(stdout(), MoveTo(0, status_y as u16))?;
-  
-  execute!(stdout(), SetForegroundColor(Color::Black), SetBackgroundColor(Color::Red))?;
-  
-  let status = format!(" Linea {} | Columna {} | Scroll X:{} Y:{} | Path {}", cursor.y + 1, cursor.x + 1, scroll_x, scroll_y, path);
-  
-  let padded = format!("{:<width$}", status, width = term_width);
-  print!("{}", padded);
-  
-  execute!(stdout(), ResetColor)?;
-
-  if let Some(mode) = popup {
-    let popup_data = mode.to_popup(popup_input);
-    let _ = draw_popup(&popup_data, term_width, term_height);
+  if !focused || text_rect.height == 0 {
+    return Ok(None);
   }
 
-  if popup.is_none() {
-    let screen_y = cursor.y.saturating_sub(scroll_y) + ui_lines;
+  let screen_y = cursor.y.saturating_sub(scroll_y).min(text_rect.height - 1) + text_rect.y;
+
+  // Cursor visible dentro del viewport horizontal (+1 por el "<", + el margen de numeros)
+  let screen_x = display_col(&lines[cursor.y], cursor.x, tab_width)
+    .saturating_sub(scroll_x)
+    .saturating_add(1 + gutter) // margen izquierdo
+    .min(text_rect.width.saturating_sub(1))
+    + text_rect.x;
 
-    // Cursor visible dentro del viewport horizontal (+1 por el "<")
-    let screen_x = cursor
-      .x
-      .saturating_sub(scroll_x)
-      .saturating_add(1) // margen izquierdo
-      .min(term_width.saturating_sub(1));
+  Ok(Some((screen_x as u16, screen_y as u16)))
+}
 
-    execute!(stdout(), MoveTo(screen_x as u16, screen_y as u16))?;
+// Lo que se imprime de un cluster: los caracteres de control y los que no ocupan lugar
+// (un acento suelto al principio de la linea, espacios de ancho cero) no deben correr
+// el resto de la linea en la terminal
+fn visible_grapheme(g: &str) -> String {
+  if g.chars().any(|c| c.is_control()) {
+    "�".to_string()
+  } else if g.width() == 0 {
+    format!(" {}", g)
+  } else {
+    g.to_string()
   }
+}
 
-  stdout().flush()?;
+// Columnas del margen izquierdo: marca de cambios y numero de linea (con un espacio)
+pub fn gutter_width(doc: &Document, config: &Config) -> usize {
+  let markers = if config.change_markers { 1 } else { 0 };
+  let numbers = match config.line_numbers {
+    LineNumbers::Off => 0,
+    _ => doc.lines.len().to_string().len().max(2) + 1,
+  };
+  markers + numbers
+}
+
+fn draw_gutter(line_idx: usize, cursor_y: usize, change: Option<LineChange>, width: usize, config: &Config) -> std::io::Result<()> {
+  if config.change_markers {
+    let (mark, style) = match change {
+      Some(LineChange::Added) => ("│", config.theme.added),
+      Some(LineChange::Modified) => ("│", config.theme.modified),
+      Some(LineChange::DeletedAbove) => ("▔", config.theme.deleted),
+      _ => (" ", Default::default()),
+    };
+    style.apply()?;
+    print!("{}", mark);
+    reset()?;
+  }
+
+  let number = match config.line_numbers {
+    LineNumbers::Off => return Ok(()),
+    LineNumbers::Relative if line_idx != cursor_y => line_idx.abs_diff(cursor_y),
+    _ => line_idx + 1,
+  };
+  let digits = width.saturating_sub(if config.change_markers { 2 } else { 1 });
+
+  // la linea del cursor resaltada
+  let style = if line_idx == cursor_y { config.theme.line_number_current } else { config.theme.line_number };
+  style.apply()?;
+  print!("{:>digits$} ", number, digits = digits);
+  reset()?;
   Ok(())
 }
 
+// Barra de pestañas: " main.rs* | draw.rs ", desplazada para que la activa siempre se vea
+fn draw_tabs(docs: &[Document], active: usize, term_width: usize, theme: &Theme) -> std::io::Result<()> {
+  let labels: Vec<String> = docs.iter()
+    .map(|doc| format!(" {}{} ", doc.name(), if doc.is_modified() { "*" } else { "" }))
+    .collect();
+
+  // primera pestaña visible: retroceder desde la activa mientras entren
+  let mut first = active;
+  let mut used = labels[active].chars().count();
+  while first > 0 && used + labels[first - 1].chars().count() < term_width {
+    first -= 1;
+    used += labels[first].chars().count() + 1;
+  }
+
+  let mut x = 0;
+  for (i, label) in labels.iter().enumerate().skip(first) {
+    if x >= term_width {
+      break;
+    }
+    if i > first {
+      print!("|");
+      x += 1;
+    }
+
+    let text: String = label.chars().take(term_width.saturating_sub(x)).collect();
+    x += text.chars().count();
 
-pub fn is_separator(c: char) -> bool {
-  c == ' ' || c == '.' || c == '?' || c == '<' || c == '>' || c == '|' || c == '/' || c == '\\' || c == '"' || c == '\'' || c == ';' || c == '@'
-}
\ No newline at end of file
+    let style = if i == active { theme.tab_active } else { theme.tab_inactive };
+    style.apply()?;
+    print!("{}", text);
+    reset()?;
+  }
+
+  Ok(())
+}
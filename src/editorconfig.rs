@@ -0,0 +1,317 @@
+use std::fs;
+use std::path::Path;
+
+use fancy_regex::Regex;
+
+use crate::file::{ Charset, LineEnding };
+
+// Soporte de .editorconfig (https://editorconfig.org): se leen los archivos desde la
+// carpeta del documento hacia arriba hasta uno con "root = true", y las secciones
+// cuyo patrón coincide con la ruta definen cómo editar y guardar el archivo.
+// Lo que no se especifica queda en None y vale la configuración global.
+
+#[derive(Clone, Default)]
+pub struct EditorConfig {
+  pub use_tabs: Option<bool>,
+  pub indent_size: Option<usize>,
+  pub tab_width: Option<usize>,
+  pub line_ending: Option<LineEnding>,
+  pub charset: Option<Charset>,
+  pub trim_trailing_whitespace: Option<bool>,
+  pub final_newline: Option<bool>,
+  pub max_line_length: Option<usize>,
+}
+
+impl EditorConfig {
+  // Propiedades que aplican a `path` (que puede no existir todavía)
+  pub fn resolve(path: &str) -> EditorConfig {
+    let path = std::path::absolute(path).unwrap_or_else(|_| Path::new(path).to_path_buf());
+    let file = path.to_string_lossy().replace('\\', "/");
+
+    // de la carpeta del archivo hacia la raíz; los más cercanos pisan a los lejanos
+    let mut found = Vec::new();
+    for dir in path.ancestors().skip(1) {
+      let Ok(content) = fs::read_to_string(dir.join(".editorconfig")) else {
+        continue;
+      };
+      let (root, sections) = parse(&content);
+      found.push((dir.to_string_lossy().replace('\\', "/"), sections));
+      if root {
+        break;
+      }
+    }
+
+    let mut props: Vec<(String, String)> = Vec::new();
+    for (dir, sections) in found.iter().rev() {
+      for (glob, section_props) in sections {
+        if glob_matches(glob, dir, &file) {
+          props.extend(section_props.iter().cloned());
+        }
+      }
+    }
+
+    let mut config = EditorConfig::default();
+    for (key, value) in &props {
+      config.set(key, value);
+    }
+
+    // indent_size = tab usa tab_width; tab_width por defecto es indent_size
+    if props.iter().rev().find(|(k, _)| k == "indent_size").is_some_and(|(_, v)| v == "tab") {
+      config.indent_size = config.tab_width;
+    }
+    if config.tab_width.is_none() {
+      config.tab_width = config.indent_size;
+    }
+    config
+  }
+
+  // valores inválidos o "unset" dejan la propiedad sin definir
+  fn set(&mut self, key: &str, value: &str) {
+    let number = || value.parse::<usize>().ok().filter(|n| *n > 0);
+    let boolean = || match value {
+      "true" => Some(true),
+      "false" => Some(false),
+      _ => None,
+    };
+
+    match key {
+      "indent_style" => self.use_tabs = match value {
+        "tab" => Some(true),
+        "space" => Some(false),
+        _ => None,
+      },
+      "indent_size" => self.indent_size = number(),
+      "tab_width" => self.tab_width = number(),
+      "end_of_line" => self.line_ending = match value {
+        "lf" => Some(LineEnding::Lf),
+        "crlf" => Some(LineEnding::CrLf),
+        "cr" => Some(LineEnding::Cr),
+        _ => None,
+      },
+      "charset" => self.charset = match value {
+        "utf-8" => Some(Charset::Utf8),
+        "utf-8-bom" => Some(Charset::Utf8Bom),
+        "latin1" => Some(Charset::Latin1),
+        "utf-16le" => Some(Charset::Utf16Le),
+        "utf-16be" => Some(Charset::Utf16Be),
+        _ => None,
+      },
+      "trim_trailing_whitespace" => self.trim_trailing_whitespace = boolean(),
+      "insert_final_newline" => self.final_newline = boolean(),
+      "max_line_length" => self.max_line_length = number(),
+      _ => {}
+    }
+  }
+}
+
+type Section = (String, Vec<(String, String)>);
+
+// (root = true, secciones con sus propiedades en minúsculas)
+fn parse(content: &str) -> (bool, Vec<Section>) {
+  let mut root = false;
+  let mut sections: Vec<Section> = Vec::new();
+
+  for line in content.lines() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+      continue;
+    }
+
+    if let Some(glob) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+      sections.push((glob.to_string(), Vec::new()));
+      continue;
+    }
+
+    let Some((key, value)) = line.split_once('=') else {
+      continue;
+    };
+    let key = key.trim().to_lowercase();
+    let value = value.trim().to_lowercase();
+
+    match sections.last_mut() {
+      Some((_, props)) => props.push((key, value)),
+      None if key == "root" => root = value == "true",
+      None => {}
+    }
+  }
+
+  (root, sections)
+}
+
+// Patrón de sección contra la ruta del archivo. Sin '/' vale para el nombre en
+// cualquier subcarpeta; con '/' es relativo a la carpeta del .editorconfig.
+fn glob_matches(glob: &str, dir: &str, file: &str) -> bool {
+  let Some(relative) = file.strip_prefix(dir.trim_end_matches('/')).and_then(|r| r.strip_prefix('/')) else {
+    return false;
+  };
+
+  let glob = if glob.contains('/') {
+    glob.trim_start_matches('/').to_string()
+  } else {
+    format!("**/{}", glob)
+  };
+
+  let (pattern, ranges) = glob_to_regex(&glob);
+  let Ok(regex) = Regex::new(&pattern) else {
+    return false;
+  };
+  let Ok(Some(captures)) = regex.captures(relative) else {
+    return false;
+  };
+
+  // {n1..n2}: el número capturado tiene que estar en el rango
+  ranges.iter().enumerate().all(|(i, (low, high))| {
+    captures.get(i + 1)
+      .and_then(|m| m.as_str().parse::<i64>().ok())
+      .is_some_and(|n| (*low..=*high).contains(&n))
+  })
+}
+
+// Traduce el glob a una expresión regular anclada. Devuelve también los rangos
+// numéricos, en el orden de sus grupos de captura.
+fn glob_to_regex(glob: &str) -> (String, Vec<(i64, i64)>) {
+  let chars: Vec<char> = glob.chars().collect();
+  let mut out = String::from("^");
+  let mut ranges = Vec::new();
+  let mut braces = 0;
+  let mut i = 0;
+
+  while i < chars.len() {
+    let c = chars[i];
+    match c {
+      '\\' if i + 1 < chars.len() => {
+        out.push_str(&fancy_regex::escape(&chars[i + 1].to_string()));
+        i += 1;
+      }
+      '*' if chars.get(i + 1) == Some(&'*') => {
+        // "**/" también vale para cero carpetas
+        if chars.get(i + 2) == Some(&'/') {
+          out.push_str("(?:.*/)?");
+          i += 2;
+        } else {
+          out.push_str(".*");
+          i += 1;
+        }
+      }
+      '*' => out.push_str("[^/]*"),
+      '?' => out.push_str("[^/]"),
+      '[' => match chars[i..].iter().position(|&c| c == ']') {
+        Some(len) if len > 1 => {
+          let class: String = chars[i + 1..i + len].iter().collect();
+          let class = class.strip_prefix('!').map(|rest| format!("^{}", rest)).unwrap_or(class);
+          out.push_str(&format!("[{}]", class.replace('\\', "\\\\")));
+          i += len;
+        }
+        _ => out.push_str("\\["),
+      },
+      '{' => {
+        let close = chars[i..].iter().position(|&c| c == '}');
+        let inner: Option<String> = close.map(|len| chars[i + 1..i + len].iter().collect());
+        let range = inner.as_deref().and_then(|inner| {
+          let (low, high) = inner.split_once("..")?;
+          Some((low.parse::<i64>().ok()?, high.parse::<i64>().ok()?))
+        });
+
+        if let (Some((low, high)), Some(len)) = (range, close) {
+          out.push_str("([+-]?\\d+)");
+          ranges.push((low.min(high), low.max(high)));
+          i += len;
+        } else if inner.as_deref().is_some_and(|inner| inner.contains(',')) {
+          out.push_str("(?:");
+          braces += 1;
+        } else {
+          out.push_str("\\{");
+        }
+      }
+      ',' if braces > 0 => out.push('|'),
+      '}' if braces > 0 => {
+        out.push(')');
+        braces -= 1;
+      }
+      _ => out.push_str(&fancy_regex::escape(&c.to_string())),
+    }
+    i += 1;
+  }
+
+  out.push('$');
+  (out, ranges)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn matches(glob: &str, relative: &str) -> bool {
+    glob_matches(glob, "/proyecto", &format!("/proyecto/{}", relative))
+  }
+
+  #[test]
+  fn glob_translation() {
+    assert_eq!(glob_to_regex("*.rs").0, "^[^/]*\\.rs$");
+    assert_eq!(glob_to_regex("**/a?").0, "^(?:.*/)?a[^/]$");
+    assert_eq!(glob_to_regex("*.{js,ts}").0, "^[^/]*\\.(?:js|ts)$");
+    assert_eq!(glob_to_regex("v{1..3}"), ("^v([+-]?\\d+)$".to_string(), vec![(1, 3)]));
+    // llaves sin coma ni rango son literales
+    assert_eq!(glob_to_regex("{a}").0, "^\\{a\\}$");
+  }
+
+  #[test]
+  fn name_globs_match_in_any_folder() {
+    assert!(matches("*.rs", "main.rs"));
+    assert!(matches("*.rs", "src/syntax/mod.rs"));
+    assert!(!matches("*.rs", "main.rsx"));
+    assert!(matches("Makefile", "sub/Makefile"));
+  }
+
+  #[test]
+  fn path_globs_are_relative() {
+    assert!(matches("src/*.rs", "src/main.rs"));
+    assert!(!matches("src/*.rs", "src/syntax/mod.rs"));
+    assert!(!matches("src/*.rs", "otro/src/main.rs"));
+    assert!(matches("/src/**/*.rs", "src/main.rs"));
+    assert!(matches("/src/**/*.rs", "src/syntax/mod.rs"));
+    assert!(!glob_matches("*.rs", "/proyecto", "/proyecto2/main.rs"));
+  }
+
+  #[test]
+  fn braces_and_classes() {
+    assert!(matches("*.{js,ts}", "a.ts"));
+    assert!(!matches("*.{js,ts}", "a.rs"));
+    assert!(matches("{package.json,.babelrc}", "web/.babelrc"));
+    assert!(matches("log{1..3}.txt", "log2.txt"));
+    assert!(!matches("log{1..3}.txt", "log4.txt"));
+    assert!(matches("log{3..1}.txt", "log1.txt"));
+    assert!(matches("[ab].c", "a.c"));
+    assert!(!matches("[!ab].c", "a.c"));
+    assert!(matches("[!ab].c", "x.c"));
+  }
+
+  #[test]
+  fn parse_sections() {
+    let (root, sections) = parse("root = TRUE\n# comentario\n[*]\nIndent_Style = Tab\n\n[*.md]\n; otro\ntrim_trailing_whitespace=false\n");
+    assert!(root);
+    assert_eq!(sections, [
+      ("*".to_string(), vec![("indent_style".to_string(), "tab".to_string())]),
+      ("*.md".to_string(), vec![("trim_trailing_whitespace".to_string(), "false".to_string())]),
+    ]);
+  }
+
+  #[test]
+  fn resolve_merges_up_to_root() {
+    let dir = std::env::temp_dir().join("camelCaseEditor-test-editorconfig");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("proyecto/src")).unwrap();
+    fs::write(dir.join(".editorconfig"), "[*]\nindent_size = 8\n").unwrap();
+    fs::write(dir.join("proyecto/.editorconfig"), "root = true\n[*]\nindent_style = space\nindent_size = 2\nend_of_line = crlf\n").unwrap();
+    fs::write(dir.join("proyecto/src/.editorconfig"), "[*.rs]\nindent_style = tab\nindent_size = tab\ntab_width = 4\n[*.txt]\ncharset = nada\n").unwrap();
+
+    let rust = EditorConfig::resolve(&dir.join("proyecto/src/main.rs").to_string_lossy());
+    assert_eq!((rust.use_tabs, rust.indent_size, rust.tab_width), (Some(true), Some(4), Some(4)));
+    assert!(rust.line_ending == Some(LineEnding::CrLf));
+
+    // la carpeta de arriba de root = true no cuenta
+    let text = EditorConfig::resolve(&dir.join("proyecto/notas.txt").to_string_lossy());
+    assert_eq!((text.use_tabs, text.indent_size, text.tab_width), (Some(false), Some(2), Some(2)));
+    assert!(text.charset.is_none());
+  }
+}
@@ -1,21 +1,141 @@
 use std::fs;
-use std::io;
+use std::io::{ self, Read };
+use std::path::{ Path, PathBuf };
+use std::time::{ SystemTime, UNIX_EPOCH };
 
-pub fn save_file(path: &str, lines: &Vec<String>) -> io::Result<()> {
-  let content = lines.join("\n");
-  fs::write(path, content)
+#[derive(Clone, Copy, PartialEq)]
+pub enum LineEnding {
+  Lf,
+  CrLf,
+  Cr,
 }
 
-pub fn open_file(path: &str) -> io::Result<Vec<String>> {
-  let content = fs::read_to_string(path)?;
-  let lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+impl LineEnding {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      LineEnding::Lf => "\n",
+      LineEnding::CrLf => "\r\n",
+      LineEnding::Cr => "\r",
+    }
+  }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Charset {
+  Utf8,
+  Utf8Bom,
+  Latin1,
+  Utf16Le,
+  Utf16Be,
+}
+
+// Cómo está escrito un archivo en disco, para guardarlo de la misma forma
+#[derive(Clone, Copy, PartialEq)]
+pub struct FileFormat {
+  pub line_ending: LineEnding,
+  pub charset: Charset,
+  pub final_newline: bool, // termina con un salto de linea
+}
+
+impl Default for FileFormat {
+  fn default() -> Self {
+    FileFormat { line_ending: LineEnding::Lf, charset: Charset::Utf8, final_newline: false }
+  }
+}
+
+pub fn save_file(path: &str, lines: &[String], format: &FileFormat) -> io::Result<()> {
+  // crear las carpetas intermedias que falten (ej: "src/nuevo/mod.rs")
+  if let Some(parent) = Path::new(path).parent()
+    && !parent.as_os_str().is_empty() {
+    fs::create_dir_all(parent)?;
+  }
+
+  let mut content = lines.join(format.line_ending.as_str());
+  if format.final_newline {
+    content.push_str(format.line_ending.as_str());
+  }
+  fs::write(path, encode(&content, format.charset)?)
+}
+
+// `charset` = None lo deduce del BOM (UTF-8 si no hay)
+pub fn open_file(path: &str, charset: Option<Charset>) -> io::Result<(Vec<String>, FileFormat)> {
+  let bytes = fs::read(path)?;
+  let charset = charset.unwrap_or_else(|| detect_charset(&bytes));
+  let content = decode(&bytes, charset)?;
+
+  let line_ending = match content.find(['\n', '\r']) {
+    Some(i) if content[i..].starts_with("\r\n") => LineEnding::CrLf,
+    Some(i) if content[i..].starts_with('\r') => LineEnding::Cr,
+    _ => LineEnding::Lf,
+  };
+  let final_newline = content.ends_with(['\n', '\r']);
+
+  let mut lines: Vec<String> = match line_ending {
+    LineEnding::Cr => content.split('\r').map(|l| l.to_string()).collect(),
+    _ => content.split('\n').map(|l| l.strip_suffix('\r').unwrap_or(l).to_string()).collect(),
+  };
+  if final_newline {
+    lines.pop();
+  }
   if lines.is_empty() {
-      Ok(vec![String::new()])
+    lines.push(String::new());
+  }
+
+  Ok((lines, FileFormat { line_ending, charset, final_newline }))
+}
+
+fn detect_charset(bytes: &[u8]) -> Charset {
+  if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+    Charset::Utf8Bom
+  } else if bytes.starts_with(&[0xFF, 0xFE]) {
+    Charset::Utf16Le
+  } else if bytes.starts_with(&[0xFE, 0xFF]) {
+    Charset::Utf16Be
   } else {
-      Ok(lines)
+    Charset::Utf8
+  }
+}
+
+fn decode(bytes: &[u8], charset: Charset) -> io::Result<String> {
+  let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+  match charset {
+    Charset::Utf8 | Charset::Utf8Bom => {
+      let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+      String::from_utf8(bytes.to_vec()).map_err(|e| invalid(e.to_string()))
+    }
+    Charset::Latin1 => Ok(bytes.iter().map(|&b| b as char).collect()),
+    Charset::Utf16Le | Charset::Utf16Be => {
+      let bom: &[u8] = if charset == Charset::Utf16Le { &[0xFF, 0xFE] } else { &[0xFE, 0xFF] };
+      let bytes = bytes.strip_prefix(bom).unwrap_or(bytes);
+      let units: Vec<u16> = bytes.chunks(2)
+        .map(|pair| {
+          let pair = [pair[0], pair.get(1).copied().unwrap_or(0)];
+          if charset == Charset::Utf16Le { u16::from_le_bytes(pair) } else { u16::from_be_bytes(pair) }
+        })
+        .collect();
+      String::from_utf16(&units).map_err(|e| invalid(e.to_string()))
+    }
   }
 }
 
+fn encode(content: &str, charset: Charset) -> io::Result<Vec<u8>> {
+  Ok(match charset {
+    Charset::Utf8 => content.as_bytes().to_vec(),
+    Charset::Utf8Bom => [&[0xEF, 0xBB, 0xBF], content.as_bytes()].concat(),
+    Charset::Latin1 => content.chars()
+      .map(|c| u8::try_from(c).map_err(|_| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("'{}' no existe en latin1", c))
+      }))
+      .collect::<io::Result<Vec<u8>>>()?,
+    Charset::Utf16Le => [0xFF, 0xFE].into_iter()
+      .chain(content.encode_utf16().flat_map(|u| u.to_le_bytes()))
+      .collect(),
+    Charset::Utf16Be => [0xFE, 0xFF].into_iter()
+      .chain(content.encode_utf16().flat_map(|u| u.to_be_bytes()))
+      .collect(),
+  })
+}
+
 pub fn list_directory(path: &str) -> io::Result<Vec<String>> {
     let mut entries = Vec::new();
     let read_dir = fs::read_dir(path)?;
@@ -36,4 +156,364 @@ pub fn list_directory(path: &str) -> io::Result<Vec<String>> {
     }
 
     Ok(entries)
-}
\ No newline at end of file
+}
+
+// Ruta escrita por el usuario (relativa a `base_dir` o absoluta) -> ruta completa
+pub fn resolve_path(base_dir: &str, input: &str) -> String {
+  Path::new(base_dir).join(input).to_string_lossy().to_string()
+}
+
+// Completa con Tab el ultimo segmento de `input` contra el sistema de archivos.
+// Devuelve el texto completado hasta el prefijo comun de las coincidencias
+// (con "/" al final si la unica coincidencia es una carpeta), o None si no hay.
+pub fn complete_path(base_dir: &str, input: &str) -> Option<String> {
+  let split = input.rfind(['/', '\\']).map(|i| i + 1).unwrap_or(0);
+  let (dir_part, prefix) = input.split_at(split);
+
+  let dir = if dir_part.is_empty() {
+    base_dir.to_string()
+  } else {
+    resolve_path(base_dir, dir_part)
+  };
+
+  let mut matches: Vec<(String, bool)> = fs::read_dir(dir)
+    .ok()?
+    .filter_map(|entry| entry.ok())
+    .filter_map(|entry| {
+      let name = entry.file_name().to_string_lossy().to_string();
+      let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+      name.starts_with(prefix).then_some((name, is_dir))
+    })
+    .collect();
+
+  if matches.is_empty() {
+    return None;
+  }
+  matches.sort();
+
+  if matches.len() == 1 {
+    let (name, is_dir) = &matches[0];
+    let sep = if *is_dir { "/" } else { "" };
+    return Some(format!("{}{}{}", dir_part, name, sep));
+  }
+
+  // prefijo comun (en chars) de todas las coincidencias
+  let first: Vec<char> = matches[0].0.chars().collect();
+  let mut common = first.len();
+  for (name, _) in &matches[1..] {
+    common = common.min(
+      name.chars().zip(first.iter()).take_while(|(a, b)| a == *b).count()
+    );
+  }
+
+  let completed: String = first[..common].iter().collect();
+  Some(format!("{}{}", dir_part, completed))
+}
+
+// Carpeta de datos del editor: ~/.camelCaseEditor (o %USERPROFILE%\.camelCaseEditor)
+pub fn app_dir() -> PathBuf {
+  let home = std::env::var_os("HOME")
+    .or_else(|| std::env::var_os("USERPROFILE"))
+    .map(PathBuf::from)
+    .unwrap_or_else(|| PathBuf::from("."));
+
+  home.join(".camelCaseEditor")
+}
+
+pub fn create_folder(dir: &str, name: &str) -> io::Result<()> {
+  fs::create_dir_all(Path::new(dir).join(name))
+}
+
+pub fn rename_entry(dir: &str, from: &str, to: &str) -> io::Result<()> {
+  let dir = Path::new(dir);
+  let target = dir.join(to);
+  if target.exists() {
+    return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} ya existe", to)));
+  }
+  fs::rename(dir.join(from), target)
+}
+
+pub fn duplicate_entry(dir: &str, from: &str, to: &str) -> io::Result<()> {
+  let dir = Path::new(dir);
+  let target = dir.join(to);
+  if target.exists() {
+    return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} ya existe", to)));
+  }
+  copy_recursive(&dir.join(from), &target)
+}
+
+// Mueve un archivo o carpeta a la papelera del editor (~/.camelCaseEditor/papelera)
+// en lugar de borrarlo definitivamente
+pub fn move_to_trash(dir: &str, name: &str) -> io::Result<PathBuf> {
+  let source = Path::new(dir).join(name);
+  // si no existe, el error antes de crear la papelera
+  fs::symlink_metadata(&source)?;
+  let trash = app_dir().join("papelera");
+  fs::create_dir_all(&trash)?;
+
+  // prefijo con la hora para no pisar otro archivo con el mismo nombre
+  let stamp = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or(0);
+  let target = trash.join(format!("{}_{}", stamp, name.trim_end_matches('/')));
+
+  // rename falla entre discos distintos: copiar y borrar
+  if fs::rename(&source, &target).is_err() {
+    copy_recursive(&source, &target)?;
+    if source.is_dir() {
+      fs::remove_dir_all(&source)?;
+    } else {
+      fs::remove_file(&source)?;
+    }
+  }
+
+  Ok(target)
+}
+
+// Nombre sugerido para una copia: "main.rs" -> "main copia.rs"
+pub fn copy_name(name: &str) -> String {
+  let name = name.trim_end_matches('/');
+  match name.rfind('.') {
+    Some(dot) if dot > 0 => format!("{} copia{}", &name[..dot], &name[dot..]),
+    _ => format!("{} copia", name),
+  }
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> io::Result<()> {
+  if from.is_dir() {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+      let entry = entry?;
+      copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+  } else {
+    fs::copy(from, to).map(|_| ())
+  }
+}
+
+// Vista previa de una entrada del popup de Abrir: primeras lineas del archivo,
+// o un resumen si es una carpeta o un archivo binario
+pub fn preview_entry(path: &Path, max_lines: usize) -> Vec<String> {
+  if path.is_dir() {
+    let mut names: Vec<String> = match fs::read_dir(path) {
+      Ok(read_dir) => read_dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect(),
+      Err(e) => return vec![format!("No se puede leer: {}", e)],
+    };
+    names.sort();
+
+    let mut preview = vec![format!("Directorio con {} entradas", names.len()), String::new()];
+    preview.extend(names.into_iter().take(max_lines.saturating_sub(2)));
+    return preview;
+  }
+
+  let mut head = Vec::new();
+  let size = match fs::File::open(path).and_then(|file| {
+    let size = file.metadata()?.len();
+    file.take(8192).read_to_end(&mut head)?;
+    Ok(size)
+  }) {
+    Ok(size) => size,
+    Err(e) => return vec![format!("No se puede leer: {}", e)],
+  };
+
+  if head.contains(&0) {
+    return vec![format!("Archivo binario ({} bytes)", size)];
+  }
+
+  String::from_utf8_lossy(&head)
+    .lines()
+    .take(max_lines)
+    .map(|line| {
+      line.replace('\t', "    ").chars().filter(|c| !c.is_control()).collect()
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // carpeta vacía propia de cada test, dentro de la temporal del sistema
+  fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("camelCaseEditor-test-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  #[test]
+  fn complete_path_single_match() {
+    let dir = temp_dir("complete-single");
+    fs::write(dir.join("main.rs"), "").unwrap();
+    fs::create_dir(dir.join("src")).unwrap();
+    let base = dir.to_string_lossy();
+
+    assert_eq!(complete_path(&base, "ma").as_deref(), Some("main.rs"));
+    // una carpeta se completa con "/" para seguir escribiendo adentro
+    assert_eq!(complete_path(&base, "sr").as_deref(), Some("src/"));
+    assert_eq!(complete_path(&base, "x"), None);
+  }
+
+  #[test]
+  fn complete_path_common_prefix() {
+    let dir = temp_dir("complete-prefix");
+    fs::write(dir.join("config.toml"), "").unwrap();
+    fs::write(dir.join("config.rs"), "").unwrap();
+    fs::write(dir.join("cargo.lock"), "").unwrap();
+    let base = dir.to_string_lossy();
+
+    assert_eq!(complete_path(&base, "co").as_deref(), Some("config."));
+    assert_eq!(complete_path(&base, "c").as_deref(), Some("c"));
+  }
+
+  #[test]
+  fn complete_path_in_subfolder() {
+    let dir = temp_dir("complete-sub");
+    fs::create_dir_all(dir.join("src/syntax")).unwrap();
+    fs::write(dir.join("src/syntax/languages.rs"), "").unwrap();
+    let base = dir.to_string_lossy();
+
+    assert_eq!(complete_path(&base, "src/sy").as_deref(), Some("src/syntax/"));
+    assert_eq!(complete_path(&base, "src/syntax/l").as_deref(), Some("src/syntax/languages.rs"));
+    assert_eq!(complete_path(&base, "nada/l"), None);
+  }
+
+  fn format(line_ending: LineEnding, charset: Charset, final_newline: bool) -> FileFormat {
+    FileFormat { line_ending, charset, final_newline }
+  }
+
+  // guarda, revisa los bytes en disco y vuelve a abrir
+  fn round_trip(name: &str, lines: &[&str], format: FileFormat, expected: &[u8]) {
+    let path = temp_dir(name).join("archivo.txt");
+    let path = path.to_string_lossy();
+    let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+
+    save_file(&path, &lines, &format).unwrap();
+    assert_eq!(fs::read(&*path).unwrap(), expected);
+
+    let charset = (format.charset == Charset::Latin1).then_some(Charset::Latin1);
+    let (opened, detected) = open_file(&path, charset).unwrap();
+    assert_eq!(opened, lines);
+    assert!(detected == format);
+  }
+
+  #[test]
+  fn line_endings_round_trip() {
+    round_trip("lf", &["a", "b"], format(LineEnding::Lf, Charset::Utf8, true), b"a\nb\n");
+    round_trip("crlf", &["a", "", "b"], format(LineEnding::CrLf, Charset::Utf8, false), b"a\r\n\r\nb");
+    round_trip("cr", &["a", "b"], format(LineEnding::Cr, Charset::Utf8, true), b"a\rb\r");
+  }
+
+  #[test]
+  fn charsets_round_trip() {
+    round_trip("bom", &["ñ"], format(LineEnding::Lf, Charset::Utf8Bom, false), b"\xEF\xBB\xBF\xC3\xB1");
+    round_trip("latin1", &["ñ"], format(LineEnding::Lf, Charset::Latin1, true), b"\xF1\n");
+    round_trip("utf16le", &["añ"], format(LineEnding::Lf, Charset::Utf16Le, false), b"\xFF\xFEa\x00\xF1\x00");
+    round_trip("utf16be", &["a"], format(LineEnding::CrLf, Charset::Utf16Be, true), b"\xFE\xFF\x00a\x00\r\x00\n");
+  }
+
+  #[test]
+  fn open_empty_and_invalid() {
+    let dir = temp_dir("open-invalid");
+    let empty = dir.join("vacio.txt");
+    fs::write(&empty, "").unwrap();
+    let (lines, format) = open_file(&empty.to_string_lossy(), None).unwrap();
+    assert_eq!(lines, [""]);
+    assert!(!format.final_newline);
+
+    let invalid = dir.join("latin1.txt");
+    fs::write(&invalid, b"\xF1\n").unwrap();
+    assert!(open_file(&invalid.to_string_lossy(), None).is_err());
+  }
+
+  #[test]
+  fn latin1_cannot_encode_everything() {
+    let path = temp_dir("latin1-error").join("archivo.txt");
+    let lines = vec!["€".to_string()];
+    let error = save_file(&path.to_string_lossy(), &lines, &format(LineEnding::Lf, Charset::Latin1, false));
+    assert_eq!(error.unwrap_err().kind(), io::ErrorKind::InvalidData);
+  }
+
+  #[test]
+  fn rename_does_not_overwrite() {
+    let dir = temp_dir("rename");
+    fs::write(dir.join("a.txt"), "a").unwrap();
+    fs::write(dir.join("b.txt"), "b").unwrap();
+    let base = dir.to_string_lossy();
+
+    let error = rename_entry(&base, "a.txt", "b.txt").unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+    assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "a");
+    assert_eq!(fs::read_to_string(dir.join("b.txt")).unwrap(), "b");
+
+    rename_entry(&base, "a.txt", "c.txt").unwrap();
+    assert!(!dir.join("a.txt").exists());
+    assert_eq!(fs::read_to_string(dir.join("c.txt")).unwrap(), "a");
+  }
+
+  #[test]
+  fn copy_names() {
+    assert_eq!(copy_name("main.rs"), "main copia.rs");
+    assert_eq!(copy_name("a.tar.gz"), "a.tar copia.gz");
+    assert_eq!(copy_name(".bashrc"), ".bashrc copia");
+    assert_eq!(copy_name("src/"), "src copia");
+  }
+
+  #[test]
+  fn duplicate_copies_folders_and_does_not_overwrite() {
+    let dir = temp_dir("duplicate");
+    fs::create_dir_all(dir.join("src/sub")).unwrap();
+    fs::write(dir.join("src/sub/x.rs"), "x").unwrap();
+    let base = dir.to_string_lossy();
+
+    let copy = copy_name("src/");
+    duplicate_entry(&base, "src/", &copy).unwrap();
+    assert_eq!(fs::read_to_string(dir.join("src copia/sub/x.rs")).unwrap(), "x");
+    assert!(dir.join("src/sub/x.rs").exists());
+
+    let error = duplicate_entry(&base, "src/", &copy).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+  }
+
+  #[test]
+  fn trash_of_a_missing_path() {
+    let dir = temp_dir("trash-missing");
+    let error = move_to_trash(&dir.to_string_lossy(), "no-existe.txt").unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::NotFound);
+  }
+
+  #[test]
+  fn preview_binary_file() {
+    let path = temp_dir("preview-binary").join("imagen.png");
+    fs::write(&path, b"\x89PNG\0\0\x01").unwrap();
+    assert_eq!(preview_entry(&path, 10), ["Archivo binario (7 bytes)"]);
+  }
+
+  #[test]
+  fn preview_directory_summary() {
+    let dir = temp_dir("preview-dir");
+    for name in ["c.txt", "a.txt", "b"] {
+      fs::write(dir.join(name), "").unwrap();
+    }
+    assert_eq!(preview_entry(&dir, 10), ["Directorio con 3 entradas", "", "a.txt", "b", "c.txt"]);
+    // el encabezado ocupa dos lineas del limite
+    assert_eq!(preview_entry(&dir, 4), ["Directorio con 3 entradas", "", "a.txt", "b"]);
+  }
+
+  #[test]
+  fn preview_long_file_is_truncated() {
+    let path = temp_dir("preview-long").join("largo.txt");
+    let content: String = (0..1000).map(|i| format!("\tlinea {}\n", i)).collect();
+    fs::write(&path, content).unwrap();
+
+    let preview = preview_entry(&path, 5);
+    assert_eq!(preview, ["    linea 0", "    linea 1", "    linea 2", "    linea 3", "    linea 4"]);
+    // y del archivo solo se leen los primeros 8 KB
+    assert!(preview_entry(&path, 10_000).len() < 1000);
+  }
+}
@@ -0,0 +1,269 @@
+use crate::CursorPos;
+use crate::selection::line_range;
+use crate::utils::{ char_to_byte_idx, display_col };
+
+// Comandos de indentación: Tab / Shift + Tab sobre la linea o las lineas seleccionadas,
+// y Backspace que borra un nivel entero dentro de la indentación. Además se detecta el
+// estilo de cada archivo al abrirlo y se puede reindentar todo con otro estilo.
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Indent {
+  pub use_tabs: bool, // tabs reales en vez de espacios
+  pub size: usize,    // espacios por nivel
+}
+
+impl Indent {
+  // texto de un nivel
+  pub fn unit(&self) -> String {
+    if self.use_tabs { "\t".to_string() } else { " ".repeat(self.size.max(1)) }
+  }
+
+  // para la linea de estado: "Espacios: 4" o "Tabs"
+  pub fn name(&self) -> String {
+    if self.use_tabs { "Tabs".to_string() } else { format!("Espacios: {}", self.size) }
+  }
+}
+
+// Estilo de indentación de un texto: tabs si la mayoría de las lineas indentadas
+// empiezan con tab; si no, el salto de espacios más común entre lineas seguidas.
+// Con tabs el tamaño es el ancho del tab. None si no hay lineas indentadas.
+pub fn detect(lines: &[String], tab_width: usize) -> Option<Indent> {
+  let mut tab_lines = 0;
+  let mut space_lines = 0;
+  let mut votes = [0usize; 9]; // votos por ancho, 2..=8
+  let mut smallest = usize::MAX;
+  let mut previous = 0;
+
+  for line in lines {
+    if line.trim().is_empty() {
+      continue;
+    }
+    if line.starts_with('\t') {
+      tab_lines += 1;
+      continue;
+    }
+
+    let spaces = line.chars().take_while(|c| *c == ' ').count();
+    if spaces > 0 {
+      space_lines += 1;
+      smallest = smallest.min(spaces);
+    }
+    // saltos de 1 suelen ser alineaciones (" * " de comentarios), no niveles
+    let delta = spaces.abs_diff(previous);
+    if (2..=8).contains(&delta) {
+      votes[delta] += 1;
+    }
+    previous = spaces;
+  }
+
+  if tab_lines == 0 && space_lines == 0 {
+    return None;
+  }
+  if tab_lines > space_lines {
+    return Some(Indent { use_tabs: true, size: tab_width });
+  }
+
+  // en empate gana el menor: con 2 espacios también aparecen saltos de 4
+  let size = (2..=8).rev().max_by_key(|&w| votes[w]).filter(|&w| votes[w] > 0)
+    .unwrap_or(smallest.clamp(1, 8));
+  Some(Indent { use_tabs: false, size })
+}
+
+// Cambia la indentación de todas las lineas de `from` a `to`. Los espacios que no
+// completan un nivel se dejan como espacios.
+pub fn reindent(lines: &mut [String], cursor: &mut CursorPos, from: Indent, to: Indent) {
+  let from_size = from.size.max(1);
+
+  for (y, line) in lines.iter_mut().enumerate() {
+    let leading = line.chars().take_while(|c| *c == ' ' || *c == '\t').count();
+    if leading == 0 {
+      continue;
+    }
+
+    // ancho en columnas, con los tabs de `from`
+    let width = line.chars().take(leading).fold(0, |col, c| {
+      if c == '\t' { col + from_size - col % from_size } else { col + 1 }
+    });
+    let new_indent = format!("{}{}", to.unit().repeat(width / from_size), " ".repeat(width % from_size));
+    let new_leading = new_indent.chars().count();
+    line.replace_range(..leading, &new_indent);
+
+    if cursor.y == y {
+      cursor.x = if cursor.x <= leading { cursor.x.min(new_leading) } else { cursor.x + new_leading - leading };
+    }
+  }
+}
+
+pub fn is_multiline(cursor: &CursorPos, selection: &Option<CursorPos>) -> bool {
+  selection.is_some_and(|start| start.y != cursor.y)
+}
+
+// Tab sin selección de varias lineas: un tab o espacios hasta el próximo nivel
+pub fn insert_tab(lines: &mut [String], cursor: &mut CursorPos, indent: Indent, tab_width: usize) {
+  let text = if indent.use_tabs {
+    "\t".to_string()
+  } else {
+    let size = indent.size.max(1);
+    let col = display_col(&lines[cursor.y], cursor.x, tab_width);
+    " ".repeat(size - col % size)
+  };
+
+  let byte_idx = char_to_byte_idx(&lines[cursor.y], cursor.x);
+  lines[cursor.y].insert_str(byte_idx, &text);
+  cursor.x += text.chars().count();
+}
+
+// Agrega un nivel al principio de cada linea (las vacías se dejan como están)
+pub fn indent_lines(lines: &mut [String], cursor: &mut CursorPos, selection: &mut Option<CursorPos>, indent: Indent) {
+  let (first, last) = line_range(cursor, selection);
+  let unit = indent.unit();
+  let added = unit.chars().count();
+
+  for (y, line) in lines.iter_mut().enumerate().take(last + 1).skip(first) {
+    if line.is_empty() {
+      continue;
+    }
+    line.insert_str(0, &unit);
+    shift(cursor, y, added as isize);
+    if let Some(start) = selection {
+      shift(start, y, added as isize);
+    }
+  }
+}
+
+// Quita un nivel (un tab o hasta `size` espacios) del principio de cada linea
+pub fn outdent_lines(lines: &mut [String], cursor: &mut CursorPos, selection: &mut Option<CursorPos>, indent: Indent) {
+  let (first, last) = line_range(cursor, selection);
+
+  for (y, line) in lines.iter_mut().enumerate().take(last + 1).skip(first) {
+    let removed = if line.starts_with('\t') {
+      1
+    } else {
+      line.chars().take(indent.size.max(1)).take_while(|c| *c == ' ').count()
+    };
+    if removed == 0 {
+      continue;
+    }
+    line.replace_range(..removed, "");
+    shift(cursor, y, -(removed as isize));
+    if let Some(start) = selection {
+      shift(start, y, -(removed as isize));
+    }
+  }
+}
+
+// Backspace con el cursor dentro de la indentación de espacios: vuelve al nivel
+// anterior. Devuelve false si no corresponde y hay que borrar un solo caracter.
+pub fn backspace_indent(lines: &mut [String], cursor: &mut CursorPos, indent: Indent) -> bool {
+  let line = &lines[cursor.y];
+  if indent.use_tabs || cursor.x == 0 || !line.chars().take(cursor.x).all(|c| c == ' ') {
+    return false;
+  }
+
+  let size = indent.size.max(1);
+  let removed = (cursor.x - 1) % size + 1;
+  lines[cursor.y].replace_range(cursor.x - removed..cursor.x, "");
+  cursor.x -= removed;
+  true
+}
+
+fn shift(pos: &mut CursorPos, y: usize, delta: isize) {
+  if pos.y == y {
+    pos.x = pos.x.saturating_add_signed(delta);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const TABS: Indent = Indent { use_tabs: true, size: 4 };
+
+  fn spaces(size: usize) -> Indent {
+    Indent { use_tabs: false, size }
+  }
+
+  fn lines(text: &str) -> Vec<String> {
+    text.lines().map(String::from).collect()
+  }
+
+  // (usa tabs, tamaño) para poder comparar sin Debug
+  fn detected(text: &str) -> Option<(bool, usize)> {
+    detected_with(text, 4)
+  }
+
+  fn detected_with(text: &str, tab_width: usize) -> Option<(bool, usize)> {
+    detect(&lines(text), tab_width).map(|indent| (indent.use_tabs, indent.size))
+  }
+
+  #[test]
+  fn detect_spaces_by_most_common_step() {
+    assert_eq!(detected("fn a() {\n  if x {\n    y();\n  }\n}\n"), Some((false, 2)));
+    assert_eq!(detected("def a():\n    if x:\n        y()\n    z()\n"), Some((false, 4)));
+    // los saltos de 1 de un comentario no cuentan
+    assert_eq!(detected("/*\n * uno\n */\nfn a() {\n    b();\n}\n"), Some((false, 4)));
+  }
+
+  #[test]
+  fn detect_tabs_and_nothing() {
+    assert_eq!(detected("a {\n\tb;\n\t\tc;\n}\n"), Some((true, 4)));
+    assert_eq!(detected_with("a {\n\tb;\n\t\tc;\n}\n", 8), Some((true, 8)));
+    assert_eq!(detected("a\nb\n\n"), None);
+    // solo alineaciones de 1: se usa la menor
+    assert_eq!(detected("a\n b\n"), Some((false, 1)));
+  }
+
+  #[test]
+  fn reindent_spaces_to_tabs_keeps_partial_levels() {
+    let mut text = lines("a\n    b\n      c\nd");
+    let mut cursor = CursorPos { x: 7, y: 2 };
+    reindent(&mut text, &mut cursor, spaces(4), TABS);
+    assert_eq!(text, ["a", "\tb", "\t  c", "d"]);
+    assert_eq!((cursor.x, cursor.y), (4, 2));
+  }
+
+  #[test]
+  fn reindent_tabs_to_spaces() {
+    let mut text = lines("\tb\n\t\tc\n");
+    let mut cursor = CursorPos { x: 1, y: 1 };
+    reindent(&mut text, &mut cursor, TABS, spaces(2));
+    assert_eq!(text, ["  b", "    c"]);
+    // dentro de la indentación se queda donde estaba
+    assert_eq!(cursor.x, 1);
+  }
+
+  #[test]
+  fn insert_tab_goes_to_next_level() {
+    let mut text = lines("ab");
+    let mut cursor = CursorPos { x: 1, y: 0 };
+    insert_tab(&mut text, &mut cursor, spaces(4), 4);
+    assert_eq!(text, ["a   b"]);
+    assert_eq!(cursor.x, 4);
+  }
+
+  #[test]
+  fn indent_and_outdent_selection() {
+    let mut text = lines("a\n\n  b");
+    let mut cursor = CursorPos { x: 3, y: 2 };
+    let mut selection = Some(CursorPos { x: 0, y: 0 });
+    indent_lines(&mut text, &mut cursor, &mut selection, spaces(2));
+    assert_eq!(text, ["  a", "", "    b"]);
+    assert_eq!(cursor.x, 5);
+
+    outdent_lines(&mut text, &mut cursor, &mut selection, TABS);
+    assert_eq!(text, ["a", "", "b"]);
+    assert_eq!(cursor.x, 1);
+    assert_eq!(selection.map(|start| start.x), Some(0));
+  }
+
+  #[test]
+  fn backspace_removes_a_level() {
+    let mut text = lines("      x");
+    let mut cursor = CursorPos { x: 6, y: 0 };
+    assert!(backspace_indent(&mut text, &mut cursor, spaces(4)));
+    assert_eq!((text[0].as_str(), cursor.x), ("    x", 4));
+    assert!(backspace_indent(&mut text, &mut cursor, spaces(4)));
+    assert_eq!((text[0].as_str(), cursor.x), ("x", 0));
+    assert!(!backspace_indent(&mut text, &mut cursor, spaces(4)));
+  }
+}
@@ -0,0 +1,120 @@
+use crossterm::event::{ KeyCode, KeyEvent, KeyModifiers };
+
+use crate::utils::{ char_to_byte_idx, line_len_chars };
+use crate::word::{ prev_word_start, next_word_start };
+
+// Campo de texto de una sola linea (usado por los popups de Guardar/Abrir)
+#[derive(Clone, Default)]
+pub struct TextInput {
+  pub text: String,
+  pub cursor: usize, // en chars
+}
+
+impl TextInput {
+  pub fn set(&mut self, text: &str) {
+    self.text = text.to_string();
+    self.cursor = line_len_chars(&self.text);
+  }
+
+  pub fn clear(&mut self) {
+    self.text.clear();
+    self.cursor = 0;
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.text.is_empty()
+  }
+
+  pub fn insert(&mut self, c: char) {
+    let byte_idx = char_to_byte_idx(&self.text, self.cursor);
+    self.text.insert(byte_idx, c);
+    self.cursor += 1;
+  }
+
+  pub fn backspace(&mut self) {
+    if self.cursor > 0 {
+      let byte_idx = char_to_byte_idx(&self.text, self.cursor - 1);
+      self.text.remove(byte_idx);
+      self.cursor -= 1;
+    }
+  }
+
+  pub fn delete(&mut self) {
+    if self.cursor < line_len_chars(&self.text) {
+      let byte_idx = char_to_byte_idx(&self.text, self.cursor);
+      self.text.remove(byte_idx);
+    }
+  }
+
+  pub fn left(&mut self) {
+    self.cursor = self.cursor.saturating_sub(1);
+  }
+
+  pub fn right(&mut self) {
+    self.cursor = (self.cursor + 1).min(line_len_chars(&self.text));
+  }
+
+  pub fn home(&mut self) {
+    self.cursor = 0;
+  }
+
+  pub fn end(&mut self) {
+    self.cursor = line_len_chars(&self.text);
+  }
+
+  pub fn word_left(&mut self) {
+    self.cursor = self.prev_word_start();
+  }
+
+  pub fn word_right(&mut self) {
+    let chars: Vec<char> = self.text.chars().collect();
+    self.cursor = next_word_start(&chars, self.cursor, &[]);
+  }
+
+  // borra la palabra (o segmento de ruta) anterior al cursor, como Ctrl + W en una terminal
+  pub fn delete_word_back(&mut self) {
+    let start = self.prev_word_start();
+    let a = char_to_byte_idx(&self.text, start);
+    let b = char_to_byte_idx(&self.text, self.cursor);
+    self.text.replace_range(a..b, "");
+    self.cursor = start;
+  }
+
+  // teclas de edición comunes a todos los campos; devuelve false si la tecla no es de edición
+  pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    match key.code {
+      KeyCode::Left if ctrl => self.word_left(),
+      KeyCode::Right if ctrl => self.word_right(),
+      KeyCode::Left => self.left(),
+      KeyCode::Right => self.right(),
+      KeyCode::Home => self.home(),
+      KeyCode::End => self.end(),
+      KeyCode::Delete => self.delete(),
+      KeyCode::Backspace if ctrl => self.delete_word_back(),
+      KeyCode::Backspace => self.backspace(),
+      KeyCode::Char('w') if ctrl => self.delete_word_back(),
+      KeyCode::Char(c) if !ctrl => self.insert(c),
+      _ => return false,
+    }
+
+    true
+  }
+
+  // texto visible en un campo de `width` columnas y la columna del cursor dentro de el
+  pub fn visible(&self, width: usize) -> (String, usize) {
+    if width == 0 {
+      return (String::new(), 0);
+    }
+
+    let skip = (self.cursor + 1).saturating_sub(width);
+    let text: String = self.text.chars().skip(skip).take(width).collect();
+    (text, self.cursor - skip)
+  }
+
+  fn prev_word_start(&self) -> usize {
+    let chars: Vec<char> = self.text.chars().collect();
+    prev_word_start(&chars, self.cursor, &[])
+  }
+}
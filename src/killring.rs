@@ -0,0 +1,201 @@
+use crate::CursorPos;
+use crate::selection::{ delete_selection, has_selection, paste_text };
+use crate::utils::set_windows_clipboard;
+
+// Anillo de texto borrado (kill ring, como en Emacs): lo que borran los comandos de
+// palabra y de linea, y lo copiado o cortado, queda acá y en el portapapeles.
+// Ctrl + Y pega lo último y Alt + Y justo después lo cambia por la entrada anterior.
+// Borrados de teclas seguidas se juntan en una sola entrada, así Ctrl + Backspace
+// varias veces se pega de una vez.
+
+const MAX_ENTRIES: usize = 30;
+
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Last {
+  #[default]
+  Other,
+  Kill,
+  // texto pegado entre start y end, que era la entrada `index`
+  Yank { start: CursorPos, end: CursorPos, index: usize },
+}
+
+#[derive(Default)]
+pub struct KillRing {
+  entries: Vec<String>, // la más nueva al final
+  previous: Last,       // lo que hizo la tecla anterior
+  current: Last,        // lo que hace la tecla actual
+  block: bool,          // la última entrada se copió de una selección en bloque
+}
+
+impl KillRing {
+  // Llamar antes de cada comando del editor: solo se juntan borrados seguidos
+  pub fn start_command(&mut self) {
+    self.previous = std::mem::take(&mut self.current);
+  }
+
+  // Texto borrado por un comando; `backward` si estaba antes del cursor
+  pub fn kill(&mut self, text: String, backward: bool) {
+    match self.entries.last_mut() {
+      Some(last) if self.previous == Last::Kill => {
+        if backward {
+          last.insert_str(0, &text);
+        } else {
+          last.push_str(&text);
+        }
+      }
+      _ => self.entries.push(text),
+    }
+    self.block = false;
+    self.trim_and_copy();
+    self.current = Last::Kill;
+  }
+
+  // Texto copiado o cortado: siempre una entrada nueva
+  pub fn push(&mut self, text: String) {
+    if text.is_empty() {
+      return;
+    }
+    self.entries.push(text);
+    self.block = false;
+    self.trim_and_copy();
+  }
+
+  // Texto copiado o cortado de una selección en bloque: se pega como bloque
+  pub fn push_block(&mut self, text: String) {
+    if text.is_empty() {
+      return;
+    }
+    self.push(text);
+    self.block = true;
+  }
+
+  fn trim_and_copy(&mut self) {
+    if self.entries.len() > MAX_ENTRIES {
+      self.entries.remove(0);
+    }
+    if let Some(last) = self.entries.last() {
+      set_windows_clipboard(last.clone());
+    }
+  }
+
+  pub fn last(&self) -> Option<&str> {
+    self.entries.last().map(|s| s.as_str())
+  }
+
+  // la última entrada, si vino de una selección en bloque
+  pub fn last_block(&self) -> Option<&str> {
+    self.last().filter(|_| self.block)
+  }
+
+  // Ctrl + Y: pega la última entrada en el cursor
+  pub fn yank(&mut self, lines: &mut Vec<String>, cursor: &mut CursorPos, selection: &mut Option<CursorPos>) {
+    if let Some(index) = self.entries.len().checked_sub(1) {
+      self.insert(index, lines, cursor, selection);
+    }
+  }
+
+  // Alt + Y: si la tecla anterior pegó algo, lo reemplaza por la entrada anterior
+  pub fn yank_pop(&mut self, lines: &mut Vec<String>, cursor: &mut CursorPos, selection: &mut Option<CursorPos>) {
+    let Last::Yank { start, end, index } = self.previous else {
+      return;
+    };
+    if *cursor != end || has_selection(selection, cursor) {
+      return;
+    }
+
+    *selection = Some(start);
+    delete_selection(lines, cursor, selection);
+    let index = (index + self.entries.len() - 1) % self.entries.len();
+    self.insert(index, lines, cursor, selection);
+  }
+
+  fn insert(&mut self, index: usize, lines: &mut Vec<String>, cursor: &mut CursorPos, selection: &mut Option<CursorPos>) {
+    if selection.is_some() {
+      delete_selection(lines, cursor, selection);
+    }
+    let start = *cursor;
+    paste_text(lines, cursor, selection, &self.entries[index]);
+    self.current = Last::Yank { start, end: *cursor, index };
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn consecutive_kills_join() {
+    let mut ring = KillRing::default();
+    ring.start_command();
+    ring.kill("bar".to_string(), true);
+    ring.start_command();
+    ring.kill("foo ".to_string(), true);
+    ring.start_command();
+    ring.kill("!".to_string(), false);
+    assert_eq!(ring.last(), Some("foo bar!"));
+
+    // otra tecla en el medio corta la entrada
+    ring.start_command();
+    ring.start_command();
+    ring.kill("x".to_string(), false);
+    assert_eq!(ring.entries, ["foo bar!", "x"]);
+  }
+
+  #[test]
+  fn push_always_adds() {
+    let mut ring = KillRing::default();
+    ring.start_command();
+    ring.kill("a".to_string(), false);
+    ring.push("b".to_string());
+    ring.push(String::new());
+    assert_eq!(ring.entries, ["a", "b"]);
+  }
+
+  #[test]
+  fn block_entries_until_the_next_push() {
+    let mut ring = KillRing::default();
+    ring.push_block("a\nb".to_string());
+    assert_eq!(ring.last_block(), Some("a\nb"));
+    ring.push_block(String::new());
+    assert_eq!(ring.last_block(), Some("a\nb"));
+    ring.push("c".to_string());
+    assert_eq!(ring.last_block(), None);
+  }
+
+  #[test]
+  fn keeps_the_newest_entries() {
+    let mut ring = KillRing::default();
+    for i in 0..MAX_ENTRIES + 5 {
+      ring.push(i.to_string());
+    }
+    assert_eq!(ring.entries.len(), MAX_ENTRIES);
+    assert_eq!(ring.entries[0], "5");
+  }
+
+  #[test]
+  fn yank_pop_cycles_entries() {
+    let mut ring = KillRing::default();
+    for text in ["uno", "dos", "tres"] {
+      ring.push(text.to_string());
+    }
+    let mut lines = vec!["<>".to_string()];
+    let mut cursor = CursorPos { x: 1, y: 0 };
+    let mut selection = None;
+
+    ring.start_command();
+    ring.yank(&mut lines, &mut cursor, &mut selection);
+    assert_eq!(lines[0], "<tres>");
+    for expected in ["<dos>", "<uno>", "<tres>"] {
+      ring.start_command();
+      ring.yank_pop(&mut lines, &mut cursor, &mut selection);
+      assert_eq!(lines[0], expected);
+    }
+    assert_eq!(cursor.x, 5);
+
+    // sin un pegado justo antes no hace nada
+    ring.start_command();
+    ring.start_command();
+    ring.yank_pop(&mut lines, &mut cursor, &mut selection);
+    assert_eq!(lines[0], "<tres>");
+  }
+}
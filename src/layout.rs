@@ -0,0 +1,179 @@
+// Reparto de la pantalla en regiones (título, pestañas, texto, mensajes, estado y popups)
+// a partir del tamaño actual de la terminal. Si no hay lugar se van quitando barras
+// para que siempre quede al menos una fila de texto.
+
+use std::io;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Rect {
+  pub x: usize,
+  pub y: usize,
+  pub width: usize,
+  pub height: usize,
+}
+
+// filas de texto que se intentan conservar antes de mostrar las barras opcionales
+const MIN_TEXT_ROWS: usize = 3;
+
+// alto máximo de los popups con lista (Guardar, Abrir, Archivos abiertos)
+const LIST_POPUP_HEIGHT: usize = 20;
+const MIN_LIST_POPUP_HEIGHT: usize = 9;
+
+pub struct Layout {
+  pub width: usize,
+  pub height: usize,
+  pub title: Option<Rect>,
+  pub tabs: Option<Rect>,
+  pub text: Rect,
+  pub message: Option<Rect>,
+  pub status: Option<Rect>,
+}
+
+impl Layout {
+  pub fn new(width: usize, height: usize) -> Layout {
+    let mut free = height;
+    let mut take = |needed_after: usize| {
+      if free > needed_after {
+        free -= 1;
+        true
+      } else {
+        false
+      }
+    };
+
+    // en orden de prioridad
+    let has_status = take(1);
+    let has_message = take(MIN_TEXT_ROWS);
+    let has_tabs = take(MIN_TEXT_ROWS);
+    let has_title = take(MIN_TEXT_ROWS);
+
+    let row = |y: usize| Rect { x: 0, y, width, height: 1 };
+
+    let mut y = 0;
+    let title = has_title.then(|| { y += 1; row(y - 1) });
+    let tabs = has_tabs.then(|| { y += 1; row(y - 1) });
+    let text = Rect { x: 0, y, width, height: free };
+    y += free;
+    let message = has_message.then(|| { y += 1; row(y - 1) });
+    let status = has_status.then(|| row(y));
+
+    Layout { width, height, title, tabs, text, message, status }
+  }
+
+  pub fn current() -> io::Result<Layout> {
+    let (width, height) = crossterm::terminal::size()?;
+    Ok(Layout::new(width as usize, height as usize))
+  }
+
+  // rectángulo centrado para un popup, recortado a la pantalla
+  pub fn popup(&self, width: usize, height: usize) -> Rect {
+    let width = width.min(self.width);
+    let height = height.min(self.height);
+    Rect {
+      x: (self.width - width) / 2,
+      y: (self.height - height) / 2,
+      width,
+      height,
+    }
+  }
+
+  // alto de los popups con lista: crece con la terminal hasta LIST_POPUP_HEIGHT;
+  // en terminales chicas tapa las barras antes que quedarse sin filas
+  pub fn list_popup_height(&self) -> usize {
+    self.height.saturating_sub(4).clamp(MIN_LIST_POPUP_HEIGHT, LIST_POPUP_HEIGHT).min(self.height)
+  }
+}
+
+// Filas disponibles para la lista de un popup de `height` filas con `header` filas fijas
+// arriba (borde, título, espacio, footer y borde ocupan 5)
+pub fn popup_list_rows(height: usize, header: usize) -> usize {
+  height.saturating_sub(5 + header).max(1)
+}
+
+// Primer elemento visible para que `index` entre en una lista de `rows` filas
+pub fn scroll_to_show(scroll: usize, index: usize, rows: usize) -> usize {
+  if index < scroll {
+    index
+  } else if index >= scroll + rows {
+    index + 1 - rows
+  } else {
+    scroll
+  }
+}
+
+// Con paneles divididos cada vista lleva una fila de título arriba
+pub fn view_text_rect(rect: Rect, split: bool) -> Rect {
+  if split && rect.height > 1 {
+    Rect { y: rect.y + 1, height: rect.height - 1, ..rect }
+  } else {
+    rect
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // fila de cada barra (título, pestañas, mensajes, estado), None si no entra
+  fn rows(layout: &Layout) -> [Option<usize>; 4] {
+    [layout.title, layout.tabs, layout.message, layout.status].map(|r| r.map(|r| r.y))
+  }
+
+  #[test]
+  fn layout_all_bars() {
+    let layout = Layout::new(80, 24);
+    assert_eq!(rows(&layout), [Some(0), Some(1), Some(22), Some(23)]);
+    assert_eq!((layout.text.y, layout.text.height, layout.text.width), (2, 20, 80));
+  }
+
+  #[test]
+  fn layout_drops_bars_in_small_terminals() {
+    // primero el título, después las pestañas y los mensajes; el estado es lo último
+    let layout = Layout::new(80, 6);
+    assert_eq!(rows(&layout), [None, Some(0), Some(4), Some(5)]);
+    assert_eq!((layout.text.y, layout.text.height), (1, 3));
+
+    let layout = Layout::new(80, 4);
+    assert_eq!(rows(&layout), [None, None, None, Some(3)]);
+    assert_eq!(layout.text.height, 3);
+
+    let layout = Layout::new(80, 2);
+    assert_eq!(rows(&layout), [None, None, None, Some(1)]);
+    assert_eq!(layout.text.height, 1);
+  }
+
+  #[test]
+  fn layout_keeps_one_text_row() {
+    let layout = Layout::new(10, 1);
+    assert_eq!(rows(&layout), [None, None, None, None]);
+    assert_eq!((layout.text.y, layout.text.height), (0, 1));
+
+    let layout = Layout::new(10, 0);
+    assert_eq!(layout.text.height, 0);
+  }
+
+  #[test]
+  fn popup_is_centered_and_clipped() {
+    let layout = Layout::new(80, 24);
+    let popup = layout.popup(40, 10);
+    assert_eq!((popup.x, popup.y, popup.width, popup.height), (20, 7, 40, 10));
+
+    let popup = layout.popup(100, 50);
+    assert_eq!((popup.x, popup.y, popup.width, popup.height), (0, 0, 80, 24));
+  }
+
+  #[test]
+  fn list_popup_height_grows_with_the_terminal() {
+    assert_eq!(Layout::new(80, 50).list_popup_height(), LIST_POPUP_HEIGHT);
+    assert_eq!(Layout::new(80, 16).list_popup_height(), 12);
+    assert_eq!(Layout::new(80, 10).list_popup_height(), MIN_LIST_POPUP_HEIGHT);
+    assert_eq!(Layout::new(80, 5).list_popup_height(), 5);
+  }
+
+  #[test]
+  fn scroll_to_show_moves_only_when_needed() {
+    assert_eq!(scroll_to_show(5, 7, 10), 5);
+    assert_eq!(scroll_to_show(5, 2, 10), 2);
+    assert_eq!(scroll_to_show(5, 20, 10), 11);
+  }
+}
@@ -0,0 +1,256 @@
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+use crate::CursorPos;
+use crate::selection::{ get_selected_text, has_selection, line_range, paste_text };
+use crate::utils::line_len_chars;
+
+// Comandos sobre lineas enteras: duplicar, mover, unir y ordenar. El cursor y el
+// principio de la selección siguen al texto que se movió.
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LineOrder {
+  Sort,
+  SortInsensitive,
+  SortNumeric,
+  Reverse,
+  Unique,
+  UniqueInsensitive,
+  Shuffle,
+}
+
+// orden de las opciones del menú de Ctrl + K, O
+pub const ORDERS: [LineOrder; 7] = [
+  LineOrder::Sort,
+  LineOrder::SortInsensitive,
+  LineOrder::SortNumeric,
+  LineOrder::Reverse,
+  LineOrder::Unique,
+  LineOrder::UniqueInsensitive,
+  LineOrder::Shuffle,
+];
+
+impl LineOrder {
+  pub fn name(&self) -> &'static str {
+    match self {
+      LineOrder::Sort => "Ordenar",
+      LineOrder::SortInsensitive => "Ordenar sin distinguir mayusculas",
+      LineOrder::SortNumeric => "Ordenar por numero",
+      LineOrder::Reverse => "Invertir",
+      LineOrder::Unique => "Quitar repetidas",
+      LineOrder::UniqueInsensitive => "Quitar repetidas sin distinguir mayusculas",
+      LineOrder::Shuffle => "Mezclar",
+    }
+  }
+}
+
+// Con selección copia el texto seleccionado detrás de ella y selecciona la copia;
+// sin selección copia la linea debajo y baja el cursor a la copia
+pub fn duplicate(lines: &mut Vec<String>, cursor: &mut CursorPos, selection: &mut Option<CursorPos>) {
+  if has_selection(selection, cursor) {
+    let Some(start) = *selection else { return };
+    let Some(text) = get_selected_text(lines, cursor, selection) else { return };
+    let end = if (start.y, start.x) > (cursor.y, cursor.x) { start } else { *cursor };
+
+    *cursor = end;
+    *selection = None;
+    paste_text(lines, cursor, selection, &text);
+    *selection = Some(end);
+    return;
+  }
+
+  lines.insert(cursor.y + 1, lines[cursor.y].clone());
+  cursor.y += 1;
+}
+
+// Alt + Arriba / Abajo: intercambia las lineas del cursor o de la selección con la
+// de arriba o la de abajo
+pub fn move_lines(lines: &mut [String], cursor: &mut CursorPos, selection: &mut Option<CursorPos>, up: bool) {
+  let (first, last) = line_range(cursor, selection);
+  if (up && first == 0) || (!up && last + 1 >= lines.len()) {
+    return;
+  }
+
+  if up {
+    lines[first - 1..=last].rotate_left(1);
+  } else {
+    lines[first..=last + 1].rotate_right(1);
+  }
+
+  for pos in std::iter::once(&mut *cursor).chain(selection.iter_mut()) {
+    pos.y = if up { pos.y - 1 } else { pos.y + 1 };
+  }
+}
+
+// Ctrl + K, J: une la linea siguiente (o las lineas seleccionadas) a la actual. Los
+// espacios del punto de unión quedan en uno solo, o en ninguno antes de un cierre.
+pub fn join_lines(lines: &mut Vec<String>, cursor: &mut CursorPos, selection: &mut Option<CursorPos>) {
+  let (first, last) = line_range(cursor, selection);
+  let last = if last == first { first + 1 } else { last };
+  if last >= lines.len() {
+    return;
+  }
+
+  let mut joined = lines[first].clone();
+  let mut join_x = line_len_chars(&joined);
+  for next in lines.drain(first + 1..=last) {
+    let left = joined.trim_end().to_string();
+    let right = next.trim_start();
+    let space = !left.is_empty() && !right.is_empty() && !right.starts_with([')', ']', '}', ',', ';']);
+
+    join_x = line_len_chars(&left);
+    joined = if space { format!("{} {}", left, right) } else { format!("{}{}", left, right) };
+  }
+
+  lines[first] = joined;
+  *cursor = CursorPos { x: join_x, y: first };
+  *selection = None;
+}
+
+// Ordena, invierte, deja únicas o mezcla las lineas seleccionadas (todo el documento
+// si no hay lineas seleccionadas). Las lineas quedan seleccionadas.
+pub fn reorder(lines: &mut Vec<String>, cursor: &mut CursorPos, selection: &mut Option<CursorPos>, order: LineOrder) {
+  let (first, last) = match selection {
+    Some(start) if start.y != cursor.y => line_range(cursor, selection),
+    _ => (0, lines.len() - 1),
+  };
+
+  let mut block: Vec<String> = lines.drain(first..=last).collect();
+  match order {
+    LineOrder::Sort => block.sort(),
+    LineOrder::SortInsensitive => block.sort_by_cached_key(|line| line.to_lowercase()),
+    // las lineas sin número van al final, en el orden en que estaban
+    LineOrder::SortNumeric => block.sort_by(|a, b| {
+      match (leading_number(a), leading_number(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+      }
+    }),
+    LineOrder::Reverse => block.reverse(),
+    LineOrder::Unique => dedup_unsorted(&mut block, |line| line.to_string()),
+    LineOrder::UniqueInsensitive => dedup_unsorted(&mut block, |line| line.to_lowercase()),
+    LineOrder::Shuffle => shuffle(&mut block),
+  }
+
+  let new_last = first + block.len() - 1;
+  lines.splice(first..first, block);
+
+  *selection = Some(CursorPos { x: 0, y: first });
+  *cursor = CursorPos { x: line_len_chars(&lines[new_last]), y: new_last };
+}
+
+// Primer número de la linea (con signo y decimales): "  -3.5 kg" -> -3.5
+fn leading_number(line: &str) -> Option<f64> {
+  let start = line.find(|c: char| c.is_ascii_digit())?;
+  let negative = line[..start].ends_with('-');
+  let rest = &line[start..];
+  let int_len = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+  let frac_len = rest[int_len..].strip_prefix('.')
+    .map_or(0, |frac| frac.chars().take_while(|c| c.is_ascii_digit()).count());
+  let len = if frac_len > 0 { int_len + 1 + frac_len } else { int_len };
+  let value: f64 = rest[..len].parse().ok()?;
+  Some(if negative { -value } else { value })
+}
+
+// Quita las repetidas dejando la primera aparición de cada una
+fn dedup_unsorted(block: &mut Vec<String>, key: impl Fn(&str) -> String) {
+  let mut seen = std::collections::HashSet::new();
+  block.retain(|line| seen.insert(key(line)));
+}
+
+// Fisher-Yates con un xorshift sembrado con la hora: no hace falta más azar que eso
+fn shuffle(block: &mut [String]) {
+  let mut state = SystemTime::now().duration_since(UNIX_EPOCH).map_or(1, |d| d.as_nanos() as u64) | 1;
+  for i in (1..block.len()).rev() {
+    state ^= state << 13;
+    state ^= state >> 7;
+    state ^= state << 17;
+    block.swap(i, (state % (i as u64 + 1)) as usize);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn lines(text: &[&str]) -> Vec<String> {
+    text.iter().map(|l| l.to_string()).collect()
+  }
+
+  #[test]
+  fn leading_numbers() {
+    assert_eq!(leading_number("  -3.5 kg"), Some(-3.5));
+    assert_eq!(leading_number("item 42."), Some(42.0));
+    assert_eq!(leading_number("v1.2.3"), Some(1.2));
+    assert_eq!(leading_number("sin numero"), None);
+  }
+
+  #[test]
+  fn join_collapses_spaces() {
+    let mut text = lines(&["foo(  ", "   bar", "  )", "x"]);
+    let mut cursor = CursorPos { x: 1, y: 2 };
+    let mut selection = Some(CursorPos { x: 1, y: 0 });
+    join_lines(&mut text, &mut cursor, &mut selection);
+    assert_eq!(text, ["foo( bar)", "x"]);
+    // el cursor queda en el último punto de unión
+    assert_eq!((cursor.x, cursor.y), (8, 0));
+    assert!(selection.is_none());
+  }
+
+  #[test]
+  fn join_next_line_without_selection() {
+    let mut text = lines(&["a", "", "b"]);
+    let mut cursor = CursorPos { x: 0, y: 0 };
+    let mut selection = None;
+    join_lines(&mut text, &mut cursor, &mut selection);
+    assert_eq!(text, ["a", "b"]);
+    join_lines(&mut text, &mut cursor, &mut selection);
+    assert_eq!(text, ["a b"]);
+    join_lines(&mut text, &mut cursor, &mut selection);
+    assert_eq!(text, ["a b"]);
+  }
+
+  #[test]
+  fn move_lines_carries_cursor_and_selection() {
+    let mut text = lines(&["a", "b", "c"]);
+    let mut cursor = CursorPos { x: 1, y: 1 };
+    let mut selection = Some(CursorPos { x: 0, y: 0 });
+    move_lines(&mut text, &mut cursor, &mut selection, false);
+    assert_eq!(text, ["c", "a", "b"]);
+    assert_eq!((cursor.y, selection.map(|s| s.y)), (2, Some(1)));
+    move_lines(&mut text, &mut cursor, &mut selection, false);
+    assert_eq!(text, ["c", "a", "b"]);
+  }
+
+  #[test]
+  fn duplicate_line_or_selection() {
+    let mut text = lines(&["ab"]);
+    let mut cursor = CursorPos { x: 1, y: 0 };
+    let mut selection = None;
+    duplicate(&mut text, &mut cursor, &mut selection);
+    assert_eq!((text.clone(), cursor.y), (lines(&["ab", "ab"]), 1));
+
+    let mut selection = Some(CursorPos { x: 0, y: 1 });
+    duplicate(&mut text, &mut cursor, &mut selection);
+    assert_eq!(text, ["ab", "aab"]);
+    assert_eq!((selection.map(|s| s.x), cursor.x), (Some(1), 2));
+  }
+
+  #[test]
+  fn reorder_lines() {
+    let sorted = |order: LineOrder, text: &[&str]| {
+      let mut text = lines(text);
+      reorder(&mut text, &mut CursorPos { x: 0, y: 0 }, &mut None, order);
+      text
+    };
+    assert_eq!(sorted(LineOrder::Sort, &["b", "B", "a"]), ["B", "a", "b"]);
+    assert_eq!(sorted(LineOrder::SortInsensitive, &["b", "B", "a"]), ["a", "b", "B"]);
+    assert_eq!(sorted(LineOrder::SortNumeric, &["x", "10 b", "-2", "9.5", "y"]), ["-2", "9.5", "10 b", "x", "y"]);
+    assert_eq!(sorted(LineOrder::UniqueInsensitive, &["a", "b", "A", "b"]), ["a", "b"]);
+
+    let mut shuffled = sorted(LineOrder::Shuffle, &["1", "2", "3", "4"]);
+    shuffled.sort();
+    assert_eq!(shuffled, ["1", "2", "3", "4"]);
+  }
+}
@@ -1,37 +1,125 @@
 use std::io::{ stdout };
 use crossterm::execute;
-use crossterm::event::{ Event, KeyCode, KeyEventKind, KeyModifiers, read };
-use crossterm::terminal::{ EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode, size };
+use crossterm::event::{ Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, poll, read };
+use crossterm::terminal::{ EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode };
 
 mod cursor;
 use cursor::CursorPos;
 
 mod draw;
-use draw::draw;
+use draw::{ draw, gutter_width };
 
 mod selection;
-use selection::{ start_selection_if_needed, has_selection, delete_selection, get_selected_text, paste_text };
+use selection::{ start_selection_if_needed, has_selection, delete_selection, paste_text, take_selection };
 
 mod moves;
-use moves::{ move_word_left, move_word_right };
+use moves::{ move_word_left, move_word_right, move_subword_left, move_subword_right };
+
+mod word;
 
 mod utils;
-use utils::{ char_to_byte_idx, line_len_chars, set_windows_clipboard, get_windows_clipboard };
+use utils::{ char_to_byte_idx, col_to_char, display_col, grapheme_width, graphemes, line_len_chars, next_grapheme, prev_grapheme, get_windows_clipboard };
 
 mod file;
-use file::{ save_file, open_file, list_directory };
+use file::{
+  save_file, list_directory, resolve_path, complete_path,
+  create_folder, rename_entry, duplicate_entry, move_to_trash, copy_name,
+};
 use std::path::Path;
 
 mod popup;
-use popup::PopupMode;
+use popup::{ Menu, Popup, PopupMode, SavePrompt };
+
+mod input;
+use input::TextInput;
+
+mod args;
+use args::{ parse_args, USAGE };
+
+mod config;
+use config::Config;
+
+mod theme;
+
+mod indent;
+use indent::{ Indent, insert_tab, indent_lines, outdent_lines, backspace_indent, is_multiline, reindent };
+
+mod editorconfig;
+
+mod case;
+use case::CASES;
+
+mod killring;
+use killring::KillRing;
+
+mod lineops;
+use lineops::ORDERS;
+
+mod multicursor;
+use multicursor::Rounds;
+
+mod block;
+
+mod select;
+
+mod brackets;
+
+mod document;
+use document::Document;
+
+mod message;
+use message::MessageLog;
+
+mod layout;
+use layout::{ Layout, view_text_rect };
+
+mod syntax;
+
+mod panes;
+use panes::{ Direction, Panes, SplitDir, View };
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-  // Argumento opcional: directorio de trabajo inicial
-  // Uso: camelCaseEditor.exe [directorio]
-  // Ej:  camelCaseEditor.exe C:\Users\nacho\Desktop\proyecto
+  // Uso: camelCaseEditor.exe [opciones] [archivo[:linea[:col]] | +linea archivo | directorio]...
+  // Ej:  camelCaseEditor.exe src\main.rs:120:5
+  // Ej:  camelCaseEditor.exe --readonly +40 README.md
   // Ej:  camelCaseEditor.exe ../proyecto
-  let mut current_dir = std::env::args()
-    .nth(1)
+  let args = match parse_args(std::env::args().skip(1)) {
+    Ok(args) => args,
+    Err(e) => {
+      eprintln!("camelCaseEditor: {}\n\n{}", e, USAGE);
+      std::process::exit(2);
+    }
+  };
+
+  if args.help {
+    println!("{}", USAGE);
+    return Ok(());
+  }
+
+  if args.version {
+    println!("camelCaseEditor {}", env!("CARGO_PKG_VERSION"));
+    return Ok(());
+  }
+
+  if let Some(cwd) = &args.cwd {
+    std::env::set_current_dir(cwd).map_err(|e| format!("--cwd {}: {}", cwd, e))?;
+  }
+
+  let mut messages = MessageLog::default();
+
+  let (mut config, config_warnings) = Config::load(args.config.as_deref())?;
+  for warning in config_warnings {
+    messages.warn(format!("config: {}", warning));
+  }
+
+  // gramáticas TextMate/Sublime del usuario; las que fallan se avisan y se ignoran
+  for error in syntax::load_grammars(&file::app_dir().join("syntaxes")) {
+    messages.warn(format!("sintaxis: {}", error));
+  }
+
+  // Argumento opcional: directorio de trabajo inicial
+  let mut current_dir = args.dir
+    .clone()
     .map(|arg| {
       let path = Path::new(&arg);
       // Convertir a ruta absoluta para evitar confusiones al navegar
@@ -41,30 +129,134 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     })
     .unwrap_or_else(|| ".".to_string());
 
+  // archivos pedidos por linea de comandos; los que no existen se crean al guardar
+  let mut docs: Vec<Document> = Vec::new();
+  for file in &args.files {
+    let mut doc = Document::open(&file.path, &config).map_err(|e| format!("{}: {}", file.path, e))?;
+    doc.readonly = args.readonly;
+    doc.go_to(file.line.unwrap_or(1).saturating_sub(1), file.col.unwrap_or(1).saturating_sub(1));
+    docs.push(doc);
+  }
+  if docs.is_empty() {
+    docs.push(Document::new());
+  }
+  let mut panes = Panes::new(0);
+
   enable_raw_mode()?;
 
   execute!(stdout(),EnterAlternateScreen)?;
 
-  let mut lines: Vec<String> = vec![String::new()];
-
   let mut popup: Option<PopupMode> = None;
-  let mut popup_input: String = String::new();
+  let mut popup_input: TextInput = TextInput::default();
+
+  follow_cursor(&docs, &mut panes, &config)?;
+
+  // Ctrl + K inicia un atajo de dos teclas (paneles, etc.)
+  let mut chord = false;
+
+  // texto borrado y copiado, compartido entre documentos
+  let mut kill_ring = KillRing::default();
 
-  let mut cursor: CursorPos = CursorPos{ x: 0, y: 0 };
-  let mut selection_start: Option<CursorPos> = None;
+  draw(&docs, &panes, &popup, &popup_input, &messages, &config, &current_dir)?;
 
-  let mut scroll_x: usize = 0;
-  let mut scroll_y: usize = 0;
+  'events: loop {
+    // con un mensaje a la vista se espera solo hasta que venza
+    if let Some(time_left) = messages.time_left()
+      && !poll(time_left)? {
+      messages.dismiss();
+      draw(&docs, &panes, &popup, &popup_input, &messages, &config, &current_dir)?;
+      continue;
+    }
+
+    let event = read()?;
 
-  draw(&lines, &cursor, selection_start, scroll_x, scroll_y, 2, &popup, &popup_input, &current_dir)?;
+    // la terminal cambió de tamaño: recalcular todo y redibujar enseguida
+    if let Event::Resize(..) = event {
+      follow_cursor(&docs, &mut panes, &config)?;
+      draw(&docs, &panes, &popup, &popup_input, &messages, &config, &current_dir)?;
+      continue;
+    }
 
-  loop {
-    if let Event::Key(key) = read()? {
+    if let Event::Key(key) = event {
       if key.kind != KeyEventKind::Press {
         continue;
       }
 
-      if !popup.is_none() {
+      // documento del panel enfocado
+      let active = panes.view().doc;
+
+      // pregunta pendiente dentro del popup de Guardar (sobrescribir, renombrar, etc.)
+      if let Some(PopupMode::Save { prompt: prompt_slot @ Some(_), .. }) = &mut popup {
+        let is_question = matches!(prompt_slot, Some(SavePrompt::Overwrite(_)) | Some(SavePrompt::Delete(_)));
+        let confirmed = match key.code {
+          KeyCode::Enter => true,
+          KeyCode::Char('s' | 'S' | 'y' | 'Y') => is_question,
+          _ => false,
+        };
+        let cancelled = key.code == KeyCode::Esc
+          || (is_question && matches!(key.code, KeyCode::Char('n' | 'N')));
+
+        if cancelled {
+          *prompt_slot = None;
+        } else if confirmed {
+          match prompt_slot.take() {
+            Some(SavePrompt::Overwrite(save_path)) => {
+              save_document(&mut docs[active], save_path, &mut messages);
+              popup = None;
+              popup_input.clear();
+            }
+            Some(prompt) => {
+              match run_file_action(prompt, &current_dir) {
+                Ok(select) => match list_directory(&current_dir) {
+                  Ok(entries) => {
+                    let selected = select.and_then(|name| {
+                      entries.iter().position(|e| e.trim_end_matches('/') == name)
+                    });
+                    let scroll_y = selected.unwrap_or(0).saturating_sub(4);
+                    popup = Some(PopupMode::Save { selected, entries, scroll_y, prompt: None });
+                  }
+                  Err(e) => messages.error(format!("Error al enlistar -> {}", e)),
+                },
+                Err(e) => messages.error(format!("Error en el archivo -> {}", e)),
+              }
+            }
+            None => {}
+          }
+        } else if let Some(input) = prompt_slot.as_mut().and_then(|p| p.input_mut()) {
+          input.handle_key(&key);
+        }
+
+        draw(&docs, &panes, &popup, &popup_input, &messages, &config, &current_dir)?;
+        continue;
+      }
+
+      // confirmar el cierre de un archivo con cambios sin guardar
+      if let Some(PopupMode::ConfirmClose { index, .. }) = popup {
+        match key.code {
+          KeyCode::Char('s' | 'S' | 'y' | 'Y') | KeyCode::Enter => {
+            close_document(&mut docs, &mut panes, index);
+            popup = None;
+          }
+          KeyCode::Char('n' | 'N') | KeyCode::Esc => popup = None,
+          _ => {}
+        }
+
+        draw(&docs, &panes, &popup, &popup_input, &messages, &config, &current_dir)?;
+        continue;
+      }
+
+      if popup.is_some() {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let in_save = matches!(popup, Some(PopupMode::Save { .. }));
+
+        // entrada del popup de Guardar sobre la que actúan F2 / Supr / Ctrl + D
+        let save_entry = match &popup {
+          Some(PopupMode::Save { selected: Some(i), entries, .. }) if entries[*i] != ".." => {
+            Some(entries[*i].trim_end_matches('/').to_string())
+          }
+          _ => None,
+        };
+
         match key.code {
           KeyCode::Esc => {
             popup = None;
@@ -76,13 +268,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
               Some(PopupMode::Save { selected, ref entries, .. }) => {
                 match selected {
                   // "Guardar como:" está seleccionado: guardar con el nombre del input
+                  // (relativo al directorio actual o absoluto, puede incluir subcarpetas)
+                  None if popup_input.is_empty() => {}
                   None => {
-                    let save_path = Path::new(&current_dir).join(&popup_input).to_string_lossy().to_string();
-                    if let Err(e) = save_file(&save_path, &lines) {
-                        eprintln!("Error al guardar -> {}", e);
+                    let save_path = resolve_path(&current_dir, &popup_input.text);
+                    let is_own_file = docs[active].path.as_deref().is_some_and(|path| same_file(path, &save_path));
+
+                    if Path::new(&save_path).is_file() && !is_own_file {
+                      // el archivo ya existe: pedir confirmación antes de sobrescribir
+                      if let Some(PopupMode::Save { prompt, .. }) = &mut popup {
+                        *prompt = Some(SavePrompt::Overwrite(save_path));
+                      }
+                    } else {
+                      save_document(&mut docs[active], save_path, &mut messages);
+                      popup = None;
+                      popup_input.clear();
                     }
-                    popup = None;
-                    popup_input.clear();
                   }
                   // Una entrada del directorio está seleccionada
                   Some(selected) => {
@@ -105,13 +306,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         match list_directory(&next_dir) {
                             Ok(new_entries) => {
                                 current_dir = next_dir;
-                                popup = Some(PopupMode::Save { selected: None, entries: new_entries, scroll_y: 0 });
+                                popup = Some(PopupMode::Save { selected: None, entries: new_entries, scroll_y: 0, prompt: None });
                             }
-                            Err(e) => eprintln!("Error al enlistar -> {}", e),
+                            Err(e) => messages.error(format!("Error al enlistar -> {}", e)),
                         }
                     } else {
                         // Seleccionó un archivo existente: poner su nombre en el input y subir a "Guardar como:"
-                        popup_input = entry.clone();
+                        popup_input.set(&entry);
                         if let Some(PopupMode::Save { selected, .. }) = &mut popup {
                             *selected = None;
                         }
@@ -121,7 +322,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
               }
           
               Some(PopupMode::Open { selected, ref entries, .. }) => {
-                let entry = entries[selected].clone();
+                // si se escribió una ruta se usa esa, si no la entrada seleccionada
+                let entry = if popup_input.is_empty() {
+                  entries[selected].clone()
+                } else {
+                  popup_input.text.clone()
+                };
                 let full_path = Path::new(&current_dir).join(&entry);
                 let full_path_str = full_path.to_string_lossy().to_string();
 
@@ -139,31 +345,52 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
                     match list_directory(&next_dir) {
                         Ok(new_entries) => {
+                            popup = Some(PopupMode::open(new_entries, &next_dir));
                             current_dir = next_dir;
-                            popup = Some(PopupMode::Open { selected: 0, entries: new_entries, scroll_y: 0 });
+                            popup_input.clear();
                         }
-                        Err(e) => eprintln!("Error al enlistar -> {}", e),
+                        Err(e) => messages.error(format!("Error al enlistar -> {}", e)),
                     }
+                } else if let Some(open_idx) = docs.iter().position(|d| {
+                    d.path.as_deref().is_some_and(|path| same_file(path, &full_path_str))
+                }) {
+                    // ya está abierto: ir a su pestaña
+                    show_document(&mut panes, &mut docs, open_idx);
+                    popup = None;
+                    popup_input.clear();
                 } else {
-                    match open_file(&full_path_str) {
-                        Ok(new_lines) => {
-                          lines = new_lines;
-                          cursor = CursorPos { x: 0, y: 0 };
-                          scroll_x = 0;
-                          scroll_y = 0;
-                          selection_start = None;
+                    match Document::open(&full_path_str, &config) {
+                        Ok(doc) => {
+                          // reemplazar el documento vacío inicial, si no abrir uno nuevo
+                          if docs[active].is_blank() {
+                            docs[active] = doc;
+                          } else {
+                            docs.push(doc);
+                            let index = docs.len() - 1;
+                            show_document(&mut panes, &mut docs, index);
+                          }
                           popup = None;
                           popup_input.clear();
                           if let Some(parent) = Path::new(&full_path_str).parent() {
                               current_dir = parent.to_string_lossy().to_string();
                           }
                         }
-                        Err(e) => eprintln!("Error al abrir -> {}", e),
+                        Err(e) => messages.error(format!("Error al abrir -> {}", e)),
                     }
                 }
               }
           
-              Some(PopupMode::Help) => {
+              Some(PopupMode::Buffers { selected, .. }) => {
+                show_document(&mut panes, &mut docs, selected);
+                popup = None;
+              }
+
+              Some(PopupMode::Menu { menu, selected }) => {
+                popup = None;
+                run_menu(menu, selected, &mut docs[active], &config, &mut messages);
+              }
+
+              Some(PopupMode::Help { .. }) | Some(PopupMode::Messages { .. }) | Some(PopupMode::ConfirmClose { .. }) => {
                 popup = None;
                 popup_input.clear();
               }
@@ -174,13 +401,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
           KeyCode::Up => {
             if let Some(mode) = &mut popup {
+                let max_visible = mode.list_rows(&Layout::current()?);
                 match mode {
-                    PopupMode::Save { selected, entries, scroll_y } => {
+                    PopupMode::Save { selected, entries, scroll_y, .. } => {
                         match *selected {
                             // desde "Guardar como:" salta al último archivo
                             None => {
                                 *selected = Some(entries.len().saturating_sub(1));
-                                let max_visible = 5;
                                 let idx = entries.len().saturating_sub(1);
                                 if idx >= *scroll_y + max_visible {
                                     *scroll_y = idx + 1 - max_visible;
@@ -190,20 +417,30 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             Some(0) => { *selected = None; *scroll_y = 0; }
                             Some(ref mut i) => {
                                 *i -= 1;
-                                //let max_visible = 5;
                                 if *i < *scroll_y {
                                     *scroll_y = *i;
                                 }
                             }
                         }
                     }
-                    PopupMode::Open { selected, entries, scroll_y } => {
+                    PopupMode::Buffers { selected, names } => {
+                        *selected = if *selected > 0 { *selected - 1 } else { names.len() - 1 };
+                    }
+                    PopupMode::Menu { menu, selected } => {
+                        *selected = if *selected > 0 { *selected - 1 } else { menu.items().len() - 1 };
+                    }
+                    PopupMode::Help { scroll } => {
+                        *scroll = scroll.saturating_sub(1);
+                    }
+                    PopupMode::Messages { selected, .. } => {
+                        *selected = selected.saturating_sub(1);
+                    }
+                    PopupMode::Open { selected, entries, scroll_y, .. } => {
                         if *selected > 0 {
                             *selected -= 1;
                         } else {
                             *selected = entries.len().saturating_sub(1);
                         }
-                        let max_visible = 6;
                         if *selected < *scroll_y {
                             *scroll_y = *selected;
                         } else if *selected >= *scroll_y + max_visible {
@@ -212,13 +449,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                     _ => {}
                 }
+                mode.refresh_preview(&current_dir);
             }
           }
 
           KeyCode::Down => {
             if let Some(mode) = &mut popup {
+                let max_visible = mode.list_rows(&Layout::current()?);
                 match mode {
-                    PopupMode::Save { selected, entries, scroll_y } => {
+                    PopupMode::Save { selected, entries, scroll_y, .. } => {
                         match *selected {
                             // desde "Guardar como:" baja al primer archivo
                             None => {
@@ -234,20 +473,31 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             }
                             Some(ref mut i) => {
                                 *i += 1;
-                                let max_visible = 5;
                                 if *i >= *scroll_y + max_visible {
                                     *scroll_y = *i + 1 - max_visible;
                                 }
                             }
                         }
                     }
-                    PopupMode::Open { selected, entries, scroll_y } => {
+                    PopupMode::Buffers { selected, names } => {
+                        *selected = if *selected + 1 < names.len() { *selected + 1 } else { 0 };
+                    }
+                    PopupMode::Menu { menu, selected } => {
+                        *selected = if *selected + 1 < menu.items().len() { *selected + 1 } else { 0 };
+                    }
+                    PopupMode::Help { scroll } => {
+                        let total = Popup::help().lines.len();
+                        *scroll = (*scroll + 1).min(total.saturating_sub(max_visible));
+                    }
+                    PopupMode::Messages { selected, lines } => {
+                        *selected = (*selected + 1).min(lines.len().saturating_sub(1));
+                    }
+                    PopupMode::Open { selected, entries, scroll_y, .. } => {
                         if *selected + 1 < entries.len() {
                             *selected += 1;
                         } else {
                             *selected = 0;
                         }
-                        let max_visible = 6;
                         if *selected < *scroll_y {
                             *scroll_y = *selected;
                         } else if *selected >= *scroll_y + max_visible {
@@ -256,40 +506,239 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                     _ => {}
                 }
+                mode.refresh_preview(&current_dir);
             }
           }
       
-          KeyCode::Backspace => {
-            popup_input.pop();
+          // cerrar el archivo resaltado en la lista
+          KeyCode::Char('w') if ctrl && matches!(popup, Some(PopupMode::Buffers { .. })) => {
+            if let Some(PopupMode::Buffers { selected, .. }) = popup {
+              popup = request_close(&mut docs, &mut panes, selected);
+              if popup.is_none() {
+                popup = Some(PopupMode::buffers(&docs, selected.min(docs.len() - 1)));
+              }
+            }
+          }
+
+          // administración de archivos en el popup de Guardar
+          KeyCode::Char('n') if ctrl && in_save => {
+            if let Some(PopupMode::Save { prompt, .. }) = &mut popup {
+              *prompt = Some(SavePrompt::NewFolder(TextInput::default()));
+            }
+          }
+
+          KeyCode::F(2) if save_entry.is_some() => {
+            if let (Some(PopupMode::Save { prompt, .. }), Some(entry)) = (&mut popup, save_entry) {
+              let mut input = TextInput::default();
+              input.set(&entry);
+              *prompt = Some(SavePrompt::Rename { entry, input });
+            }
+          }
+
+          KeyCode::Char('d') if ctrl && save_entry.is_some() => {
+            if let (Some(PopupMode::Save { prompt, .. }), Some(entry)) = (&mut popup, save_entry) {
+              let mut input = TextInput::default();
+              input.set(&copy_name(&entry));
+              *prompt = Some(SavePrompt::Duplicate { entry, input });
+            }
+          }
+
+          KeyCode::Delete if save_entry.is_some() => {
+            if let (Some(PopupMode::Save { prompt, .. }), Some(entry)) = (&mut popup, save_entry) {
+              *prompt = Some(SavePrompt::Delete(entry));
+            }
+          }
+
+          // completar la ruta contra el sistema de archivos
+          KeyCode::Tab => {
+            if matches!(popup, Some(PopupMode::Save { .. }) | Some(PopupMode::Open { .. }))
+              && let Some(completed) = complete_path(&current_dir, &popup_input.text) {
+              popup_input.set(&completed);
+            }
           }
       
-          KeyCode::Char(c) => {
-            popup_input.push(c);
+          // edición del campo de texto
+          _ => {
+            popup_input.handle_key(&key);
           }
+        }
       
+        draw(&docs, &panes, &popup, &popup_input, &messages, &config, &current_dir)?;
+        continue;
+      }
+
+      let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+      // segunda tecla de Ctrl + K
+      if chord {
+        chord = false;
+        kill_ring.start_command();
+        docs[active].block = None;
+        let area = Layout::current()?.text;
+
+        match key.code {
+          // paneles
+          KeyCode::Char('v' | 'V' | '\\') => change_view(&mut panes, &mut docs, |p| p.split(SplitDir::Columns)),
+          KeyCode::Char('h' | 'H' | 's' | 'S') => change_view(&mut panes, &mut docs, |p| p.split(SplitDir::Rows)),
+          KeyCode::Char('x' | 'X') => change_view(&mut panes, &mut docs, Panes::close),
+          KeyCode::Char('+' | '=') => panes.resize(5),
+          KeyCode::Char('-') => panes.resize(-5),
+          KeyCode::Left => change_view(&mut panes, &mut docs, |p| p.focus(Direction::Left, area)),
+          KeyCode::Right => change_view(&mut panes, &mut docs, |p| p.focus(Direction::Right, area)),
+          KeyCode::Up => change_view(&mut panes, &mut docs, |p| p.focus(Direction::Up, area)),
+          KeyCode::Down => change_view(&mut panes, &mut docs, |p| p.focus(Direction::Down, area)),
+          // historial de mensajes
+          KeyCode::Char('l' | 'L') => popup = Some(PopupMode::messages(&messages)),
+          // numeros de linea: absolutos -> relativos -> ocultos
+          KeyCode::Char('g' | 'G') => {
+            config.line_numbers = config.line_numbers.next();
+            messages.info(format!("Numeros de linea {}", config.line_numbers.name()));
+          }
+          // indentación del archivo
+          KeyCode::Char('i' | 'I') => popup = Some(PopupMode::Menu { menu: Menu::Indent, selected: 0 }),
+          // convertir entre camelCase, snake_case, etc.
+          KeyCode::Char('c' | 'C') => popup = Some(PopupMode::Menu { menu: Menu::Case, selected: 0 }),
+          // ordenar, invertir, quitar repetidas o mezclar lineas
+          KeyCode::Char('o' | 'O') => popup = Some(PopupMode::Menu { menu: Menu::Lines, selected: 0 }),
+          // duplicar / unir lineas
+          KeyCode::Char('d' | 'D' | 'j' | 'J') if !docs[active].readonly => {
+            docs[active].others.clear();
+            let Document { lines, cursor, selection_start, .. } = &mut docs[active];
+            if matches!(key.code, KeyCode::Char('d' | 'D')) {
+              lineops::duplicate(lines, cursor, selection_start);
+            } else {
+              lineops::join_lines(lines, cursor, selection_start);
+            }
+          }
+          // borrar hasta el principio / final de la linea, o las lineas enteras
+          KeyCode::Backspace | KeyCode::Delete | KeyCode::Char('k' | 'K') if !docs[active].readonly => {
+            let tab_width = docs[active].tab_width(&config);
+            docs[active].others.clear();
+            kill_line(&mut docs[active], key.code, tab_width, &mut kill_ring);
+          }
+          // un cursor en cada aparición de la selección o de la palabra
+          KeyCode::Char('a' | 'A') => {
+            let word_chars = docs[active].word_chars(&config);
+            let count = multicursor::select_all_occurrences(&mut docs[active], &word_chars);
+            messages.info(format!("{} apariciones", count));
+          }
+          // ir al paréntesis que corresponde
+          KeyCode::Char('m' | 'M') => {
+            let jumped = brackets::jump(&mut docs[active]);
+            if !jumped {
+              messages.warn("No hay parentesis con pareja en el cursor");
+            }
+          }
+          // seleccionar la palabra del cursor / el contenido de paréntesis o comillas
+          KeyCode::Char('w' | 'W') => {
+            docs[active].others.clear();
+            let word_chars = docs[active].word_chars(&config);
+            let Document { lines, cursor, selection_start, .. } = &mut docs[active];
+            if !select::select_word(lines, cursor, selection_start, &word_chars) {
+              messages.warn("No hay palabra en el cursor");
+            }
+          }
+          KeyCode::Char('b' | 'B') => {
+            docs[active].others.clear();
+            let Document { lines, cursor, selection_start, .. } = &mut docs[active];
+            if !select::select_brackets(lines, cursor, selection_start) {
+              messages.warn("No hay parentesis ni comillas alrededor");
+            }
+          }
+          // siguiente tema de colores
+          KeyCode::Char('t' | 'T') => match config.theme.next() {
+            Ok(theme) => {
+              config.theme = theme;
+              messages.info(format!("Tema {}", config.theme.name));
+            }
+            Err(e) => messages.error(format!("tema: {}", e)),
+          },
           _ => {}
         }
-      
-        draw(
-          &lines,
-          &cursor,
-          selection_start,
-          scroll_x,
-          scroll_y,
-          2,
-          &popup,
-          &popup_input,
-          &current_dir
-        )?;
+
+        follow_cursor(&docs, &mut panes, &config)?;
+        draw(&docs, &panes, &popup, &popup_input, &messages, &config, &current_dir)?;
+        continue;
+      }
+
+      if ctrl && key.code == KeyCode::Char('k') {
+        chord = true;
         continue;
       }
 
+      kill_ring.start_command();
+
+      // en modo solo lectura se ignora todo lo que no sea moverse, seleccionar o copiar
+      if docs[active].readonly && !is_readonly_key(&key) {
+        continue;
+      }
+
+      // cambiar de archivo: Ctrl + Tab / Ctrl + Shift + Tab o Ctrl + AvPag / RePag
+      match key.code {
+        KeyCode::Tab | KeyCode::PageDown if ctrl => {
+          let next = (active + 1) % docs.len();
+          show_document(&mut panes, &mut docs, next);
+          follow_cursor(&docs, &mut panes, &config)?;
+          draw(&docs, &panes, &popup, &popup_input, &messages, &config, &current_dir)?;
+          continue;
+        }
+        KeyCode::BackTab | KeyCode::PageUp if ctrl => {
+          let prev = (active + docs.len() - 1) % docs.len();
+          show_document(&mut panes, &mut docs, prev);
+          follow_cursor(&docs, &mut panes, &config)?;
+          draw(&docs, &panes, &popup, &popup_input, &messages, &config, &current_dir)?;
+          continue;
+        }
+        _ => {}
+      }
+
+      let tab_width = docs[active].tab_width(&config);
+      let word_chars = docs[active].word_chars(&config);
+
+      // selección en bloque: Alt + Shift + Arriba / Abajo, y con el bloque ya empezado
+      // también Izq / Der (si no, son las de subpalabra)
+      let alt_shift = !ctrl && key.modifiers.contains(KeyModifiers::ALT | KeyModifiers::SHIFT);
+      let block_key = match key.code {
+        KeyCode::Up | KeyCode::Down => alt_shift,
+        KeyCode::Left | KeyCode::Right => alt_shift && docs[active].block.is_some(),
+        _ => false,
+      };
+      if block_key {
+        block::extend(&mut docs[active], key.code, tab_width);
+        follow_cursor(&docs, &mut panes, &config)?;
+        draw(&docs, &panes, &popup, &popup_input, &messages, &config, &current_dir)?;
+        continue;
+      }
+
+      // lo escrito en un bloque queda alineado aunque haya lineas cortas
+      let from_block = docs[active].block.is_some();
+      let typing = matches!(key.code, KeyCode::Char(_) | KeyCode::Tab) && !key.modifiers.contains(KeyModifiers::ALT);
+      if from_block && typing && is_edit_key(&key) {
+        block::pad(&mut docs[active], tab_width);
+      }
+      if !(ctrl && key.code == KeyCode::Char('c')) {
+        docs[active].block = None;
+      }
+
+      // con varios cursores, las teclas de edición y movimiento se repiten en cada uno;
+      // las demás que cambian el texto dejan un solo cursor
+      let repeat = is_multi_cursor_key(&key);
+      if !repeat && is_edit_key(&key) {
+        docs[active].others.clear();
+      }
+      let copied = if ctrl && matches!(key.code, KeyCode::Char('c' | 'x')) {
+        multicursor::selected_text(&docs[active], from_block)
+      } else {
+        None
+      };
+
+      let Document { lines, cursor, selection_start, others, path: file_path, .. } = &mut docs[active];
       match key.code {
         // salir
         KeyCode::Char('q') 
           if key.modifiers.contains(KeyModifiers::CONTROL)
           /* && key.modifiers.contains(KeyModifiers::ALT) */ => {
-          break;
+          break 'events;
         },
 
         // ayuda
@@ -297,36 +746,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
           if key.modifiers.contains(KeyModifiers::CONTROL)
           /* && key.modifiers.contains(KeyModifiers::ALT) */ => {
             // show_help = !show_help;
-            popup = Some(PopupMode::Help);
+            popup = Some(PopupMode::Help { scroll: 0 });
             popup_input.clear();
           }
-        
+      
         // copiar
         KeyCode::Char('c') 
           if key.modifiers.contains(KeyModifiers::CONTROL)
           /* && key.modifiers.contains(KeyModifiers::ALT) */ =>  {
-            if let Some(text) = get_selected_text(&lines, &cursor, &selection_start) {
-              set_windows_clipboard(text);
+            if let Some(text) = copied {
+              if from_block { kill_ring.push_block(text) } else { kill_ring.push(text) }
             }
         },
-      
-        // cortar
-        KeyCode::Char('x') 
-          if key.modifiers.contains(KeyModifiers::CONTROL)
-          /* && key.modifiers.contains(KeyModifiers::ALT) */ => {
-            if let Some(text) = get_selected_text(&lines, &cursor, &selection_start) {
-              set_windows_clipboard(text);
-              delete_selection(&mut lines, &mut cursor, &mut selection_start);
-            }
+    
+        // cerrar archivo
+        KeyCode::Char('w') if ctrl => {
+          popup = request_close(&mut docs, &mut panes, active);
         },
-      
-        // pegar
-        KeyCode::Char('v') 
-          if key.modifiers.contains(KeyModifiers::CONTROL)
-          /* && key.modifiers.contains(KeyModifiers::ALT) */ => {
-            if let Some(text) = get_windows_clipboard() {
-              paste_text(&mut lines, &mut cursor, &mut selection_start, &text);
-            }
+
+        // lista de archivos abiertos
+        KeyCode::Char('b') if ctrl => {
+          popup = Some(PopupMode::buffers(&docs, active));
         },
 
         // mostrar guardado
@@ -336,10 +776,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         {
           match list_directory(&current_dir) {
               Ok(entries) => {
-                  popup = Some(PopupMode::Save { selected: None, entries, scroll_y: 0 });
+                  popup = Some(PopupMode::Save { selected: None, entries, scroll_y: 0, prompt: None });
                   popup_input.clear();
+
+                  // si el documento ya tiene archivo, proponer ese nombre
+                  if let Some(path) = file_path {
+                    popup_input.set(&relative_to(path, &current_dir));
+                  }
               }
-              Err(e) => eprintln!("Error al listar directorio -> {}", e),
+              Err(e) => messages.error(format!("Error al listar directorio -> {}", e)),
           }
         }
 
@@ -349,281 +794,961 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
           /* && key.modifiers.contains(KeyModifiers::ALT) */ => {
             match list_directory(&current_dir) {
                 Ok(entries) => {
-                    popup = Some(PopupMode::Open { selected: 0, entries, scroll_y: 0 });
+                    popup = Some(PopupMode::open(entries, &current_dir));
                     popup_input.clear();
                 }
-                Err(e) => eprintln!("Error al listar directorio -> {}", e),
+                Err(e) => messages.error(format!("Error al listar directorio -> {}", e)),
             }
         },
-        
-        // escribir
-        KeyCode::Char(c) => {
-          if has_selection(&selection_start, &cursor) {
-            delete_selection(&mut lines, &mut cursor, &mut selection_start);
-          }
-
-          // auto-cierre de pares
-          let closing = match c {
-            '{'  => Some(('{', '}', true)),   // true = insertar espacio entre medio
-            '['  => Some(('[', ']', false)),
-            '('  => Some(('(', ')', false)),
-            '\'' => Some(('\'', '\'', false)),
-            '"'  => Some(('"', '"', false)),
-            _    => None,
-          };
-
-          if let Some((open, close, with_space)) = closing {
-            let line_chars: Vec<char> = lines[cursor.y].chars().collect();
-            // para comillas: no auto-cerrar si el char siguiente ya es el cierre
-            // (evita doblar al escribir el cierre manualmente)
-            let next_is_close = cursor.x < line_chars.len() && line_chars[cursor.x] == close;
-            let is_quote = open == close; // ' o "
+      
+        // cambiar lo pegado por lo borrado antes
+        KeyCode::Char('y' | 'Y') if key.modifiers.contains(KeyModifiers::ALT) => {
+          kill_ring.yank_pop(lines, cursor, selection_start);
+        },
 
-            if is_quote && next_is_close {
-              // simplemente mover el cursor hacia adelante (saltar el cierre existente)
-              cursor.x += 1;
-            } else {
-              let byte_idx = char_to_byte_idx(&lines[cursor.y], cursor.x);
-              if with_space {
-                lines[cursor.y].insert_str(byte_idx, "{ }");
-                cursor.x += 2; // queda sobre el '}'
-              } else {
-                let pair = format!("{}{}", open, close);
-                lines[cursor.y].insert_str(byte_idx, &pair);
-                cursor.x += 1; // queda entre los dos chars
-              }
-            }
-          } else {
-            // para ) ] }: si el siguiente char ya es ese cierre, solo saltar
-            let line_chars: Vec<char> = lines[cursor.y].chars().collect();
-            let skip_close = matches!(c, ')' | ']' | '}')
-              && cursor.x < line_chars.len()
-              && line_chars[cursor.x] == c;
-
-            if skip_close {
-              cursor.x += 1;
-            } else {
-              let byte_idx = char_to_byte_idx(&lines[cursor.y], cursor.x);
-              lines[cursor.y].insert(byte_idx, c);
-              cursor.x += 1;
-            }
-          }
+        // otro cursor en la linea de arriba / abajo
+        KeyCode::Up | KeyCode::Down
+          if ctrl && key.modifiers.contains(KeyModifiers::ALT) => {
+          multicursor::add_cursor(&mut docs[active], key.code == KeyCode::Up, tab_width);
         },
 
-        // izquierda
-        KeyCode::Left => {
-          let selecting = key.modifiers.contains(KeyModifiers::SHIFT);
-          
-          if selecting {
-            start_selection_if_needed(&mut selection_start, CursorPos { x: cursor.x, y: cursor.y });
-          }
-          else {
-            selection_start = None;
-          }
+        // mover las lineas del cursor o de la selección
+        KeyCode::Up | KeyCode::Down
+          if key.modifiers.contains(KeyModifiers::ALT) && !key.modifiers.contains(KeyModifiers::SHIFT) => {
+          lineops::move_lines(lines, cursor, selection_start, key.code == KeyCode::Up);
+        },
 
-          if cursor.x > 0 {
-            cursor.x -= 1;
-          }
-          else if cursor.y > 0 {
-            cursor.y -= 1;
-            cursor.x = line_len_chars(&lines[cursor.y]);
+        // seleccionar la siguiente aparición / todas las apariciones
+        KeyCode::Char('d') if ctrl => {
+          let added = multicursor::add_next_occurrence(&mut docs[active], &word_chars);
+          if !added {
+            messages.warn("No hay mas apariciones");
           }
+        },
+        KeyCode::Char('l' | 'L') if ctrl && key.modifiers.contains(KeyModifiers::SHIFT) => {
+          let count = multicursor::select_all_occurrences(&mut docs[active], &word_chars);
+          messages.info(format!("{} apariciones", count));
+        },
 
-          if key.modifiers.contains(KeyModifiers::CONTROL){
-            move_word_left(&lines, &mut cursor.x, cursor.y);
+        // ir al paréntesis que corresponde (Ctrl + ] llega como Ctrl + 5 en muchas terminales)
+        KeyCode::Char(']' | '5') if ctrl => {
+          let jumped = brackets::jump(&mut docs[active]);
+          if !jumped {
+            messages.warn("No hay parentesis con pareja en el cursor");
           }
         },
 
-        // derecha
-        KeyCode::Right => {
-          let selecting = key.modifiers.contains(KeyModifiers::SHIFT);
+        // seleccionar todo / la linea
+        KeyCode::Char('a') if ctrl => {
+          others.clear();
+          select::select_all(lines, cursor, selection_start);
+        },
+        KeyCode::Char('l') if ctrl => {
+          others.clear();
+          select::select_line(lines, cursor, selection_start);
+        },
 
-          if selecting {
-            start_selection_if_needed(&mut selection_start, CursorPos { x: cursor.x, y: cursor.y });
-          }
-          else{ 
-            selection_start = None;
-          }
+        // agrandar / achicar la selección por pasos
+        KeyCode::Char('=') if key.modifiers.contains(KeyModifiers::ALT) => {
+          others.clear();
+          select::expand(&mut docs[active], &word_chars);
+        },
+        KeyCode::Char('-') if key.modifiers.contains(KeyModifiers::ALT) => {
+          others.clear();
+          select::shrink(&mut docs[active]);
+        },
 
-          if cursor.x < line_len_chars(&lines[cursor.y]) {
-            cursor.x += 1;
-          }
-          else if cursor.y + 1 < lines.len() {
-            cursor.y += 1;
-            cursor.x = 0;
-          }
-          if key.modifiers.contains(KeyModifiers::CONTROL){
-            move_word_right(&lines, &mut cursor.x, cursor.y);
+        // volver a un solo cursor, o quitar la selección
+        KeyCode::Esc => {
+          if !others.is_empty() {
+            others.clear();
+          } else {
+            *selection_start = None;
           }
         },
 
-        // subir
-        KeyCode::Up => {
-          let selecting = key.modifiers.contains(KeyModifiers::SHIFT);
-          
-          if selecting {
-            start_selection_if_needed(&mut selection_start, CursorPos { x: cursor.x, y: cursor.y });
-          }
-          else {
-            selection_start = None;
+        // borrar la palabra anterior / siguiente
+        KeyCode::Backspace | KeyCode::Delete if ctrl => {
+          let backward = key.code == KeyCode::Backspace;
+          if !has_selection(selection_start, cursor) {
+            let mut target = *cursor;
+            if backward {
+              move_word_left(lines, &mut target, &word_chars);
+            } else {
+              move_word_right(lines, &mut target, &word_chars);
+            }
+            *selection_start = Some(target);
           }
-
-          if cursor.y > 0 {
-            cursor.y -= 1;
-            cursor.x = cursor.x.min(line_len_chars(&lines[cursor.y]));
+          if let Some(text) = take_selection(lines, cursor, selection_start) {
+            kill_ring.kill(text, backward);
           }
         },
 
-        // bajar
-        KeyCode::Down => {
-          let selecting = key.modifiers.contains(KeyModifiers::SHIFT);
-          
-          if selecting {
-            start_selection_if_needed(&mut selection_start, CursorPos { x: cursor.x, y: cursor.y });
-          }
-          else {
-            selection_start = None;
+        // escribir, moverse y borrar: se repite en cada cursor (ver multicursor.rs)
+        _ => {
+          // con varios cursores se corta en cada uno, pero se guarda todo junto una vez
+          if ctrl && key.code == KeyCode::Char('x') && let Some(text) = copied {
+            if from_block { kill_ring.push_block(text) } else { kill_ring.push(text) }
           }
+          // sin portapapeles del sistema se usa lo último copiado en el editor
+          let pasted = if ctrl && key.code == KeyCode::Char('v') {
+            get_windows_clipboard().or_else(|| kill_ring.last().map(|s| s.to_string()))
+          } else {
+            None
+          };
 
-          if cursor.y + 1 < lines.len() {
-            cursor.y += 1;
-            cursor.x = cursor.x.min(line_len_chars(&lines[cursor.y]));
+          let mut rounds = Rounds::new(&mut docs[active]);
+          // lo copiado de un bloque se pega como bloque si hay un solo cursor
+          let as_block = rounds.len() == 1 && pasted.is_some() && kill_ring.last_block() == pasted.as_deref();
+          for round in 0..rounds.len() {
+            rounds.load(&mut docs[active], round);
+            let paste = pasted.as_deref().map(|text| (rounds.piece(text, round), as_block));
+            cursor_key(&mut docs[active], &key, paste, &config, &mut kill_ring, &mut messages);
+            rounds.store(&docs[active]);
           }
+          rounds.finish(&mut docs[active]);
         },
+      }
 
-        // inicio
-        KeyCode::Home => {
-          let selecting = key.modifiers.contains(KeyModifiers::SHIFT);
+      follow_cursor(&docs, &mut panes, &config)?;
 
-          if selecting {
-            start_selection_if_needed(&mut selection_start, CursorPos { x: cursor.x, y: cursor.y });
-          }
-          else{ 
-            selection_start = None;
-          }
+      draw(&docs, &panes, &popup, &popup_input, &messages, &config, &current_dir)?;
+    }
+  }
 
-          if key.modifiers.contains(KeyModifiers::CONTROL){
-            cursor.y = 0;
+  execute!(stdout(),LeaveAlternateScreen)?;
+
+  disable_raw_mode()?;
+  Ok(())
+}
+
+// Una tecla de edición o movimiento en el cursor del documento; con varios cursores
+// se llama una vez por cada uno (ver multicursor.rs). `paste` es lo que le toca pegar
+// a este cursor y si va como bloque.
+fn cursor_key(doc: &mut Document, key: &KeyEvent, paste: Option<(String, bool)>, config: &Config, kill_ring: &mut KillRing, messages: &mut MessageLog) {
+  let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+  let indent = doc.indent(config);
+  let tab_width = doc.tab_width(config);
+  let word_chars = doc.word_chars(config);
+  let Document { lines, cursor, selection_start, .. } = doc;
+
+  match key.code {
+    // cortar (lo cortado ya se guardó, una vez para todos los cursores)
+    KeyCode::Char('x') 
+      if key.modifiers.contains(KeyModifiers::CONTROL)
+      /* && key.modifiers.contains(KeyModifiers::ALT) */ => {
+        take_selection(lines, cursor, selection_start);
+    },
+
+    // pegar
+    KeyCode::Char('v') 
+      if key.modifiers.contains(KeyModifiers::CONTROL)
+      /* && key.modifiers.contains(KeyModifiers::ALT) */ => {
+        if let Some((text, as_block)) = paste {
+          if as_block {
+            block::paste(lines, cursor, selection_start, &text, tab_width);
+          } else {
+            paste_text(lines, cursor, selection_start, &text);
           }
-          cursor.x = 0;
-        },
+        }
+    },
 
-        // final
-        KeyCode::End => {
-          let selecting = key.modifiers.contains(KeyModifiers::SHIFT);
+    // pegar lo último borrado
+    KeyCode::Char('y') if ctrl => {
+      kill_ring.yank(lines, cursor, selection_start);
+    },
 
-          if selecting {
-            start_selection_if_needed(&mut selection_start, CursorPos { x: cursor.x, y: cursor.y });
-          }
-          else{ 
-            selection_start = None;
-          }
+    // rotar el estilo del nombre: camelCase -> PascalCase -> snake_case -> ...
+    KeyCode::Char('c' | 'C') if key.modifiers.contains(KeyModifiers::ALT) => {
+      if let Some(case) = case::convert(lines, cursor, selection_start, None) {
+        messages.info(format!("Convertido a {}", case.name()));
+      }
+    },
 
-          if key.modifiers.contains(KeyModifiers::CONTROL){
-            cursor.y = lines.len() - 1;
-          }
-          cursor.x = line_len_chars(&lines[cursor.y]);
-        },
+    // escribir; con Ctrl o Alt solo no (son comandos sin asignar), pero Ctrl + Alt sí,
+    // porque AltGr llega así en Windows
+    KeyCode::Char(c) if ctrl == key.modifiers.contains(KeyModifiers::ALT) => {
+      if has_selection(selection_start, cursor) {
+        delete_selection(lines, cursor, selection_start);
+      }
 
-        // enter
-        KeyCode::Enter => {
-          // detectar el indent actual (espacios/tabs al inicio de la línea)
-          let current_indent: String = lines[cursor.y]
-            .chars()
-            .take_while(|c| *c == ' ' || *c == '\t')
-            .collect();
-
-          let line_chars: Vec<char> = lines[cursor.y].chars().collect();
-
-          // caso 1: cursor justo antes de un cierre → dos saltos: indent + línea del cierre
-          let before_close_brace = cursor.x < line_chars.len()
-            && matches!(line_chars[cursor.x], '}' | ']' | ')' | '\'' | '"');
- 
-          // caso 2: cursor justo después de una apertura, sin cierre inmediatamente adelante
-          let after_open_brace = !before_close_brace
-            && cursor.x >= 1
-            && matches!(line_chars[cursor.x - 1], '{' | '[' | '(' | '\'' | '"');
-
-          if before_close_brace {
-            // partir la línea: el '}' queda en la cola
-            let byte_idx = char_to_byte_idx(&lines[cursor.y], cursor.x);
-            let tail = lines[cursor.y].split_off(byte_idx);
-
-            // línea intermedia con indent extra (donde queda el cursor)
-            let inner_indent = format!("{}  ", current_indent);
-            lines.insert(cursor.y + 1, inner_indent.clone());
-
-            // línea del '}' con el indent original
-            lines.insert(cursor.y + 2, format!("{}{}", current_indent, tail));
-
-            cursor.y += 1;
-            cursor.x = inner_indent.len();
-          } else if after_open_brace {
-            // un solo salto con indent extra, lo que haya después del '{'  va a la nueva línea
-            let byte_idx = char_to_byte_idx(&lines[cursor.y], cursor.x);
-            let tail = lines[cursor.y].split_off(byte_idx);
-
-            let inner_indent = format!("{}  ", current_indent);
-            lines.insert(cursor.y + 1, format!("{}{}", inner_indent, tail.trim_start()));
-
-            cursor.y += 1;
-            cursor.x = inner_indent.len();
+      // auto-cierre de pares
+      let closing = match c {
+        _ if !config.auto_pairs => None,
+        '{'  => Some(('{', '}', true)),   // true = insertar espacio entre medio
+        '['  => Some(('[', ']', false)),
+        '('  => Some(('(', ')', false)),
+        '\'' => Some(('\'', '\'', false)),
+        '"'  => Some(('"', '"', false)),
+        _    => None,
+      };
+
+      if let Some((open, close, with_space)) = closing {
+        let line_chars: Vec<char> = lines[cursor.y].chars().collect();
+        // para comillas: no auto-cerrar si el char siguiente ya es el cierre
+        // (evita doblar al escribir el cierre manualmente)
+        let next_is_close = cursor.x < line_chars.len() && line_chars[cursor.x] == close;
+        let is_quote = open == close; // ' o "
+
+        if is_quote && next_is_close {
+          // simplemente mover el cursor hacia adelante (saltar el cierre existente)
+          cursor.x += 1;
+        } else {
+          let byte_idx = char_to_byte_idx(&lines[cursor.y], cursor.x);
+          if with_space {
+            lines[cursor.y].insert_str(byte_idx, "{ }");
+            cursor.x += 2; // queda sobre el '}'
           } else {
-            let byte_idx = char_to_byte_idx(&lines[cursor.y], cursor.x);
-            let new_line = lines[cursor.y].split_off(byte_idx);
-            lines.insert(cursor.y + 1, format!("{}{}", current_indent, new_line));
-            cursor.y += 1;
-            cursor.x = current_indent.len();
+            let pair = format!("{}{}", open, close);
+            lines[cursor.y].insert_str(byte_idx, &pair);
+            cursor.x += 1; // queda entre los dos chars
           }
-        },
+        }
+      } else {
+        // para ) ] }: si el siguiente char ya es ese cierre, solo saltar
+        let line_chars: Vec<char> = lines[cursor.y].chars().collect();
+        let skip_close = config.auto_pairs
+          && matches!(c, ')' | ']' | '}')
+          && cursor.x < line_chars.len()
+          && line_chars[cursor.x] == c;
+
+        if skip_close {
+          cursor.x += 1;
+        } else {
+          let byte_idx = char_to_byte_idx(&lines[cursor.y], cursor.x);
+          lines[cursor.y].insert(byte_idx, c);
+          cursor.x += 1;
+        }
+      }
+    },
+
+    // subpalabras: se detienen en las jorobas de camelCase, '_', '-' y entre letras y números
+    KeyCode::Left | KeyCode::Right if key.modifiers.contains(KeyModifiers::ALT) => {
+      if key.modifiers.contains(KeyModifiers::SHIFT) {
+        start_selection_if_needed(selection_start, CursorPos { x: cursor.x, y: cursor.y });
+      } else {
+        *selection_start = None;
+      }
 
-        // backspace
-        KeyCode::Backspace => {
-          if has_selection(&selection_start, &cursor) {
-            delete_selection(&mut lines, &mut cursor, &mut selection_start);
-          } else if cursor.x > 0 {
-            let byte_idx = char_to_byte_idx(&lines[cursor.y], cursor.x - 1);
-
-            lines[cursor.y].remove(byte_idx);
-            cursor.x -= 1;
-          } else if cursor.y > 0 {
-            let current = lines.remove(cursor.y);
-            
-            cursor.y -= 1;
-            cursor.x = line_len_chars(&lines[cursor.y]);
-            lines[cursor.y].push_str(&current);
-          }
-        },
-        _ => {}
+      if key.code == KeyCode::Left {
+        move_subword_left(lines, cursor);
+      } else {
+        move_subword_right(lines, cursor);
       }
+    },
 
-      let (term_width, term_height) = size()?;
-      let visible_lines = term_height as usize - (2 + 1);
 
-      let usable_width = (term_width as usize).saturating_sub(2);
-      
-      if cursor.x < scroll_x {
-        scroll_x = cursor.x;
+    // borrar la subpalabra anterior / siguiente
+    KeyCode::Backspace | KeyCode::Delete if key.modifiers.contains(KeyModifiers::ALT) => {
+      if !has_selection(selection_start, cursor) {
+        let mut target = *cursor;
+        if key.code == KeyCode::Backspace {
+          move_subword_left(lines, &mut target);
+        } else {
+          move_subword_right(lines, &mut target);
+        }
+        *selection_start = Some(target);
       }
-      
-      if cursor.x >= scroll_x + usable_width {
-        scroll_x = cursor.x + 1 - usable_width;
+      delete_selection(lines, cursor, selection_start);
+    },
+
+    // izquierda
+    KeyCode::Left => {
+      let selecting = key.modifiers.contains(KeyModifiers::SHIFT);
+    
+      if selecting {
+        start_selection_if_needed(selection_start, CursorPos { x: cursor.x, y: cursor.y });
+      }
+      else {
+        *selection_start = None;
+      }
+
+      if key.modifiers.contains(KeyModifiers::CONTROL){
+        move_word_left(lines, cursor, &word_chars);
+      }
+      else if cursor.x > 0 {
+        cursor.x = prev_grapheme(&lines[cursor.y], cursor.x);
+      }
+      else if cursor.y > 0 {
+        cursor.y -= 1;
+        cursor.x = line_len_chars(&lines[cursor.y]);
+      }
+    },
+
+    // derecha
+    KeyCode::Right => {
+      let selecting = key.modifiers.contains(KeyModifiers::SHIFT);
+
+      if selecting {
+        start_selection_if_needed(selection_start, CursorPos { x: cursor.x, y: cursor.y });
+      }
+      else{ 
+        *selection_start = None;
+      }
+
+      if key.modifiers.contains(KeyModifiers::CONTROL){
+        move_word_right(lines, cursor, &word_chars);
+      }
+      else if cursor.x < line_len_chars(&lines[cursor.y]) {
+        cursor.x = next_grapheme(&lines[cursor.y], cursor.x);
+      }
+      else if cursor.y + 1 < lines.len() {
+        cursor.y += 1;
+        cursor.x = 0;
+      }
+    },
+
+    // subir
+    KeyCode::Up => {
+      let selecting = key.modifiers.contains(KeyModifiers::SHIFT);
+    
+      if selecting {
+        start_selection_if_needed(selection_start, CursorPos { x: cursor.x, y: cursor.y });
+      }
+      else {
+        *selection_start = None;
+      }
+
+      // misma columna de pantalla, aunque haya tabs o caracteres anchos en el medio
+      if cursor.y > 0 {
+        let col = display_col(&lines[cursor.y], cursor.x, tab_width);
+        cursor.y -= 1;
+        cursor.x = col_to_char(&lines[cursor.y], col, tab_width);
+      }
+    },
+
+    // bajar
+    KeyCode::Down => {
+      let selecting = key.modifiers.contains(KeyModifiers::SHIFT);
+    
+      if selecting {
+        start_selection_if_needed(selection_start, CursorPos { x: cursor.x, y: cursor.y });
+      }
+      else {
+        *selection_start = None;
+      }
+
+      if cursor.y + 1 < lines.len() {
+        let col = display_col(&lines[cursor.y], cursor.x, tab_width);
+        cursor.y += 1;
+        cursor.x = col_to_char(&lines[cursor.y], col, tab_width);
+      }
+    },
+
+    // inicio
+    KeyCode::Home => {
+      let selecting = key.modifiers.contains(KeyModifiers::SHIFT);
+
+      if selecting {
+        start_selection_if_needed(selection_start, CursorPos { x: cursor.x, y: cursor.y });
+      }
+      else{ 
+        *selection_start = None;
       }
 
-      if cursor.y < scroll_y {
-        scroll_y = cursor.y;
+      if key.modifiers.contains(KeyModifiers::CONTROL){
+        cursor.y = 0;
       }
+      cursor.x = 0;
+    },
 
-      if cursor.y >= scroll_y + visible_lines {
-        scroll_y = cursor.y + 1 - visible_lines;
+    // final
+    KeyCode::End => {
+      let selecting = key.modifiers.contains(KeyModifiers::SHIFT);
+
+      if selecting {
+        start_selection_if_needed(selection_start, CursorPos { x: cursor.x, y: cursor.y });
+      }
+      else{ 
+        *selection_start = None;
       }
 
-      draw(&lines, &cursor, selection_start, scroll_x, scroll_y, 2, &popup, &popup_input, &current_dir)?;
+      if key.modifiers.contains(KeyModifiers::CONTROL){
+        cursor.y = lines.len() - 1;
+      }
+      cursor.x = line_len_chars(&lines[cursor.y]);
+    },
+
+    // enter
+    KeyCode::Enter => {
+      // detectar el indent actual (espacios/tabs al inicio de la línea)
+      let current_indent: String = lines[cursor.y]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect();
+
+      let line_chars: Vec<char> = lines[cursor.y].chars().collect();
+
+      // caso 1: cursor justo antes de un cierre → dos saltos: indent + línea del cierre
+      let before_close_brace = cursor.x < line_chars.len()
+        && matches!(line_chars[cursor.x], '}' | ']' | ')' | '\'' | '"');
+
+      // caso 2: cursor justo después de una apertura, sin cierre inmediatamente adelante
+      let after_open_brace = !before_close_brace
+        && cursor.x >= 1
+        && matches!(line_chars[cursor.x - 1], '{' | '[' | '(' | '\'' | '"');
+
+      if before_close_brace {
+        // partir la línea: el '}' queda en la cola
+        let byte_idx = char_to_byte_idx(&lines[cursor.y], cursor.x);
+        let tail = lines[cursor.y].split_off(byte_idx);
+
+        // línea intermedia con indent extra (donde queda el cursor)
+        let inner_indent = format!("{}{}", current_indent, indent.unit());
+        lines.insert(cursor.y + 1, inner_indent.clone());
+
+        // línea del '}' con el indent original
+        lines.insert(cursor.y + 2, format!("{}{}", current_indent, tail));
+
+        cursor.y += 1;
+        cursor.x = inner_indent.len();
+      } else if after_open_brace {
+        // un solo salto con indent extra, lo que haya después del '{'  va a la nueva línea
+        let byte_idx = char_to_byte_idx(&lines[cursor.y], cursor.x);
+        let tail = lines[cursor.y].split_off(byte_idx);
+
+        let inner_indent = format!("{}{}", current_indent, indent.unit());
+        lines.insert(cursor.y + 1, format!("{}{}", inner_indent, tail.trim_start()));
+
+        cursor.y += 1;
+        cursor.x = inner_indent.len();
+      } else {
+        let byte_idx = char_to_byte_idx(&lines[cursor.y], cursor.x);
+        let new_line = lines[cursor.y].split_off(byte_idx);
+        lines.insert(cursor.y + 1, format!("{}{}", current_indent, new_line));
+        cursor.y += 1;
+        cursor.x = current_indent.len();
+      }
+    },
+
+    // tab: indentar las lineas seleccionadas o insertar un nivel en el cursor
+    KeyCode::Tab => {
+      if is_multiline(cursor, selection_start) {
+        indent_lines(lines, cursor, selection_start, indent);
+      } else {
+        if has_selection(selection_start, cursor) {
+          delete_selection(lines, cursor, selection_start);
+        }
+        *selection_start = None;
+        insert_tab(lines, cursor, indent, tab_width);
+      }
+    },
+
+    // shift + tab: quitar un nivel a la linea o a las lineas seleccionadas
+    KeyCode::BackTab => {
+      outdent_lines(lines, cursor, selection_start, indent);
+    },
+
+    // suprimir: la selección, el cluster siguiente o el salto de linea
+    KeyCode::Delete => {
+      if has_selection(selection_start, cursor) {
+        delete_selection(lines, cursor, selection_start);
+      } else if cursor.x < line_len_chars(&lines[cursor.y]) {
+        let end = next_grapheme(&lines[cursor.y], cursor.x);
+        let a = char_to_byte_idx(&lines[cursor.y], cursor.x);
+        let b = char_to_byte_idx(&lines[cursor.y], end);
+
+        lines[cursor.y].replace_range(a..b, "");
+      } else if cursor.y + 1 < lines.len() {
+        let next = lines.remove(cursor.y + 1);
+        lines[cursor.y].push_str(&next);
+      }
+      *selection_start = None;
+    },
+
+    // backspace
+    KeyCode::Backspace => {
+      if has_selection(selection_start, cursor) {
+        delete_selection(lines, cursor, selection_start);
+      } else if backspace_indent(lines, cursor, indent) {
+        // borró un nivel de indentación
+      } else if cursor.x > 0 {
+        // el cluster entero: no queda medio emoji ni una letra sin su acento
+        let start = prev_grapheme(&lines[cursor.y], cursor.x);
+        let a = char_to_byte_idx(&lines[cursor.y], start);
+        let b = char_to_byte_idx(&lines[cursor.y], cursor.x);
+
+        lines[cursor.y].replace_range(a..b, "");
+        cursor.x = start;
+      } else if cursor.y > 0 {
+        let current = lines.remove(cursor.y);
+      
+        cursor.y -= 1;
+        cursor.x = line_len_chars(&lines[cursor.y]);
+        lines[cursor.y].push_str(&current);
+      }
+    },
+    _ => {}
+  }
+}
+
+// Cierra el documento `index`, o pide confirmación si tiene cambios sin guardar
+fn request_close(docs: &mut Vec<Document>, panes: &mut Panes, index: usize) -> Option<PopupMode> {
+  if docs[index].is_modified() {
+    return Some(PopupMode::ConfirmClose { index, name: docs[index].name() });
+  }
+  close_document(docs, panes, index);
+  None
+}
+
+fn close_document(docs: &mut Vec<Document>, panes: &mut Panes, index: usize) {
+  docs.remove(index);
+  if docs.is_empty() {
+    docs.push(Document::new());
+  }
+  // los paneles que lo mostraban pasan al documento que ocupa su lugar
+  panes.doc_closed(index, index.min(docs.len() - 1));
+}
+
+// Guarda el documento en `path` y avisa el resultado en la barra de mensajes
+fn save_document(doc: &mut Document, path: String, messages: &mut MessageLog) {
+  let plan = doc.prepare_save(&path);
+  match save_file(&path, doc.lines_to_save(&plan), &plan.format) {
+    Ok(()) => {
+      messages.info(format!("Guardadas {} lineas en {}", doc.lines.len(), path));
+      doc.mark_saved(path, plan);
     }
+    Err(e) => messages.error(format!("Error al guardar {} -> {}", path, e)),
   }
+}
+
+// Ctrl + K, Backspace / Delete: borra hasta el principio o el final de la linea (el salto
+// de linea si el cursor ya está ahí). Ctrl + K, K: borra las lineas del cursor o de la
+// selección. Lo borrado va al kill ring.
+fn kill_line(doc: &mut Document, code: KeyCode, tab_width: usize, kill_ring: &mut KillRing) {
+  let Document { lines, cursor, selection_start, .. } = doc;
+
+  if let KeyCode::Char(_) = code {
+    let (first, last) = match *selection_start {
+      Some(start) => (start.y.min(cursor.y), start.y.max(cursor.y)),
+      None => (cursor.y, cursor.y),
+    };
+    let col = display_col(&lines[cursor.y], cursor.x, tab_width);
+    let text: String = lines.drain(first..=last).map(|line| line + "\n").collect();
+    if lines.is_empty() {
+      lines.push(String::new());
+    }
 
-  execute!(stdout(),LeaveAlternateScreen)?;
+    // queda en la linea que subió, en la misma columna de pantalla
+    cursor.y = first.min(lines.len() - 1);
+    cursor.x = col_to_char(&lines[cursor.y], col, tab_width);
+    *selection_start = None;
+    kill_ring.kill(text, false);
+    return;
+  }
+
+  let backward = code == KeyCode::Backspace;
+  if !has_selection(selection_start, cursor) {
+    let len = line_len_chars(&lines[cursor.y]);
+    let target = match backward {
+      true if cursor.x > 0 => CursorPos { x: 0, y: cursor.y },
+      true if cursor.y > 0 => CursorPos { x: line_len_chars(&lines[cursor.y - 1]), y: cursor.y - 1 },
+      false if cursor.x < len => CursorPos { x: len, y: cursor.y },
+      false if cursor.y + 1 < lines.len() => CursorPos { x: 0, y: cursor.y + 1 },
+      _ => return,
+    };
+    *selection_start = Some(target);
+  }
+  if let Some(text) = take_selection(lines, cursor, selection_start) {
+    kill_ring.kill(text, backward);
+  }
+}
+
+// Ejecuta la opción `selected` de un menú de Ctrl + K sobre el documento
+fn run_menu(menu: Menu, selected: usize, doc: &mut Document, config: &Config, messages: &mut MessageLog) {
+  match menu {
+    Menu::Indent => {
+      let chosen = match selected {
+        0 => Indent { use_tabs: false, size: 2 },
+        1 => Indent { use_tabs: false, size: 4 },
+        2 => Indent { use_tabs: false, size: 8 },
+        3 => Indent { use_tabs: true, size: doc.tab_width(config) },
+        4 => indent::detect(&doc.lines, doc.tab_width(config)).unwrap_or(config.indent()),
+        _ => {
+          if doc.readonly {
+            return;
+          }
+          // lo que hay en el archivo pasa a la indentación elegida
+          let current = doc.indent(config);
+          let found = indent::detect(&doc.lines, doc.tab_width(config)).unwrap_or(current);
+          doc.selection_start = None;
+          doc.others.clear();
+          reindent(&mut doc.lines, &mut doc.cursor, found, current);
+          messages.info(format!("Reindentado con {}", current.name()));
+          return;
+        }
+      };
+      doc.set_indent(chosen);
+      messages.info(format!("Indentacion {}", chosen.name()));
+    }
+    Menu::Case => {
+      if doc.readonly {
+        return;
+      }
+      doc.others.clear();
+      let Document { lines, cursor, selection_start, .. } = doc;
+      if case::convert(lines, cursor, selection_start, Some(CASES[selected])).is_none() {
+        messages.warn("No hay palabra en el cursor");
+      }
+    }
+    Menu::Lines => {
+      if doc.readonly {
+        return;
+      }
+      doc.others.clear();
+      let Document { lines, cursor, selection_start, .. } = doc;
+      lineops::reorder(lines, cursor, selection_start, ORDERS[selected]);
+      messages.info(format!("{}: listo", ORDERS[selected].name()));
+    }
+  }
+}
+
+// Muestra el documento `index` en el panel enfocado, recordando el scroll del anterior
+fn show_document(panes: &mut Panes, docs: &mut [Document], index: usize) {
+  let view = panes.view_mut();
+  if view.doc == index {
+    return;
+  }
+
+  if let Some(old) = docs.get_mut(view.doc) {
+    old.scroll_x = view.scroll_x;
+    old.scroll_y = view.scroll_y;
+  }
+  *view = View::new(index, docs[index].scroll_x, docs[index].scroll_y);
+}
+
+// Cambia el panel enfocado (dividir, cerrar, moverse): el cursor y la selección del
+// documento quedan guardados en la vista que deja el foco y se toman los de la nueva
+fn change_view(panes: &mut Panes, docs: &mut [Document], change: impl FnOnce(&mut Panes)) {
+  let view = panes.view_mut();
+  let doc = &mut docs[view.doc];
+  view.cursor = doc.cursor;
+  view.selection_start = doc.selection_start;
+
+  change(panes);
+
+  let view = panes.view();
+  let doc = &mut docs[view.doc];
+  doc.cursor = view.cursor.clamped(&doc.lines);
+  doc.selection_start = view.selection_start.map(|start| start.clamped(&doc.lines));
+  doc.others.clear();
+  doc.block = None;
+}
+
+// Ajusta el scroll de los paneles para que su cursor quede dentro de la pantalla. Los
+// no enfocados que muestran el documento editado se corren con el texto y, si aun así
+// quedan fuera (se borraron lineas), se los trae al texto y el scroll los sigue.
+fn follow_cursor(docs: &[Document], panes: &mut Panes, config: &Config) -> std::io::Result<()> {
+  let area = Layout::current()?.text;
+  let split = panes.is_split();
+
+  // las otras vistas del documento editado siguen al texto que se agregó o borró arriba
+  let doc = &docs[panes.view().doc];
+  let top = multicursor::selections(doc).iter().map(|s| s.range().0.y).min().unwrap_or(0);
+  panes.follow_edit(top, doc.lines.len());
+
+  for (view_idx, rect) in panes.layout(area) {
+    let rect = view_text_rect(rect, split);
+    let focused = view_idx == panes.focused;
+    let View { doc, scroll_x, scroll_y, cursor, selection_start } = &mut panes.views[view_idx];
+    let doc = &docs[*doc];
+    if focused {
+      *cursor = doc.cursor;
+    } else {
+      *cursor = cursor.clamped(&doc.lines);
+      *selection_start = selection_start.map(|start| start.clamped(&doc.lines));
+    }
+
+    let line = &doc.lines[cursor.y];
+    let tab_width = doc.tab_width(config);
+    // scroll_x es en columnas de pantalla (un tab o un caracter ancho ocupan varias)
+    let cursor_col = display_col(line, cursor.x, tab_width);
+    let cursor_width = graphemes(line)
+      .find(|(start, _)| *start == cursor.x)
+      .map_or(1, |(_, g)| grapheme_width(g, cursor_col, tab_width));
+
+    // el margen de numeros solo se dibuja si deja lugar para el texto
+    let gutter = gutter_width(doc, config);
+    let gutter = if gutter + 2 < rect.width { gutter } else { 0 };
+
+    let visible_lines = rect.height.max(1);
+    let usable_width = rect.width.saturating_sub(2 + gutter).max(1);
+  
+    if cursor_col < *scroll_x {
+      *scroll_x = cursor_col;
+    }
+  
+    if cursor_col + cursor_width > *scroll_x + usable_width {
+      *scroll_x = (cursor_col + cursor_width).saturating_sub(usable_width).min(cursor_col);
+    }
+
+    if cursor.y < *scroll_y {
+      *scroll_y = cursor.y;
+    }
+
+    if cursor.y >= *scroll_y + visible_lines {
+      *scroll_y = cursor.y + 1 - visible_lines;
+    }
+  }
 
-  disable_raw_mode()?;
   Ok(())
-}
\ No newline at end of file
+}
+
+// Ruta de `path` relativa a `dir` si está dentro de él, si no la ruta completa
+fn relative_to(path: &str, dir: &str) -> String {
+  let absolute = |p: &str| std::fs::canonicalize(p).unwrap_or_else(|_| Path::new(p).to_path_buf());
+  let file = absolute(path);
+  let dir = absolute(dir);
+
+  // archivo que todavía no existe: canonicalizar la carpeta que lo contiene
+  let file = match (file.parent(), file.file_name()) {
+    (Some(parent), Some(name)) if !file.exists() => absolute(&parent.to_string_lossy()).join(name),
+    _ => file,
+  };
+
+  file.strip_prefix(&dir)
+    .unwrap_or(&file)
+    .to_string_lossy()
+    .to_string()
+}
+
+fn same_file(a: &str, b: &str) -> bool {
+  match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+    (Ok(a), Ok(b)) => a == b,
+    _ => a == b,
+  }
+}
+
+// Teclas que modifican el texto
+fn is_edit_key(key: &KeyEvent) -> bool {
+  let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+  match key.code {
+    KeyCode::Char('x' | 'v' | 'y') if ctrl => true,
+    KeyCode::Char('y' | 'Y') if key.modifiers.contains(KeyModifiers::ALT) => true,
+    // agrandar / achicar la selección
+    KeyCode::Char('=' | '-') if key.modifiers.contains(KeyModifiers::ALT) => false,
+    KeyCode::Char(_) => !ctrl,
+    KeyCode::Tab | KeyCode::BackTab => !ctrl,
+    KeyCode::Up | KeyCode::Down => {
+      !ctrl && key.modifiers.contains(KeyModifiers::ALT) && !key.modifiers.contains(KeyModifiers::SHIFT)
+    }
+    KeyCode::Enter | KeyCode::Backspace | KeyCode::Delete => true,
+    _ => false,
+  }
+}
+
+// Teclas que se pueden usar en modo solo lectura: moverse, seleccionar, copiar y los
+// comandos que no tocan el texto. El resto se ignora, también las combinaciones sin
+// asignar.
+fn is_readonly_key(key: &KeyEvent) -> bool {
+  let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+  let alt = key.modifiers.contains(KeyModifiers::ALT);
+  match key.code {
+    // salir, ayuda, copiar, cerrar, archivos abiertos, guardar como, abrir, apariciones,
+    // seleccionar todo / la linea e ir al paréntesis
+    KeyCode::Char(c) if ctrl => matches!(c.to_ascii_lowercase(), 'q' | 'h' | 'c' | 'w' | 'b' | 's' | 'o' | 'd' | 'a' | 'l' | ']' | '5'),
+    // agrandar / achicar la selección
+    KeyCode::Char('=' | '-') => alt,
+    // con Alt y sin Shift mueven las lineas
+    KeyCode::Up | KeyCode::Down => ctrl || !alt || key.modifiers.contains(KeyModifiers::SHIFT),
+    KeyCode::Left | KeyCode::Right | KeyCode::Home | KeyCode::End => true,
+    KeyCode::PageUp | KeyCode::PageDown | KeyCode::Esc => true,
+    // cambiar de archivo
+    KeyCode::Tab | KeyCode::BackTab => ctrl,
+    _ => false,
+  }
+}
+
+// Teclas que se repiten en cada cursor cuando hay varios
+fn is_multi_cursor_key(key: &KeyEvent) -> bool {
+  let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+  match key.code {
+    KeyCode::Char('v' | 'x' | 'y') if ctrl => true,
+    KeyCode::Char('=' | '-') if key.modifiers.contains(KeyModifiers::ALT) => false,
+    KeyCode::Char(_) => !ctrl,
+    KeyCode::Up | KeyCode::Down => !key.modifiers.contains(KeyModifiers::ALT),
+    KeyCode::Left | KeyCode::Right | KeyCode::Home | KeyCode::End => true,
+    KeyCode::Enter | KeyCode::Backspace | KeyCode::Delete | KeyCode::Tab | KeyCode::BackTab => !ctrl,
+    _ => false,
+  }
+}
+
+// Ejecuta una acción confirmada del popup de Guardar (crear, renombrar, duplicar, borrar).
+// Devuelve el nombre de la entrada a seleccionar al volver a listar el directorio.
+fn run_file_action(prompt: SavePrompt, dir: &str) -> std::io::Result<Option<String>> {
+  match prompt {
+    SavePrompt::NewFolder(input) if !input.is_empty() => {
+      create_folder(dir, &input.text)?;
+      Ok(Some(input.text))
+    }
+    SavePrompt::Rename { entry, input } if !input.is_empty() => {
+      rename_entry(dir, &entry, &input.text)?;
+      Ok(Some(input.text))
+    }
+    SavePrompt::Duplicate { entry, input } if !input.is_empty() => {
+      duplicate_entry(dir, &entry, &input.text)?;
+      Ok(Some(input.text))
+    }
+    SavePrompt::Delete(entry) => {
+      move_to_trash(dir, &entry)?;
+      Ok(None)
+    }
+    _ => Ok(None),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+    KeyEvent::new(code, modifiers)
+  }
+
+  fn doc(text: &str) -> Document {
+    let mut doc = Document::new();
+    doc.lines = vec![text.to_string()];
+    doc.cursor = CursorPos { x: 1, y: 0 };
+    doc
+  }
+
+  // lo que hace el bucle principal con una tecla de edición: el filtro de solo
+  // lectura, Alt + Y y el resto en cursor_key
+  fn press(doc: &mut Document, key: KeyEvent, kill_ring: &mut KillRing) {
+    if doc.readonly && !is_readonly_key(&key) {
+      return;
+    }
+    kill_ring.start_command();
+    if key.modifiers.contains(KeyModifiers::ALT) && key.code == KeyCode::Char('y') {
+      let Document { lines, cursor, selection_start, .. } = doc;
+      kill_ring.yank_pop(lines, cursor, selection_start);
+    } else {
+      cursor_key(doc, &key, None, &Config::default(), kill_ring, &mut MessageLog::default());
+    }
+  }
+
+  #[test]
+  fn readonly_ignores_every_edit() {
+    let mut kill_ring = KillRing::default();
+    kill_ring.push("uno".to_string());
+    kill_ring.push("dos".to_string());
+    let mut doc = doc("ab");
+    // un pegado justo antes, para que Alt + Y tenga qué cambiar
+    press(&mut doc, key(KeyCode::Char('y'), KeyModifiers::CONTROL), &mut kill_ring);
+    assert_eq!(doc.lines, ["adosb"]);
+    doc.readonly = true;
+
+    let keys = [
+      key(KeyCode::Char('x'), KeyModifiers::NONE),
+      key(KeyCode::Char('V'), KeyModifiers::SHIFT),
+      key(KeyCode::Char('y'), KeyModifiers::ALT),
+      key(KeyCode::Char('e'), KeyModifiers::CONTROL),
+      key(KeyCode::Char('u'), KeyModifiers::CONTROL),
+      key(KeyCode::Char('r'), KeyModifiers::ALT),
+      key(KeyCode::Char('c'), KeyModifiers::ALT),
+      key(KeyCode::Char('y'), KeyModifiers::CONTROL),
+      key(KeyCode::Enter, KeyModifiers::NONE),
+      key(KeyCode::Tab, KeyModifiers::NONE),
+      key(KeyCode::Backspace, KeyModifiers::ALT),
+      key(KeyCode::Delete, KeyModifiers::NONE),
+    ];
+    for key in keys {
+      press(&mut doc, key, &mut kill_ring);
+      assert_eq!(doc.lines, ["adosb"], "{:?}", key);
+    }
+  }
+
+  #[test]
+  fn readonly_allows_moving_and_selecting() {
+    let keys = [
+      key(KeyCode::Left, KeyModifiers::SHIFT | KeyModifiers::CONTROL),
+      key(KeyCode::Right, KeyModifiers::ALT),
+      key(KeyCode::Down, KeyModifiers::ALT | KeyModifiers::SHIFT),
+      key(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::ALT),
+      key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+      key(KeyCode::Char('L'), KeyModifiers::CONTROL | KeyModifiers::SHIFT),
+      key(KeyCode::Char('='), KeyModifiers::ALT),
+      key(KeyCode::Tab, KeyModifiers::CONTROL),
+    ];
+    assert!(keys.iter().all(is_readonly_key));
+    // mover lineas sí cambia el texto
+    assert!(!is_readonly_key(&key(KeyCode::Up, KeyModifiers::ALT)));
+  }
+
+  #[test]
+  fn unbound_shortcuts_do_not_type() {
+    let mut kill_ring = KillRing::default();
+    let mut doc = doc("ab");
+    press(&mut doc, key(KeyCode::Char('e'), KeyModifiers::CONTROL), &mut kill_ring);
+    press(&mut doc, key(KeyCode::Char('r'), KeyModifiers::ALT), &mut kill_ring);
+    assert_eq!(doc.lines, ["ab"]);
+
+    // AltGr llega como Ctrl + Alt
+    press(&mut doc, key(KeyCode::Char('@'), KeyModifiers::CONTROL | KeyModifiers::ALT), &mut kill_ring);
+    press(&mut doc, key(KeyCode::Char('X'), KeyModifiers::SHIFT), &mut kill_ring);
+    assert_eq!(doc.lines, ["a@Xb"]);
+  }
+
+  // documentos guardados con esos nombres
+  fn saved_docs(names: &[&str]) -> Vec<Document> {
+    names.iter()
+      .map(|name| {
+        let mut doc = Document::new();
+        doc.path = Some(format!("/tmp/{}", name));
+        doc
+      })
+      .collect()
+  }
+
+  fn buffer_names(docs: &[Document]) -> Vec<String> {
+    match PopupMode::buffers(docs, 0) {
+      PopupMode::Buffers { names, .. } => names,
+      _ => unreachable!(),
+    }
+  }
+
+  #[test]
+  fn closing_a_document_moves_its_panes_and_renumbers() {
+    let mut docs = saved_docs(&["a.rs", "b.rs", "c.rs"]);
+    let mut panes = Panes::new(1);
+    panes.split(SplitDir::Columns);
+    panes.split(SplitDir::Columns);
+    panes.views[0].doc = 2;
+    panes.views[1].cursor = CursorPos { x: 0, y: 5 };
+
+    assert!(request_close(&mut docs, &mut panes, 1).is_none());
+    let shown: Vec<usize> = panes.views.iter().map(|v| v.doc).collect();
+    // c.rs pasa a ser el 1 y los que mostraban b.rs muestran ahora c.rs
+    assert_eq!(shown, [1, 1, 1]);
+    assert_eq!(panes.views[1].cursor.y, 0);
+    assert_eq!(buffer_names(&docs), [" 1 a.rs  /tmp/a.rs", " 2 c.rs  /tmp/c.rs"]);
+
+    // el último de la lista deja los paneles en el anterior
+    close_document(&mut docs, &mut panes, 1);
+    assert!(panes.views.iter().all(|v| v.doc == 0));
+    assert_eq!(buffer_names(&docs), [" 1 a.rs  /tmp/a.rs"]);
+  }
+
+  #[test]
+  fn closing_the_last_document_leaves_an_empty_one() {
+    let mut docs = saved_docs(&["a.rs"]);
+    let mut panes = Panes::new(0);
+    panes.split(SplitDir::Rows);
+    close_document(&mut docs, &mut panes, 0);
+    assert_eq!(docs.len(), 1);
+    assert!(docs[0].path.is_none());
+    assert!(panes.views.iter().all(|v| v.doc == 0));
+    assert_eq!(buffer_names(&docs), [" 1 [sin nombre]  "]);
+  }
+
+  #[test]
+  fn closing_a_modified_document_asks_first() {
+    let mut docs = saved_docs(&["a.rs", "b.rs"]);
+    docs[1].lines = vec!["cambio".to_string()];
+    let mut panes = Panes::new(1);
+    let popup = request_close(&mut docs, &mut panes, 1);
+    assert!(matches!(popup, Some(PopupMode::ConfirmClose { index: 1, .. })));
+    assert_eq!(docs.len(), 2);
+    assert_eq!(buffer_names(&docs)[1], " 2 b.rs*  /tmp/b.rs");
+  }
+}
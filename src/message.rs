@@ -0,0 +1,173 @@
+use std::time::{ Duration, Instant };
+
+// Mensajes para el usuario (errores, avisos y confirmaciones) que se muestran en la
+// barra de mensajes sobre la linea de estado y se borran solos después de un rato.
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Severity {
+  Info,
+  Warning,
+  Error,
+}
+
+impl Severity {
+  pub fn label(&self) -> &'static str {
+    match self {
+      Severity::Info => "info",
+      Severity::Warning => "aviso",
+      Severity::Error => "error",
+    }
+  }
+
+  // los errores quedan más tiempo a la vista
+  fn duration(&self) -> Duration {
+    match self {
+      Severity::Info => Duration::from_secs(4),
+      Severity::Warning => Duration::from_secs(6),
+      Severity::Error => Duration::from_secs(10),
+    }
+  }
+}
+
+pub struct Message {
+  pub text: String,
+  pub severity: Severity,
+  pub time: Instant,
+}
+
+// límite del historial, para no crecer sin fin
+const MAX_HISTORY: usize = 200;
+
+#[derive(Default)]
+pub struct MessageLog {
+  history: Vec<Message>,
+  visible: bool, // el último mensaje todavía se muestra
+}
+
+impl MessageLog {
+  pub fn info(&mut self, text: impl Into<String>) {
+    self.push(Severity::Info, text.into());
+  }
+
+  pub fn warn(&mut self, text: impl Into<String>) {
+    self.push(Severity::Warning, text.into());
+  }
+
+  pub fn error(&mut self, text: impl Into<String>) {
+    self.push(Severity::Error, text.into());
+  }
+
+  fn push(&mut self, severity: Severity, text: String) {
+    self.push_at(severity, text, Instant::now());
+  }
+
+  // con la hora pasada de afuera, para poder probar el vencimiento
+  fn push_at(&mut self, severity: Severity, text: String, time: Instant) {
+    if self.history.len() >= MAX_HISTORY {
+      self.history.remove(0);
+    }
+    self.history.push(Message { text, severity, time });
+    self.visible = true;
+  }
+
+  // mensaje a mostrar en la barra, si no venció
+  pub fn current(&self) -> Option<&Message> {
+    if self.visible {
+      self.history.last()
+    } else {
+      None
+    }
+  }
+
+  // tiempo que le queda al mensaje actual en pantalla
+  pub fn time_left(&self) -> Option<Duration> {
+    self.time_left_at(Instant::now())
+  }
+
+  fn time_left_at(&self, now: Instant) -> Option<Duration> {
+    let message = self.current()?;
+    Some(message.severity.duration().saturating_sub(now.saturating_duration_since(message.time)))
+  }
+
+  pub fn dismiss(&mut self) {
+    self.visible = false;
+  }
+
+  pub fn history(&self) -> &[Message] {
+    &self.history
+  }
+}
+
+// "hace 5 s", "hace 3 min", "hace 2 h"
+pub fn age(time: Instant) -> String {
+  let secs = time.elapsed().as_secs();
+  match secs {
+    0..=59 => format!("hace {} s", secs),
+    60..=3599 => format!("hace {} min", secs / 60),
+    _ => format!("hace {} h", secs / 3600),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn secs(n: u64) -> Duration {
+    Duration::from_secs(n)
+  }
+
+  #[test]
+  fn history_is_capped() {
+    let mut log = MessageLog::default();
+    for i in 0..MAX_HISTORY + 5 {
+      log.info(i.to_string());
+    }
+    assert_eq!(log.history().len(), MAX_HISTORY);
+    // se van los más viejos
+    assert_eq!(log.history()[0].text, "5");
+    assert_eq!(log.current().map(|m| m.text.as_str()), Some("204"));
+  }
+
+  #[test]
+  fn errors_stay_longer_than_warnings_and_info() {
+    let start = Instant::now();
+    let mut log = MessageLog::default();
+    let left: Vec<Option<Duration>> = [Severity::Info, Severity::Warning, Severity::Error]
+      .into_iter()
+      .map(|severity| {
+        log.push_at(severity, severity.label().to_string(), start);
+        log.time_left_at(start + secs(1))
+      })
+      .collect();
+    assert_eq!(left, [Some(secs(3)), Some(secs(5)), Some(secs(9))]);
+    assert!(log.current().is_some_and(|m| m.severity == Severity::Error));
+  }
+
+  #[test]
+  fn time_left_runs_out() {
+    let start = Instant::now();
+    let mut log = MessageLog::default();
+    assert_eq!(log.time_left_at(start), None);
+
+    log.push_at(Severity::Warning, "aviso".to_string(), start);
+    assert_eq!(log.time_left_at(start), Some(secs(6)));
+    assert_eq!(log.time_left_at(start + secs(6)), Some(Duration::ZERO));
+    assert_eq!(log.time_left_at(start + secs(60)), Some(Duration::ZERO));
+    // un mensaje nuevo vuelve a contar desde su hora
+    log.push_at(Severity::Info, "info".to_string(), start + secs(60));
+    assert_eq!(log.time_left_at(start + secs(61)), Some(secs(3)));
+  }
+
+  #[test]
+  fn dismiss_hides_but_keeps_the_history() {
+    let mut log = MessageLog::default();
+    log.error("fallo");
+    log.dismiss();
+    assert!(log.current().is_none());
+    assert!(log.time_left().is_none());
+    assert_eq!(log.history().len(), 1);
+
+    log.info("otro");
+    assert_eq!(log.current().map(|m| m.text.as_str()), Some("otro"));
+  }
+}
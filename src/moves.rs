@@ -1,41 +1,142 @@
-pub fn move_word_left(lines: &Vec<String>, x: &mut usize, y: usize) {
-  let line: &String = &lines[y];
-  let chars: Vec<char> = line.chars().collect();
+use crate::CursorPos;
+use crate::utils::{ grapheme_ceil, grapheme_floor };
+use crate::word::{ prev_word_start, next_word_start };
 
-  if *x == 0 {
+// Palabra anterior (ver word.rs). Al principio de la linea pasa al final de la anterior.
+pub fn move_word_left(lines: &[String], cursor: &mut CursorPos, word_chars: &[char]) {
+  if cursor.x == 0 {
+    if cursor.y > 0 {
+      cursor.y -= 1;
+      cursor.x = lines[cursor.y].chars().count();
+    }
     return;
   }
 
-  let mut i: usize = *x - 1;
+  let chars: Vec<char> = lines[cursor.y].chars().collect();
+  cursor.x = prev_word_start(&chars, cursor.x, word_chars);
+}
 
-  // retroceder sobre separadores
-  while i > 0 && crate::draw::is_separator(chars[i]) {
-    i -= 1;
+// Palabra siguiente. Al final de la linea pasa al principio de la siguiente.
+pub fn move_word_right(lines: &[String], cursor: &mut CursorPos, word_chars: &[char]) {
+  let chars: Vec<char> = lines[cursor.y].chars().collect();
+  if cursor.x >= chars.len() {
+    if cursor.y + 1 < lines.len() {
+      cursor.y += 1;
+      cursor.x = 0;
+    }
+    return;
   }
 
-  // retroceder hasta el separador anterior
-  while i > 0 && !crate::draw::is_separator(chars[i - 1]) {
+  cursor.x = next_word_start(&chars, cursor.x, word_chars);
+}
+
+// Limite de subpalabra entre chars[i - 1] y chars[i]: joroba de camelCase (aB),
+// cambio entre letra y número (a2), y fin de sigla antes de una palabra (HTTPResponse)
+pub fn is_subword_boundary(chars: &[char], i: usize) -> bool {
+  let (prev, c) = (chars[i - 1], chars[i]);
+  (prev.is_lowercase() && c.is_uppercase())
+    || (prev.is_numeric() != c.is_numeric())
+    || (prev.is_uppercase() && c.is_uppercase() && chars.get(i + 1).is_some_and(|n| n.is_lowercase()))
+}
+
+// Subpalabra anterior: saltea '_', '-', espacios y puntuación y vuelve al principio de
+// la parte del identificador. Al principio de la linea pasa al final de la anterior.
+pub fn move_subword_left(lines: &[String], cursor: &mut CursorPos) {
+  if cursor.x == 0 {
+    if cursor.y > 0 {
+      cursor.y -= 1;
+      cursor.x = lines[cursor.y].chars().count();
+    }
+    return;
+  }
+
+  let chars: Vec<char> = lines[cursor.y].chars().collect();
+  let mut i = cursor.x.min(chars.len());
+
+  while i > 0 && !chars[i - 1].is_alphanumeric() {
     i -= 1;
   }
+  if i > 0 {
+    i -= 1;
+    while i > 0 && chars[i - 1].is_alphanumeric() && !is_subword_boundary(&chars, i) {
+      i -= 1;
+    }
+  }
 
-  *x = i;
+  cursor.x = grapheme_floor(&lines[cursor.y], i);
 }
 
-pub fn move_word_right(lines: &Vec<String>, x: &mut usize, y: usize) {
-  let line = &lines[y];
-  let chars: Vec<char> = line.chars().collect();
-
-  let mut i = *x;
+// Subpalabra siguiente: hasta el final de la próxima parte del identificador.
+// Al final de la linea pasa al principio de la siguiente.
+pub fn move_subword_right(lines: &[String], cursor: &mut CursorPos) {
+  let chars: Vec<char> = lines[cursor.y].chars().collect();
+  if cursor.x >= chars.len() {
+    if cursor.y + 1 < lines.len() {
+      cursor.y += 1;
+      cursor.x = 0;
+    }
+    return;
+  }
 
-  // avanzo hasta encontrar un separador
-  while i < chars.len() && !crate::draw::is_separator(chars[i]) {
+  let mut i = cursor.x;
+  while i < chars.len() && !chars[i].is_alphanumeric() {
     i += 1;
   }
-
-  // salto todos los separadores que me cruce
-  while i < chars.len() && crate::draw::is_separator(chars[i]) {
+  if i < chars.len() {
     i += 1;
+    while i < chars.len() && chars[i].is_alphanumeric() && !is_subword_boundary(&chars, i) {
+      i += 1;
+    }
+  }
+
+  cursor.x = grapheme_ceil(&lines[cursor.y], i);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // columnas por las que pasa el cursor desde `x` hasta que deja de moverse o cambia de linea
+  fn stops(line: &str, x: usize, step: fn(&[String], &mut CursorPos)) -> Vec<usize> {
+    let lines = vec![line.to_string()];
+    let mut cursor = CursorPos { x, y: 0 };
+    let mut stops = Vec::new();
+    loop {
+      let before = cursor.x;
+      step(&lines, &mut cursor);
+      if cursor.x == before {
+        return stops;
+      }
+      stops.push(cursor.x);
+    }
   }
 
-  *x = i;
-}
\ No newline at end of file
+  #[test]
+  fn subword_boundaries() {
+    let chars: Vec<char> = "parseHTTPResponse2".chars().collect();
+    let boundaries: Vec<usize> = (1..chars.len()).filter(|&i| is_subword_boundary(&chars, i)).collect();
+    assert_eq!(boundaries, [5, 9, 17]);
+  }
+
+  #[test]
+  fn subword_right_stops_at_part_ends() {
+    assert_eq!(stops("parseHTTPResponse2", 0, move_subword_right), [5, 9, 17, 18]);
+    assert_eq!(stops("my_var = x", 0, move_subword_right), [2, 6, 10]);
+  }
+
+  #[test]
+  fn subword_left_stops_at_part_starts() {
+    assert_eq!(stops("parseHTTPResponse2", 18, move_subword_left), [17, 9, 5, 0]);
+    assert_eq!(stops("my_var = x", 10, move_subword_left), [9, 3, 0]);
+  }
+
+  #[test]
+  fn subword_moves_cross_lines() {
+    let lines = vec!["ab".to_string(), "cd".to_string()];
+    let mut cursor = CursorPos { x: 2, y: 0 };
+    move_subword_right(&lines, &mut cursor);
+    assert_eq!((cursor.x, cursor.y), (0, 1));
+    move_subword_left(&lines, &mut cursor);
+    assert_eq!((cursor.x, cursor.y), (2, 0));
+  }
+}
@@ -0,0 +1,360 @@
+use crate::CursorPos;
+use crate::document::Document;
+use crate::selection::{ Selection, get_selected_text, merge_selections };
+use crate::utils::{ col_to_char, display_col, line_len_chars };
+use crate::word::word_at;
+
+// Varios cursores, como en Sublime o VS Code. El documento guarda el principal en
+// cursor / selection_start y el resto en `others`. Las teclas de edición y movimiento
+// se repiten en cada cursor de abajo hacia arriba: lo que se edita en uno no mueve a
+// los que faltan, y los ya hechos se guardan como distancia al final del documento (en
+// lineas) y al final de su linea (en chars), que no cambian al editar más arriba.
+
+// (lineas hasta el final del documento, chars hasta el final de la linea)
+type Anchor = (usize, usize);
+
+fn anchor(lines: &[String], pos: CursorPos) -> Anchor {
+  (lines.len() - 1 - pos.y, line_len_chars(&lines[pos.y]).saturating_sub(pos.x))
+}
+
+fn unanchor(lines: &[String], (from_end, from_line_end): Anchor) -> CursorPos {
+  let y = (lines.len() - 1).saturating_sub(from_end);
+  CursorPos { x: line_len_chars(&lines[y]).saturating_sub(from_line_end), y }
+}
+
+// Una pasada de una tecla por todos los cursores
+pub struct Rounds {
+  order: Vec<Selection>,                 // de abajo hacia arriba; vacío con un solo cursor
+  primary: usize,                        // índice del principal en `order`
+  done: Vec<(Anchor, Option<Anchor>)>,
+}
+
+impl Rounds {
+  // Toma los cursores del documento
+  pub fn new(doc: &mut Document) -> Rounds {
+    if doc.others.is_empty() {
+      return Rounds { order: Vec::new(), primary: 0, done: Vec::new() };
+    }
+
+    let primary = Selection { cursor: doc.cursor, start: doc.selection_start };
+    let mut order: Vec<Selection> = std::iter::once(primary).chain(doc.others.drain(..)).collect();
+    order.sort_by_key(|s| { let (a, _) = s.range(); std::cmp::Reverse((a.y, a.x)) });
+    let primary = order.iter().position(|s| *s == primary).unwrap_or(0);
+    Rounds { order, primary, done: Vec::new() }
+  }
+
+  pub fn len(&self) -> usize {
+    self.order.len().max(1)
+  }
+
+  // Pone el cursor de la vuelta `round` como el del documento
+  pub fn load(&self, doc: &mut Document, round: usize) {
+    if let Some(sel) = self.order.get(round) {
+      doc.cursor = sel.cursor;
+      doc.selection_start = sel.start;
+    }
+  }
+
+  pub fn store(&mut self, doc: &Document) {
+    if self.order.is_empty() {
+      return;
+    }
+    let start = doc.selection_start.map(|start| anchor(&doc.lines, start));
+    self.done.push((anchor(&doc.lines, doc.cursor), start));
+  }
+
+  // Devuelve los cursores al documento, juntando los que quedaron encimados
+  pub fn finish(self, doc: &mut Document) {
+    if self.order.is_empty() {
+      return;
+    }
+
+    let mut selections: Vec<Selection> = self.done.iter()
+      .map(|(cursor, start)| Selection {
+        cursor: unanchor(&doc.lines, *cursor),
+        start: start.map(|start| unanchor(&doc.lines, start)),
+      })
+      .collect();
+    set_selections(doc, &mut selections, self.primary);
+  }
+
+  // Ctrl + V con tantas lineas como cursores: a cada cursor su linea
+  pub fn piece(&self, text: &str, round: usize) -> String {
+    let parts: Vec<&str> = text.split('\n').collect();
+    if self.order.len() > 1 && parts.len() == self.order.len() {
+      parts[parts.len() - 1 - round].to_string()
+    } else {
+      text.to_string()
+    }
+  }
+}
+
+// Todas las selecciones, la principal primero
+pub fn selections(doc: &Document) -> Vec<Selection> {
+  std::iter::once(Selection { cursor: doc.cursor, start: doc.selection_start })
+    .chain(doc.others.iter().copied())
+    .collect()
+}
+
+// Texto de todas las selecciones, en el orden del documento y una por linea. Los
+// cursores sin selección no aportan nada, salvo en un bloque (`block`): ahí dejan su
+// linea vacía, así el bloque se pega alineado aunque tenga lineas cortas.
+pub fn selected_text(doc: &Document, block: bool) -> Option<String> {
+  let mut all = selections(doc);
+  all.sort_by_key(|s| { let (a, _) = s.range(); (a.y, a.x) });
+  let parts: Vec<String> = all.iter()
+    .map(|s| get_selected_text(&doc.lines, &s.cursor, &s.start).filter(|text| !text.is_empty()))
+    .filter_map(|text| if block { Some(text.unwrap_or_default()) } else { text })
+    .collect();
+  if parts.iter().all(|p| p.is_empty()) { None } else { Some(parts.join("\n")) }
+}
+
+pub fn set_selections(doc: &mut Document, selections: &mut Vec<Selection>, primary: usize) {
+  let primary = merge_selections(selections, primary);
+  let main = selections.remove(primary);
+  doc.cursor = main.cursor;
+  doc.selection_start = main.start;
+  doc.others = std::mem::take(selections);
+}
+
+// Ctrl + Alt + Arriba / Abajo: un cursor más en la linea de arriba del primero (o de
+// abajo del último), en la misma columna de pantalla. El nuevo queda como principal.
+pub fn add_cursor(doc: &mut Document, up: bool, tab_width: usize) {
+  let mut all = selections(doc);
+  let edge = if up {
+    all.iter().map(|s| s.cursor).min_by_key(|c| (c.y, c.x))
+  } else {
+    all.iter().map(|s| s.cursor).max_by_key(|c| (c.y, c.x))
+  };
+  let Some(edge) = edge else { return };
+  if (up && edge.y == 0) || (!up && edge.y + 1 >= doc.lines.len()) {
+    return;
+  }
+
+  let y = if up { edge.y - 1 } else { edge.y + 1 };
+  let col = display_col(&doc.lines[edge.y], edge.x, tab_width);
+  all.push(Selection { cursor: CursorPos { x: col_to_char(&doc.lines[y], col, tab_width), y }, start: None });
+  let primary = all.len() - 1;
+  set_selections(doc, &mut all, primary);
+}
+
+// Texto a buscar: la selección principal, o la palabra del cursor (que queda
+// seleccionada). None si no hay ninguna de las dos.
+fn search_text(doc: &mut Document, word_chars: &[char]) -> Option<String> {
+  if let Some(text) = get_selected_text(&doc.lines, &doc.cursor, &doc.selection_start).filter(|t| !t.is_empty()) {
+    return Some(text);
+  }
+
+  let chars: Vec<char> = doc.lines[doc.cursor.y].chars().collect();
+  let (from, to) = word_at(&chars, doc.cursor.x, word_chars)?;
+  doc.selection_start = Some(CursorPos { x: from, y: doc.cursor.y });
+  doc.cursor.x = to;
+  Some(chars[from..to].iter().collect())
+}
+
+// Ctrl + D: sin selección toma la palabra del cursor; con selección agrega la
+// siguiente aparición del texto (volviendo al principio al llegar al final).
+// Devuelve false si no hay más apariciones sin seleccionar.
+pub fn add_next_occurrence(doc: &mut Document, word_chars: &[char]) -> bool {
+  let had_selection = get_selected_text(&doc.lines, &doc.cursor, &doc.selection_start).is_some_and(|t| !t.is_empty());
+  let Some(text) = search_text(doc, word_chars) else {
+    return false;
+  };
+  if !had_selection {
+    return true;
+  }
+
+  let taken: Vec<(CursorPos, CursorPos)> = selections(doc).iter().map(|s| s.range()).collect();
+  let (_, mut from) = Selection { cursor: doc.cursor, start: doc.selection_start }.range();
+  for _ in 0..=taken.len() {
+    let Some((start, end)) = find_from(&doc.lines, &text, from) else {
+      return false;
+    };
+    if !taken.contains(&(start, end)) {
+      let mut all = selections(doc);
+      all.push(Selection { cursor: end, start: Some(start) });
+      let primary = all.len() - 1;
+      set_selections(doc, &mut all, primary);
+      return true;
+    }
+    from = end;
+  }
+  false
+}
+
+// Ctrl + K, A: selecciona todas las apariciones de la selección o de la palabra del
+// cursor. Devuelve cuántas hay.
+pub fn select_all_occurrences(doc: &mut Document, word_chars: &[char]) -> usize {
+  let Some(text) = search_text(doc, word_chars) else {
+    return 0;
+  };
+
+  let current = Selection { cursor: doc.cursor, start: doc.selection_start }.range();
+  let found = find_all(&doc.lines, &text);
+  let primary = found.iter().position(|range| *range == current).unwrap_or(0);
+  let mut all: Vec<Selection> = found.into_iter()
+    .map(|(start, end)| Selection { cursor: end, start: Some(start) })
+    .collect();
+
+  let count = all.len();
+  if count > 0 {
+    set_selections(doc, &mut all, primary);
+  }
+  count
+}
+
+// Próxima aparición de `text` desde `from`, volviendo al principio si hace falta:
+// (principio, fin)
+fn find_from(lines: &[String], text: &str, from: CursorPos) -> Option<(CursorPos, CursorPos)> {
+  let joined = lines.join("\n");
+  let offset: usize = lines[..from.y].iter().map(|l| l.len() + 1).sum::<usize>()
+    + crate::utils::char_to_byte_idx(&lines[from.y], from.x);
+
+  let found = joined[offset..].find(text).map(|i| i + offset).or_else(|| joined.find(text))?;
+  Some((byte_to_pos(&joined, found), byte_to_pos(&joined, found + text.len())))
+}
+
+// Todas las apariciones de `text`, sin encimarse
+fn find_all(lines: &[String], text: &str) -> Vec<(CursorPos, CursorPos)> {
+  let joined = lines.join("\n");
+  let mut found = Vec::new();
+  // posición del último byte convertido: se avanza de a poco para no recorrer todo cada vez
+  let (mut scanned, mut y, mut line_start) = (0, 0, 0);
+  let mut pos_at = |byte: usize| {
+    for (i, c) in joined[scanned..byte].char_indices() {
+      if c == '\n' {
+        y += 1;
+        line_start = scanned + i + 1;
+      }
+    }
+    scanned = byte;
+    CursorPos { x: joined[line_start..byte].chars().count(), y }
+  };
+
+  for (byte, _) in joined.match_indices(text) {
+    let start = pos_at(byte);
+    found.push((start, pos_at(byte + text.len())));
+  }
+  found
+}
+
+fn byte_to_pos(joined: &str, byte: usize) -> CursorPos {
+  let before = &joined[..byte];
+  let y = before.matches('\n').count();
+  let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+  CursorPos { x: before[line_start..].chars().count(), y }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn doc(lines: &[&str]) -> Document {
+    let mut doc = Document::new();
+    doc.lines = lines.iter().map(|l| l.to_string()).collect();
+    doc
+  }
+
+  // (x, y) de cada cursor, la principal primero, y el texto de cada selección
+  fn cursors(doc: &Document) -> Vec<((usize, usize), String)> {
+    selections(doc).iter()
+      .map(|s| ((s.cursor.x, s.cursor.y), get_selected_text(&doc.lines, &s.cursor, &s.start).unwrap_or_default()))
+      .collect()
+  }
+
+  fn cursor(x: usize, y: usize) -> Selection {
+    Selection { cursor: CursorPos { x, y }, start: None }
+  }
+
+  #[test]
+  fn anchors_survive_edits_above() {
+    let mut lines: Vec<String> = vec!["ab".to_string(), "cdef".to_string()];
+    let anchored = anchor(&lines, CursorPos { x: 1, y: 1 });
+    lines[0].push_str("xyz");
+    lines.insert(0, String::new());
+    let pos = unanchor(&lines, anchored);
+    assert_eq!((pos.x, pos.y), (1, 2));
+  }
+
+  #[test]
+  fn rounds_type_in_every_cursor() {
+    let mut doc = doc(&["ab", "cd", "ef"]);
+    doc.cursor = CursorPos { x: 1, y: 0 };
+    doc.others = vec![cursor(1, 1), cursor(1, 2)];
+
+    let mut rounds = Rounds::new(&mut doc);
+    assert_eq!(rounds.len(), 3);
+    for round in 0..rounds.len() {
+      rounds.load(&mut doc, round);
+      let (x, y) = (doc.cursor.x, doc.cursor.y);
+      doc.lines[y].insert_str(x, &rounds.piece("1\n2\n3", round));
+      doc.cursor.x += 1;
+      rounds.store(&doc);
+    }
+    rounds.finish(&mut doc);
+
+    assert_eq!(doc.lines, ["a1b", "c2d", "e3f"]);
+    assert_eq!(cursors(&doc).iter().map(|(pos, _)| *pos).collect::<Vec<_>>(), [(2, 0), (2, 1), (2, 2)]);
+  }
+
+  #[test]
+  fn piece_only_splits_matching_line_counts() {
+    let mut doc = doc(&["a", "b"]);
+    doc.others = vec![cursor(0, 1)];
+    let rounds = Rounds::new(&mut doc);
+    assert_eq!((rounds.piece("x\ny", 0), rounds.piece("x\ny", 1)), ("y".to_string(), "x".to_string()));
+    assert_eq!(rounds.piece("x\ny\nz", 0), "x\ny\nz");
+
+    let single = Rounds::new(&mut doc);
+    assert_eq!((single.len(), single.piece("x\ny", 0)), (1, "x\ny".to_string()));
+  }
+
+  #[test]
+  fn selected_text_joins_selections_in_order() {
+    let mut doc = doc(&["foo bar", "baz"]);
+    doc.cursor = CursorPos { x: 3, y: 1 };
+    doc.selection_start = Some(CursorPos { x: 0, y: 1 });
+    doc.others = vec![
+      Selection { cursor: CursorPos { x: 3, y: 0 }, start: Some(CursorPos { x: 0, y: 0 }) },
+      cursor(5, 0),
+    ];
+    assert_eq!(selected_text(&doc, false).as_deref(), Some("foo\nbaz"));
+
+    doc.selection_start = None;
+    doc.others = vec![cursor(1, 0)];
+    assert_eq!(selected_text(&doc, false), None);
+  }
+
+  #[test]
+  fn ctrl_d_adds_next_occurrences() {
+    let mut doc = doc(&["foo x foo", "foo"]);
+    doc.cursor = CursorPos { x: 1, y: 0 };
+    assert!(add_next_occurrence(&mut doc, &[]));
+    assert_eq!(cursors(&doc), [((3, 0), "foo".to_string())]);
+
+    assert!(add_next_occurrence(&mut doc, &[]));
+    assert!(add_next_occurrence(&mut doc, &[]));
+    assert_eq!(cursors(&doc).iter().map(|(pos, _)| *pos).collect::<Vec<_>>(), [(3, 1), (3, 0), (9, 0)]);
+    assert!(!add_next_occurrence(&mut doc, &[]));
+  }
+
+  #[test]
+  fn select_all_keeps_the_current_as_primary() {
+    let mut doc = doc(&["año año", "xaño"]);
+    doc.cursor = CursorPos { x: 5, y: 0 };
+    assert_eq!(select_all_occurrences(&mut doc, &[]), 3);
+    assert_eq!(cursors(&doc).iter().map(|(pos, _)| *pos).collect::<Vec<_>>(), [(7, 0), (3, 0), (4, 1)]);
+
+    let mut empty = self::doc(&["  "]);
+    assert_eq!(select_all_occurrences(&mut empty, &[]), 0);
+  }
+
+  #[test]
+  fn add_cursor_keeps_the_screen_column() {
+    let mut doc = doc(&["\tx", "abcdef", "ab"]);
+    doc.cursor = CursorPos { x: 2, y: 1 };
+    add_cursor(&mut doc, true, 4);
+    add_cursor(&mut doc, false, 4);
+    assert_eq!(cursors(&doc).iter().map(|(pos, _)| *pos).collect::<Vec<_>>(), [(2, 2), (0, 0), (2, 1)]);
+  }
+}
@@ -0,0 +1,404 @@
+// Paneles: vistas de documentos con scroll propio, organizadas en un árbol de divisiones
+
+use crate::CursorPos;
+use crate::layout::Rect;
+
+// Una ventana sobre un documento. Dos vistas pueden mostrar el mismo documento, cada
+// una con su cursor: el de la vista enfocada es el del documento, las demás guardan el
+// suyo acá (ver change_view en main.rs).
+#[derive(Clone, Copy)]
+pub struct View {
+  pub doc: usize,
+  pub scroll_x: usize,
+  pub scroll_y: usize,
+  pub cursor: CursorPos,
+  pub selection_start: Option<CursorPos>,
+}
+
+impl View {
+  pub fn new(doc: usize, scroll_x: usize, scroll_y: usize) -> Self {
+    View { doc, scroll_x, scroll_y, cursor: CursorPos { x: 0, y: 0 }, selection_start: None }
+  }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SplitDir {
+  Columns, // lado a lado
+  Rows,    // apiladas
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Direction {
+  Left,
+  Right,
+  Up,
+  Down,
+}
+
+enum Node {
+  Leaf(usize), // indice en `views`
+  Split { dir: SplitDir, ratio: usize, first: Box<Node>, second: Box<Node> }, // ratio en % para `first`
+}
+
+pub struct Panes {
+  pub views: Vec<View>,
+  pub focused: usize,
+  root: Node,
+  synced: Option<(usize, usize, usize)>, // (documento, primera linea con cursor, lineas) en el último follow_edit
+}
+
+impl Panes {
+  pub fn new(doc: usize) -> Self {
+    Panes {
+      views: vec![View::new(doc, 0, 0)],
+      focused: 0,
+      root: Node::Leaf(0),
+      synced: None,
+    }
+  }
+
+  pub fn view(&self) -> &View {
+    &self.views[self.focused]
+  }
+
+  pub fn view_mut(&mut self) -> &mut View {
+    &mut self.views[self.focused]
+  }
+
+  pub fn is_split(&self) -> bool {
+    self.views.len() > 1
+  }
+
+  // divide el panel enfocado; el nuevo muestra el mismo documento y queda enfocado
+  pub fn split(&mut self, dir: SplitDir) {
+    let new_view = self.views.len();
+    self.views.push(self.views[self.focused]);
+
+    let focused = self.focused;
+    replace_leaf(&mut self.root, focused, &mut |leaf| Node::Split {
+      dir,
+      ratio: 50,
+      first: Box::new(leaf),
+      second: Box::new(Node::Leaf(new_view)),
+    });
+    self.focused = new_view;
+  }
+
+  // cierra el panel enfocado (salvo que sea el único); el hermano ocupa su lugar
+  pub fn close(&mut self) {
+    if !self.is_split() {
+      return;
+    }
+
+    let closed = self.focused;
+    let root = std::mem::replace(&mut self.root, Node::Leaf(0));
+    self.root = remove_leaf(root, closed).unwrap_or(Node::Leaf(0));
+    self.views.remove(closed);
+    renumber(&mut self.root, closed);
+
+    self.focused = first_leaf(&self.root);
+  }
+
+  // agranda (delta > 0) o achica el panel enfocado dentro de su división
+  pub fn resize(&mut self, delta: isize) {
+    resize_leaf(&mut self.root, self.focused, delta);
+  }
+
+  // mueve el foco al panel vecino en la dirección indicada
+  pub fn focus(&mut self, direction: Direction, area: Rect) {
+    let rects = self.layout(area);
+    let Some(&(_, current)) = rects.iter().find(|(v, _)| *v == self.focused) else { return };
+
+    let center = |r: &Rect| (r.x * 2 + r.width, r.y * 2 + r.height); // x2 para evitar fracciones
+    let (cx, cy) = center(&current);
+
+    let best = rects.iter()
+      .filter(|(v, r)| *v != self.focused && match direction {
+        Direction::Left => r.x + r.width <= current.x,
+        Direction::Right => r.x >= current.x + current.width,
+        Direction::Up => r.y + r.height <= current.y,
+        Direction::Down => r.y >= current.y + current.height,
+      })
+      .min_by_key(|(_, r)| {
+        let (x, y) = center(r);
+        x.abs_diff(cx) + y.abs_diff(cy)
+      });
+
+    if let Some(&(view, _)) = best {
+      self.focused = view;
+    }
+  }
+
+  // rectángulo de cada vista dentro de `area` (sin contar los separadores)
+  pub fn layout(&self, area: Rect) -> Vec<(usize, Rect)> {
+    let mut out = Vec::new();
+    layout_node(&self.root, area, &mut out);
+    out
+  }
+
+  // Después de cada tecla, con la primera linea con cursor del documento enfocado y
+  // sus lineas. Si desde la vez anterior se agregaron o borraron lineas, las otras
+  // vistas del documento con el cursor más abajo que esa linea se corren lo mismo, como
+  // los cursores ya hechos en multicursor.rs.
+  pub fn follow_edit(&mut self, top: usize, lines: usize) {
+    let doc = self.view().doc;
+    if let Some((synced_doc, synced_top, synced_lines)) = self.synced
+      && synced_doc == doc
+      && synced_lines != lines
+    {
+      let delta = lines as isize - synced_lines as isize;
+      for (i, view) in self.views.iter_mut().enumerate() {
+        if i == self.focused || view.doc != doc {
+          continue;
+        }
+        for pos in std::iter::once(&mut view.cursor).chain(view.selection_start.iter_mut()) {
+          if pos.y > synced_top {
+            pos.y = pos.y.saturating_add_signed(delta).max(synced_top);
+          }
+        }
+      }
+    }
+    self.synced = Some((doc, top, lines));
+  }
+
+  // ajusta las vistas después de cerrar el documento `index`
+  pub fn doc_closed(&mut self, index: usize, replacement: usize) {
+    self.synced = None;
+    for view in self.views.iter_mut() {
+      if view.doc == index {
+        *view = View::new(replacement, 0, 0);
+      } else if view.doc > index {
+        view.doc -= 1;
+      }
+    }
+  }
+}
+
+fn replace_leaf(node: &mut Node, target: usize, make: &mut dyn FnMut(Node) -> Node) -> bool {
+  match node {
+    Node::Leaf(v) if *v == target => {
+      let leaf = std::mem::replace(node, Node::Leaf(target));
+      *node = make(leaf);
+      true
+    }
+    Node::Leaf(_) => false,
+    Node::Split { first, second, .. } => {
+      replace_leaf(first, target, make) || replace_leaf(second, target, make)
+    }
+  }
+}
+
+fn remove_leaf(node: Node, target: usize) -> Option<Node> {
+  match node {
+    Node::Leaf(v) if v == target => None,
+    Node::Leaf(v) => Some(Node::Leaf(v)),
+    Node::Split { dir, ratio, first, second } => {
+      match (remove_leaf(*first, target), remove_leaf(*second, target)) {
+        (Some(first), Some(second)) => Some(Node::Split { dir, ratio, first: Box::new(first), second: Box::new(second) }),
+        (Some(only), None) | (None, Some(only)) => Some(only),
+        (None, None) => None,
+      }
+    }
+  }
+}
+
+// corrige los indices de las hojas después de quitar la vista `removed`
+fn renumber(node: &mut Node, removed: usize) {
+  match node {
+    Node::Leaf(v) => {
+      if *v > removed {
+        *v -= 1;
+      }
+    }
+    Node::Split { first, second, .. } => {
+      renumber(first, removed);
+      renumber(second, removed);
+    }
+  }
+}
+
+fn first_leaf(node: &Node) -> usize {
+  match node {
+    Node::Leaf(v) => *v,
+    Node::Split { first, .. } => first_leaf(first),
+  }
+}
+
+fn contains(node: &Node, target: usize) -> bool {
+  match node {
+    Node::Leaf(v) => *v == target,
+    Node::Split { first, second, .. } => contains(first, target) || contains(second, target),
+  }
+}
+
+fn resize_leaf(node: &mut Node, target: usize, delta: isize) -> bool {
+  let Node::Split { ratio, first, second, .. } = node else { return false };
+
+  // primero la división más interna que contiene al panel
+  if resize_leaf(first, target, delta) || resize_leaf(second, target, delta) {
+    return true;
+  }
+
+  let change = if contains(first, target) {
+    delta
+  } else if contains(second, target) {
+    -delta
+  } else {
+    return false;
+  };
+
+  *ratio = (*ratio as isize + change).clamp(10, 90) as usize;
+  true
+}
+
+fn layout_node(node: &Node, area: Rect, out: &mut Vec<(usize, Rect)>) {
+  match node {
+    Node::Leaf(v) => out.push((*v, area)),
+    Node::Split { dir: SplitDir::Columns, ratio, first, second } => {
+      // una columna para el separador vertical
+      let usable = area.width.saturating_sub(1);
+      let first_width = usable * ratio / 100;
+      layout_node(first, Rect { width: first_width, ..area }, out);
+      layout_node(second, Rect {
+        x: area.x + first_width + 1,
+        width: usable - first_width,
+        ..area
+      }, out);
+    }
+    Node::Split { dir: SplitDir::Rows, ratio, first, second } => {
+      let first_height = area.height * ratio / 100;
+      layout_node(first, Rect { height: first_height, ..area }, out);
+      layout_node(second, Rect {
+        y: area.y + first_height,
+        height: area.height - first_height,
+        ..area
+      }, out);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const AREA: Rect = Rect { x: 0, y: 0, width: 81, height: 20 };
+
+  // (vista, x, y, ancho, alto) de cada panel
+  fn rects(panes: &Panes) -> Vec<(usize, usize, usize, usize, usize)> {
+    panes.layout(AREA).iter().map(|(v, r)| (*v, r.x, r.y, r.width, r.height)).collect()
+  }
+
+  fn pos(x: usize, y: usize) -> CursorPos {
+    CursorPos { x, y }
+  }
+
+  #[test]
+  fn split_shares_the_area() {
+    let mut panes = Panes::new(3);
+    panes.split(SplitDir::Columns);
+    assert_eq!(panes.focused, 1);
+    assert_eq!(panes.view().doc, 3);
+    // una columna para el separador
+    assert_eq!(rects(&panes), [(0, 0, 0, 40, 20), (1, 41, 0, 40, 20)]);
+
+    panes.split(SplitDir::Rows);
+    assert_eq!(rects(&panes), [(0, 0, 0, 40, 20), (1, 41, 0, 40, 10), (2, 41, 10, 40, 10)]);
+  }
+
+  #[test]
+  fn close_gives_the_area_to_the_sibling() {
+    let mut panes = Panes::new(0);
+    panes.split(SplitDir::Columns);
+    panes.split(SplitDir::Rows);
+    panes.views[2].doc = 7;
+
+    panes.focused = 1;
+    panes.close();
+    // la vista 2 pasa a ser la 1 y ocupa toda la columna derecha
+    assert_eq!(rects(&panes), [(0, 0, 0, 40, 20), (1, 41, 0, 40, 20)]);
+    assert_eq!(panes.views[1].doc, 7);
+    assert_eq!(panes.focused, 0);
+
+    panes.close();
+    assert_eq!(rects(&panes), [(0, 0, 0, 81, 20)]);
+    assert_eq!(panes.view().doc, 7);
+    // la única no se cierra
+    panes.close();
+    assert_eq!(panes.views.len(), 1);
+  }
+
+  #[test]
+  fn focus_moves_to_the_nearest_neighbour() {
+    let mut panes = Panes::new(0);
+    panes.split(SplitDir::Columns);
+    panes.split(SplitDir::Rows);
+
+    panes.focus(Direction::Up, AREA);
+    assert_eq!(panes.focused, 1);
+    panes.focus(Direction::Up, AREA);
+    assert_eq!(panes.focused, 1);
+    panes.focus(Direction::Left, AREA);
+    assert_eq!(panes.focused, 0);
+    panes.focus(Direction::Right, AREA);
+    assert_eq!(panes.focused, 1);
+    panes.focus(Direction::Down, AREA);
+    assert_eq!(panes.focused, 2);
+  }
+
+  #[test]
+  fn resize_is_clamped() {
+    let mut panes = Panes::new(0);
+    panes.split(SplitDir::Columns);
+    panes.resize(10);
+    // el enfocado es el de la derecha: crece hacia la izquierda
+    assert_eq!(rects(&panes), [(0, 0, 0, 32, 20), (1, 33, 0, 48, 20)]);
+
+    panes.resize(100);
+    assert_eq!(rects(&panes)[0].3, 8);
+    panes.resize(-100);
+    assert_eq!(rects(&panes)[0].3, 72);
+  }
+
+  #[test]
+  fn closed_documents_are_renumbered() {
+    let mut panes = Panes::new(0);
+    panes.split(SplitDir::Columns);
+    panes.split(SplitDir::Columns);
+    panes.views[0].doc = 2;
+    panes.views[1].doc = 1;
+    panes.views[1].cursor = pos(3, 4);
+
+    panes.doc_closed(1, 1);
+    let docs: Vec<usize> = panes.views.iter().map(|v| v.doc).collect();
+    assert_eq!(docs, [1, 1, 0]);
+    // la vista que lo mostraba empieza de nuevo en el reemplazo
+    assert_eq!((panes.views[1].cursor.x, panes.views[1].cursor.y), (0, 0));
+  }
+
+  #[test]
+  fn other_views_follow_lines_added_or_removed_above() {
+    let mut panes = Panes::new(0);
+    panes.split(SplitDir::Columns);
+    panes.split(SplitDir::Columns);
+    panes.views[0].cursor = pos(2, 10);
+    panes.views[0].selection_start = Some(pos(0, 8));
+    panes.views[1].cursor = pos(1, 3);
+    panes.views[1].doc = 1;
+    panes.follow_edit(5, 20);
+
+    // tres lineas más a partir de la 5: la vista de más abajo baja, la de otro documento no
+    panes.follow_edit(8, 23);
+    assert_eq!((panes.views[0].cursor.y, panes.views[0].selection_start.map(|s| s.y)), (13, Some(11)));
+    assert_eq!(panes.views[1].cursor.y, 3);
+
+    // borrar desde la 8 hasta el final deja la vista en la 8
+    panes.follow_edit(8, 9);
+    assert_eq!((panes.views[0].cursor.y, panes.views[0].selection_start.map(|s| s.y)), (8, Some(8)));
+
+    // una vista más arriba de lo editado se queda donde estaba
+    panes.views[0].cursor = pos(0, 2);
+    panes.views[0].selection_start = None;
+    panes.follow_edit(8, 15);
+    assert_eq!(panes.views[0].cursor.y, 2);
+  }
+}
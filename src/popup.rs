@@ -1,6 +1,16 @@
 use std::io::stdout;
+use std::path::Path;
 
-use crossterm::{cursor::MoveTo, execute, style::{Color, ResetColor, SetForegroundColor, SetBackgroundColor}};
+use crossterm::{cursor::MoveTo, execute};
+
+use crate::case::{ CASES, Case };
+use crate::document::Document;
+use crate::file::preview_entry;
+use crate::input::TextInput;
+use crate::layout::{ Layout, popup_list_rows, scroll_to_show };
+use crate::lineops::{ ORDERS, LineOrder };
+use crate::message::{ MessageLog, age };
+use crate::theme::{ Theme, reset };
 
 #[derive(Clone)]
 pub struct Popup {
@@ -10,14 +20,74 @@ pub struct Popup {
   pub width: usize,
   pub height: usize,
   pub selected_line: Option<usize>,
-  pub scroll: usize,
+  pub input_cursor: Option<usize>, // columna del cursor en la primera linea (campo de texto)
+  pub preview: Vec<String>,        // panel de la derecha (vacío = sin panel)
+  pub preview_from: usize,         // fila de contenido donde empiezan la lista y el panel
+}
+
+// Pregunta pendiente dentro del popup de Guardar (ocupa la fila de "Guardar como:")
+#[derive(Clone)]
+pub enum SavePrompt {
+  Overwrite(String),                          // ruta completa del archivo que ya existe
+  Delete(String),                             // entrada a mover a la papelera
+  NewFolder(TextInput),                       // nombre de la carpeta nueva
+  Rename { entry: String, input: TextInput },
+  Duplicate { entry: String, input: TextInput },
+}
+
+impl SavePrompt {
+  pub fn input_mut(&mut self) -> Option<&mut TextInput> {
+    match self {
+      SavePrompt::NewFolder(input)
+      | SavePrompt::Rename { input, .. }
+      | SavePrompt::Duplicate { input, .. } => Some(input),
+      SavePrompt::Overwrite(_) | SavePrompt::Delete(_) => None,
+    }
+  }
 }
 
 #[derive(Clone)]
 pub enum PopupMode {
-  Save { selected: Option<usize>, entries: Vec<String>, scroll_y: usize },
-  Open { selected: usize, entries: Vec<String>, scroll_y: usize },
-  Help,
+  Save { selected: Option<usize>, entries: Vec<String>, scroll_y: usize, prompt: Option<SavePrompt> },
+  Open { selected: usize, entries: Vec<String>, scroll_y: usize, preview: Vec<String> },
+  Buffers { selected: usize, names: Vec<String> },
+  Messages { selected: usize, lines: Vec<String> },
+  ConfirmClose { index: usize, name: String },
+  Help { scroll: usize },
+  Menu { menu: Menu, selected: usize },
+}
+
+// Listas de opciones fijas de los comandos de Ctrl + K
+#[derive(Clone, Copy, PartialEq)]
+pub enum Menu {
+  Indent,
+  Case,
+  Lines,
+}
+
+impl Menu {
+  pub fn title(&self) -> &'static str {
+    match self {
+      Menu::Indent => "Indentacion",
+      Menu::Case => "Convertir nombres",
+      Menu::Lines => "Ordenar lineas",
+    }
+  }
+
+  pub fn items(&self) -> Vec<&'static str> {
+    match self {
+      Menu::Indent => vec![
+        "Espacios: 2",
+        "Espacios: 4",
+        "Espacios: 8",
+        "Tabs",
+        "Detectar desde el archivo",
+        "Reindentar el archivo con la indentacion actual",
+      ],
+      Menu::Case => CASES.iter().map(Case::name).collect(),
+      Menu::Lines => ORDERS.iter().map(LineOrder::name).collect(),
+    }
+  }
 }
 
 impl Popup {
@@ -27,14 +97,53 @@ impl Popup {
       "Ctrl + C -> Copiar",
       "Ctrl + X -> Cortar",
       "Ctrl + V -> Pegar",
+      "Ctrl + Y / Alt + Y -> Pegar lo ultimo borrado / lo borrado antes",
       "Ctrl + S -> Guardar archivo",
       "Ctrl + O -> Abrir archivo",
+      "Ctrl + W -> Cerrar archivo",
+      "Ctrl + B -> Lista de archivos abiertos",
+      "Ctrl + Tab / Ctrl + AvPag -> Archivo siguiente",
+      "Ctrl + Shift + Tab / Ctrl + RePag -> Archivo anterior",
+      "Ctrl + K, V / H -> Dividir panel lado a lado / apilado",
+      "Ctrl + K, X -> Cerrar panel",
+      "Ctrl + K, Flechas -> Cambiar de panel",
+      "Ctrl + K, + / - -> Agrandar / achicar panel",
+      "Ctrl + K, L -> Historial de mensajes",
+      "Ctrl + K, G -> Numeros de linea absolutos / relativos / ocultos",
+      "Ctrl + K, T -> Cambiar tema de colores",
+      "Ctrl + K, I -> Indentacion del archivo / reindentar",
+      "Ctrl + K, C -> Convertir palabra o seleccion (camelCase, snake_case...)",
+      "Alt + C -> Rotar camelCase / PascalCase / snake_case / SCREAMING / kebab",
+      "Tab -> Completar ruta (Guardar/Abrir)",
+      "Ctrl + N -> Nueva carpeta (Guardar)",
+      "F2 / Supr / Ctrl + D -> Renombrar / Borrar / Duplicar",
       "Shift + Flechas -> Seleccionar",
+      "Ctrl + A / Ctrl + L -> Seleccionar todo / la linea (repetido, suma lineas)",
+      "Ctrl + K, W / Ctrl + K, B -> Seleccionar la palabra / entre parentesis o comillas",
+      "Alt + = / Alt + - -> Agrandar / achicar la seleccion por pasos",
+      "Ctrl + ] (o Ctrl + 5) / Ctrl + K, M -> Ir al parentesis que corresponde",
       "Ctrl + Flechas -> Mover por palabra",
+      "Alt + Izq / Der -> Mover por subpalabra (camelCase, snake_case)",
+      "Alt + Shift + Izq / Der -> Seleccionar por subpalabra",
+      "Alt + Shift + Arriba / Abajo -> Seleccion en bloque (despues Izq / Der la ensancha)",
+      "Alt + Backspace / Alt + Supr -> Borrar subpalabra",
+      "Supr -> Borrar el caracter siguiente",
+      "Ctrl + Backspace / Ctrl + Supr -> Borrar palabra",
+      "Ctrl + K, Backspace / Supr -> Borrar hasta el principio / final de la linea",
+      "Ctrl + K, K -> Borrar linea",
+      "Ctrl + K, D -> Duplicar linea o seleccion",
+      "Ctrl + K, J -> Unir la linea siguiente (o las seleccionadas)",
+      "Ctrl + K, O -> Ordenar / invertir / quitar repetidas / mezclar lineas",
+      "Alt + Arriba / Abajo -> Mover lineas",
+      "Ctrl + Alt + Arriba / Abajo -> Agregar cursor arriba / abajo",
+      "Ctrl + D -> Seleccionar la siguiente aparicion (agrega un cursor)",
+      "Ctrl + Shift + L / Ctrl + K, A -> Un cursor en cada aparicion",
+      "Esc -> Volver a un solo cursor",
       "Inicio/Home -> Mover al principio de la linea",
       "Fin/End -> Mover al final de la linea",
       "Ctrl + Inicio/Home -> Mover al principio del documento",
       "Ctrl + Fin/End -> Mover al final del documento",
+      "Tab / Shift + Tab -> Indentar / desindentar lineas",
       "Esc -> Cerrar ayuda",
     ]
     .into_iter()
@@ -51,50 +160,153 @@ impl Popup {
       width: box_width,
       height: box_height,
       selected_line: None,
-      scroll: 0,
+      input_cursor: None,
+      preview: Vec::new(),
+      preview_from: 0,
     }
   }
 }
 
+const POPUP_WIDTH: usize = 50;
+
+// lineas de vista previa que se leen de cada archivo
+const PREVIEW_LINES: usize = 40;
+
 impl PopupMode {
-  pub fn to_popup(&self, input: &str) -> Popup {
+  pub fn buffers(docs: &[Document], selected: usize) -> PopupMode {
+    let names = docs.iter()
+      .enumerate()
+      .map(|(i, doc)| {
+        let modified = if doc.is_modified() { "*" } else { "" };
+        let path = doc.path.as_deref().unwrap_or("");
+        format!("{:>2} {}{}  {}", i + 1, doc.name(), modified, path)
+      })
+      .collect();
+
+    PopupMode::Buffers { selected, names }
+  }
+
+  // historial de mensajes, con el más reciente seleccionado
+  pub fn messages(log: &MessageLog) -> PopupMode {
+    let lines: Vec<String> = log.history().iter()
+      .map(|m| format!("[{}] {}  ({})", m.severity.label(), m.text, age(m.time)))
+      .collect();
+
+    PopupMode::Messages { selected: lines.len().saturating_sub(1), lines }
+  }
+
+  pub fn open(entries: Vec<String>, dir: &str) -> PopupMode {
+    let mut mode = PopupMode::Open { selected: 0, entries, scroll_y: 0, preview: Vec::new() };
+    mode.refresh_preview(dir);
+    mode
+  }
+
+  // relee la vista previa de la entrada seleccionada en el popup de Abrir
+  pub fn refresh_preview(&mut self, dir: &str) {
+    if let PopupMode::Open { selected, entries, preview, .. } = self {
+      *preview = match entries.get(*selected) {
+        Some(entry) => preview_entry(&Path::new(dir).join(entry), PREVIEW_LINES),
+        None => Vec::new(),
+      };
+    }
+  }
+
+  // filas visibles de la lista del popup con la terminal actual
+  pub fn list_rows(&self, layout: &Layout) -> usize {
+    match self {
+      PopupMode::Save { .. } | PopupMode::Open { .. } => popup_list_rows(layout.list_popup_height(), 2),
+      PopupMode::Help { .. } => popup_list_rows(Popup::help().height.min(layout.height), 0),
+      PopupMode::Menu { menu, .. } => popup_list_rows((menu.items().len() + 6).min(layout.list_popup_height()), 0),
+      _ => popup_list_rows(layout.list_popup_height(), 0),
+    }
+  }
+
+  pub fn to_popup(&self, input: &TextInput, dir: &str, layout: &Layout) -> Popup {
+    let width = POPUP_WIDTH.min(layout.width);
+    let height = layout.list_popup_height();
+    let rows = self.list_rows(layout);
+
+    // ancho util dentro del borde y el margen
+    let content_width = width.saturating_sub(4);
+
     match self {
-      PopupMode::Save { selected, entries, scroll_y } => {
-          let mut lines = vec![format!("Guardar como: {}", input)];
-          lines.push(format!("Directorio: ."));
+      PopupMode::Save { selected, entries, scroll_y, prompt } => {
+          // la primera fila es el campo "Guardar como:" o la pregunta pendiente
+          let (label, field_input) = match prompt {
+            None => ("Guardar como: ", Some(input)),
+            Some(SavePrompt::NewFolder(input)) => ("Nueva carpeta: ", Some(input)),
+            Some(SavePrompt::Rename { input, .. }) => ("Renombrar a: ", Some(input)),
+            Some(SavePrompt::Duplicate { input, .. }) => ("Duplicar como: ", Some(input)),
+            Some(SavePrompt::Overwrite(_)) | Some(SavePrompt::Delete(_)) => ("", None),
+          };
+
+          let mut input_cursor = None;
+          let first_line = match (prompt, field_input) {
+            (Some(SavePrompt::Overwrite(path)), _) => {
+              let name = Path::new(path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+              format!("Sobrescribir {}? (s/n)", name)
+            }
+            (Some(SavePrompt::Delete(entry)), _) => format!("Mover {} a la papelera? (s/n)", entry),
+            (_, Some(field_input)) => {
+              let (field, field_cursor) = field_input.visible(content_width.saturating_sub(label.len()));
+              input_cursor = Some(label.len() + field_cursor);
+              format!("{}{}", label, field)
+            }
+            (_, None) => String::new(),
+          };
 
-          let max_visible = 5;
+          let mut lines = vec![first_line];
+          lines.push(format!("Directorio: {}", shorten_path(dir, content_width.saturating_sub(12))));
+
+          // si la terminal se achicó, la entrada seleccionada tiene que seguir a la vista
+          let scroll = selected.map_or(*scroll_y, |i| scroll_to_show(*scroll_y, i, rows));
           let visible_entries = entries.iter()
-              .skip(*scroll_y)
-              .take(max_visible)
+              .skip(scroll)
+              .take(rows)
               .cloned();
 
           lines.extend(visible_entries);
 
           // None => fila "Guardar como:" (indice 0), Some(i) => entrada i (+2 por cabeceras)
-          let selected_line = match selected {
-              None => Some(0),
-              Some(i) => Some(i.saturating_sub(*scroll_y) + 2),
+          let selected_line = match (prompt, selected) {
+              (Some(_), _) | (None, None) => Some(0),
+              (None, Some(i)) => Some(i.saturating_sub(scroll) + 2),
+          };
+
+          let footer = match (prompt, selected) {
+              (Some(SavePrompt::Overwrite(_)) | Some(SavePrompt::Delete(_)), _) => "S = Si   N = No",
+              (Some(_), _) => "Enter = Confirmar   Esc = Cancelar",
+              (None, None) => "Enter Guardar  Tab Completar  ^N Carpeta",
+              (None, Some(_)) => "F2 Renombrar  Supr Borrar  ^D Duplicar",
           };
 
           Popup {
               title: "Guardar archivo".to_string(),
               lines,
-              footer: "Enter = Confirmar   Esc = Cancelar".to_string(),
-              width: 50,
-              height: 12,
+              footer: footer.to_string(),
+              width,
+              height,
               selected_line,
-              scroll: *scroll_y,
+              input_cursor,
+              preview: Vec::new(),
+              preview_from: 0,
           }
       }
 
-      PopupMode::Open { selected, entries, scroll_y } => {
-          let mut lines = vec![format!("Directorio: {}", input)];
+      PopupMode::Open { selected, entries, scroll_y, preview } => {
+          // usa casi todo el ancho de la terminal para dejar lugar a la vista previa
+          let width = layout.width.saturating_sub(4).max(POPUP_WIDTH).min(layout.width);
+          let content_width = width.saturating_sub(4);
+
+          let label = "Abrir: ";
+          let (field, field_cursor) = input.visible(content_width.saturating_sub(label.len()));
+          let mut lines = vec![format!("{}{}", label, field)];
+          lines.push(format!("Directorio: {}", shorten_path(dir, content_width.saturating_sub(12))));
           
-          let max_visible = 6;
+          let scroll = scroll_to_show(*scroll_y, *selected, rows);
           let visible_entries = entries.iter()
-              .skip(*scroll_y)
-              .take(max_visible)
+              .skip(scroll)
+              .take(rows)
               .cloned();
 
           lines.extend(visible_entries);
@@ -102,98 +314,244 @@ impl PopupMode {
           Popup {
               title: "Abrir archivo".to_string(),
               lines,
-              footer: format!("{} de {} - Esc = Salir", *selected + 1, entries.len()),
-              width: 50,
-              height: 12,
-              selected_line: Some(selected.saturating_sub(*scroll_y) + 1),
-              scroll: *scroll_y,
+              footer: format!("{} de {} - Tab = Completar - Esc = Salir", *selected + 1, entries.len()),
+              width,
+              height,
+              selected_line: Some(selected.saturating_sub(scroll) + 2),
+              input_cursor: Some(label.len() + field_cursor),
+              preview: preview.clone(),
+              preview_from: 2,
+          }
+      }
+
+      PopupMode::Buffers { selected, names } => {
+          let scroll = (selected + 1).saturating_sub(rows);
+
+          Popup {
+              title: "Archivos abiertos".to_string(),
+              lines: names.iter().skip(scroll).take(rows).cloned().collect(),
+              footer: "Enter Ir   ^W Cerrar   Esc Salir".to_string(),
+              width,
+              height,
+              selected_line: Some(selected - scroll),
+              input_cursor: None,
+              preview: Vec::new(),
+              preview_from: 0,
           }
       }
 
-      PopupMode::Help => {
+      PopupMode::Messages { selected, lines } => {
+          let scroll = (selected + 1).saturating_sub(rows);
+          let lines = if lines.is_empty() {
+            vec!["No hay mensajes".to_string()]
+          } else {
+            lines.iter().skip(scroll).take(rows).cloned().collect()
+          };
+
+          Popup {
+              title: "Mensajes".to_string(),
+              lines,
+              footer: "Esc Salir".to_string(),
+              width: layout.width.saturating_sub(4).max(POPUP_WIDTH).min(layout.width),
+              height,
+              selected_line: Some(selected - scroll),
+              input_cursor: None,
+              preview: Vec::new(),
+              preview_from: 0,
+          }
+      }
+
+      PopupMode::Menu { menu, selected } => {
+          let items = menu.items();
+          let scroll = (selected + 1).saturating_sub(rows);
+
+          Popup {
+              title: menu.title().to_string(),
+              lines: items.iter().skip(scroll).take(rows).map(|item| item.to_string()).collect(),
+              footer: "Enter Elegir   Esc Salir".to_string(),
+              width,
+              height: (items.len() + 6).min(height),
+              selected_line: Some(selected - scroll),
+              input_cursor: None,
+              preview: Vec::new(),
+              preview_from: 0,
+          }
+      }
+
+      PopupMode::ConfirmClose { name, .. } => {
+          Popup {
+              title: "Cerrar archivo".to_string(),
+              lines: vec![
+                format!("{} tiene cambios sin guardar.", name),
+                "Cerrar igual?".to_string(),
+              ],
+              footer: "S = Si   N = No".to_string(),
+              width,
+              height: 7,
+              selected_line: None,
+              input_cursor: None,
+              preview: Vec::new(),
+              preview_from: 0,
+          }
+      }
+
+      PopupMode::Help { scroll } => {
           let mut p = Popup::help();
           p.selected_line = None;
+
+          // si no entra en la pantalla se desplaza con las flechas
+          let total = p.lines.len();
+          if total > rows {
+            let scroll = (*scroll).min(total - rows);
+            p.lines = p.lines.into_iter().skip(scroll).take(rows).collect();
+            p.footer = format!("{}-{} de {}  Flechas = Desplazar", scroll + 1, scroll + rows, total);
+          }
           p
       }
     }
   }
 }
 
+// Recorta una ruta larga por la izquierda: "...proyecto/src"
+fn shorten_path(path: &str, max: usize) -> String {
+  let len = path.chars().count();
+  if len <= max {
+    return path.to_string();
+  }
+
+  let tail: String = path.chars().skip(len - max.saturating_sub(3)).collect();
+  format!("...{}", tail)
+}
 
+// Devuelve la posicion en pantalla del cursor del campo de texto, si el popup tiene uno
 pub fn draw_popup(
   popup: &Popup,
-  term_width: usize,
-  term_height: usize,
-) -> std::io::Result<()> {
-  let start_x = (term_width.saturating_sub(popup.width)) / 2;
-  let start_y = (term_height.saturating_sub(popup.height)) / 2;
+  layout: &Layout,
+  theme: &Theme,
+) -> std::io::Result<Option<(u16, u16)>> {
+  // recortado a la pantalla; si ni el borde entra no se dibuja
+  let area = layout.popup(popup.width, popup.height);
+  if area.width < 4 || area.height < 3 {
+    return Ok(None);
+  }
 
-  let inner_width = popup.width - 2;
+  let start_x = area.x;
+  let start_y = area.y;
+  let inner_width = area.width - 2;
 
   // fondo + laterales
-  for y in 0..popup.height {
-    execute!(
-      stdout(),
-      MoveTo(start_x as u16, (start_y + y) as u16),
-      SetForegroundColor(Color::DarkRed)
-    )?;
+  for y in 0..area.height {
+    execute!(stdout(), MoveTo(start_x as u16, (start_y + y) as u16))?;
+    theme.border.apply()?;
     print!("|");
-    execute!(stdout(), ResetColor)?;
+    reset()?;
     print!("{}", " ".repeat(inner_width));
-    execute!(stdout(), SetForegroundColor(Color::DarkRed))?;
+    theme.border.apply()?;
     print!("|");
-    execute!(stdout(), ResetColor)?;
+    reset()?;
   }
 
   // borde superior
-  execute!(
-    stdout(),
-    MoveTo(start_x as u16, start_y as u16),
-    SetForegroundColor(Color::DarkRed)
-  )?;
+  execute!(stdout(), MoveTo(start_x as u16, start_y as u16))?;
+  theme.border.apply()?;
   print!("┌{}┐", "─".repeat(inner_width));
 
   // borde inferior
   execute!(
     stdout(),
-    MoveTo(start_x as u16, (start_y + popup.height - 1) as u16)
+    MoveTo(start_x as u16, (start_y + area.height - 1) as u16)
   )?;
   print!("└{}┘", "─".repeat(inner_width));
-  execute!(stdout(), ResetColor)?;
+  reset()?;
+
+  // filas entre el título y el footer
+  let content_start_y = start_y + 3; // título + espacio
+  let footer_y = start_y + area.height - 2;
+  let content_rows = footer_y.saturating_sub(content_start_y);
 
   // === TÍTULO (centrado) ===
-  let title_x = start_x + 1 + (inner_width.saturating_sub(popup.title.len())) / 2;
-  execute!(
-    stdout(),
-    MoveTo(title_x as u16, (start_y + 1) as u16)
-  )?;
-  print!("{}", popup.title);
+  if area.height > 3 {
+    let title_x = start_x + 1 + (inner_width.saturating_sub(popup.title.len())) / 2;
+    execute!(
+      stdout(),
+      MoveTo(title_x as u16, (start_y + 1) as u16)
+    )?;
+    print!("{}", fit(&popup.title, inner_width).trim_end());
+  }
+
+  // con vista previa la lista ocupa la columna izquierda y el panel la derecha
+  let content_width = inner_width.saturating_sub(2);
+  let list_width = if popup.preview.is_empty() {
+    content_width
+  } else {
+    (content_width * 2 / 5).max(20).min(content_width)
+  };
 
   // === LÍNEAS ===
-  let content_start_y = start_y + 3; // título + espacio
-  for (i, line) in popup.lines.iter().enumerate() {
+  for (i, line) in popup.lines.iter().enumerate().take(content_rows) {
     execute!(
       stdout(),
       MoveTo((start_x + 2) as u16, (content_start_y + i) as u16)
     )?;
+
+    let width = if i >= popup.preview_from { list_width } else { content_width };
     
     if Some(i) == popup.selected_line {
-        execute!(stdout(), SetForegroundColor(Color::Black), SetBackgroundColor(Color::White))?;
-        print!("{:<width$}", line, width = inner_width - 2);
-        execute!(stdout(), ResetColor)?;
+        theme.popup_selected.apply()?;
+        print!("{}", fit(line, width));
+        reset()?;
     } else {
-        print!("{}", line);
+        print!("{}", fit(line, width).trim_end());
+    }
+  }
+
+  // === VISTA PREVIA ===
+  let sep_x = start_x + 2 + list_width + 1;
+  let preview_x = sep_x + 2;
+  let preview_width = (start_x + area.width - 2).saturating_sub(preview_x);
+
+  if !popup.preview.is_empty() && preview_width > 0 {
+    let rows = content_rows.saturating_sub(popup.preview_from);
+
+    for row in 0..rows {
+      let y = content_start_y + popup.preview_from + row;
+      execute!(stdout(), MoveTo(sep_x as u16, y as u16))?;
+      theme.border.apply()?;
+      print!("│");
+      reset()?;
+      execute!(stdout(), MoveTo(preview_x as u16, y as u16))?;
+
+      if let Some(line) = popup.preview.get(row) {
+        print!("{}", fit(line, preview_width).trim_end());
+      }
     }
   }
 
   // === FOOTER (centrado) ===
-  let footer_y = start_y + popup.height - 2;
-  let footer_x = start_x + 1 + (inner_width.saturating_sub(popup.footer.len())) / 2;
-  execute!(
-    stdout(),
-    MoveTo(footer_x as u16, footer_y as u16)
-  )?;
-  print!("{}", popup.footer);
+  if footer_y > start_y + 1 {
+    let footer_x = start_x + 1 + (inner_width.saturating_sub(popup.footer.len())) / 2;
+    execute!(
+      stdout(),
+      MoveTo(footer_x as u16, footer_y as u16)
+    )?;
+    print!("{}", fit(&popup.footer, inner_width).trim_end());
+  }
 
-  Ok(())
-}
\ No newline at end of file
+  if content_rows == 0 {
+    return Ok(None);
+  }
+
+  let cursor = popup.input_cursor.map(|col| {
+    ((start_x + 2 + col.min(content_width)) as u16, content_start_y as u16)
+  });
+
+  Ok(cursor)
+}
+
+// Recorta o rellena con espacios una linea para que ocupe exactamente `width` columnas
+pub fn fit(line: &str, width: usize) -> String {
+  let mut out: String = line.chars().take(width).collect();
+  let len = out.chars().count();
+  out.push_str(&" ".repeat(width - len));
+  out
+}
@@ -0,0 +1,370 @@
+use crate::CursorPos;
+use crate::document::Document;
+use crate::selection::{ Selection, line_range };
+use crate::utils::line_len_chars;
+use crate::word::word_at;
+
+// Comandos de selección: todo, palabra, linea, entre paréntesis o comillas, y
+// agrandar / achicar por pasos (palabra -> contenido del string -> contenido de los
+// paréntesis -> bloque indentado -> párrafo -> documento). Achicar vuelve por los
+// mismos pasos gracias a una pila con las selecciones anteriores.
+
+const PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+const QUOTES: [char; 3] = ['"', '\'', '`'];
+
+// Selecciones por las que pasó Alt + = (por documento)
+#[derive(Default)]
+pub struct Expansion {
+  history: Vec<Selection>, // las anteriores, la más reciente al final
+  last: Option<Selection>, // la que dejó el último paso: si cambió, se empieza de nuevo
+}
+
+type Range = (CursorPos, CursorPos);
+
+// Ctrl + A
+pub fn select_all(lines: &[String], cursor: &mut CursorPos, selection: &mut Option<CursorPos>) {
+  let last = lines.len() - 1;
+  *selection = Some(CursorPos { x: 0, y: 0 });
+  *cursor = CursorPos { x: line_len_chars(&lines[last]), y: last };
+}
+
+// Ctrl + K, W: la palabra del cursor. Devuelve false si no hay ninguna.
+pub fn select_word(lines: &[String], cursor: &mut CursorPos, selection: &mut Option<CursorPos>, word_chars: &[char]) -> bool {
+  let chars: Vec<char> = lines[cursor.y].chars().collect();
+  let Some((from, to)) = word_at(&chars, cursor.x, word_chars) else {
+    return false;
+  };
+  *selection = Some(CursorPos { x: from, y: cursor.y });
+  cursor.x = to;
+  true
+}
+
+// Ctrl + L: la linea entera con su salto; repetido, suma la linea siguiente
+pub fn select_line(lines: &[String], cursor: &mut CursorPos, selection: &mut Option<CursorPos>) {
+  let (first, mut last) = line_range(cursor, selection);
+  let whole = *selection == Some(CursorPos { x: 0, y: first }) && *cursor == line_end(lines, last);
+  if whole && last + 1 < lines.len() {
+    last += 1;
+  }
+  *selection = Some(CursorPos { x: 0, y: first });
+  *cursor = line_end(lines, last);
+}
+
+// principio de la linea siguiente, o el final de la última
+fn line_end(lines: &[String], y: usize) -> CursorPos {
+  if y + 1 < lines.len() {
+    CursorPos { x: 0, y: y + 1 }
+  } else {
+    CursorPos { x: line_len_chars(&lines[y]), y }
+  }
+}
+
+// Ctrl + K, B: el contenido de los paréntesis o comillas que rodean la selección; si ya
+// estaba seleccionado, con los paréntesis o comillas incluidos. Devuelve false si no hay.
+pub fn select_brackets(lines: &[String], cursor: &mut CursorPos, selection: &mut Option<CursorPos>) -> bool {
+  let current = Selection { cursor: *cursor, start: *selection }.range();
+  let mut candidates = Vec::new();
+  push_pair(&mut candidates, enclosing_quotes(lines, current));
+  push_pair(&mut candidates, enclosing_brackets(lines, current));
+  apply(lines, cursor, selection, current, candidates)
+}
+
+// Alt + =: el siguiente paso más grande que la selección actual
+pub fn expand(doc: &mut Document, word_chars: &[char]) -> bool {
+  let before = Selection { cursor: doc.cursor, start: doc.selection_start };
+  if doc.expansion.last != Some(before) {
+    doc.expansion.history.clear();
+  }
+
+  let current = before.range();
+  let lines = &doc.lines;
+  let mut candidates = Vec::new();
+  let chars: Vec<char> = lines[current.0.y].chars().collect();
+  if current.0.y == current.1.y
+    && let Some((from, to)) = word_at(&chars, current.0.x, word_chars)
+  {
+    candidates.push((CursorPos { x: from, y: current.0.y }, CursorPos { x: to, y: current.0.y }));
+  }
+  push_pair(&mut candidates, enclosing_quotes(lines, current));
+  push_pair(&mut candidates, enclosing_brackets(lines, current));
+  candidates.push(indented_block(lines, current));
+  candidates.push(paragraph(lines, current));
+  candidates.push((CursorPos { x: 0, y: 0 }, line_end(lines, lines.len() - 1)));
+
+  if !apply(&doc.lines, &mut doc.cursor, &mut doc.selection_start, current, candidates) {
+    return false;
+  }
+  doc.expansion.history.push(before);
+  doc.expansion.last = Some(Selection { cursor: doc.cursor, start: doc.selection_start });
+  true
+}
+
+// Alt + -: vuelve a la selección de antes del último Alt + =
+pub fn shrink(doc: &mut Document) -> bool {
+  let current = Selection { cursor: doc.cursor, start: doc.selection_start };
+  if doc.expansion.last != Some(current) {
+    doc.expansion.history.clear();
+    return false;
+  }
+  let Some(previous) = doc.expansion.history.pop() else {
+    return false;
+  };
+
+  doc.cursor = previous.cursor;
+  doc.selection_start = previous.start;
+  doc.expansion.last = Some(previous);
+  true
+}
+
+// Selecciona el candidato más chico que contenga a `current` y sea más grande
+fn apply(lines: &[String], cursor: &mut CursorPos, selection: &mut Option<CursorPos>, current: Range, candidates: Vec<Range>) -> bool {
+  let (from, to) = (offset(lines, current.0), offset(lines, current.1));
+  let best = candidates.into_iter()
+    .map(|range| (offset(lines, range.0), offset(lines, range.1), range))
+    .filter(|&(a, b, _)| a <= from && to <= b && b - a > to - from)
+    .min_by_key(|&(a, b, _)| b - a);
+
+  let Some((_, _, (start, end))) = best else {
+    return false;
+  };
+  *selection = Some(start);
+  *cursor = end;
+  true
+}
+
+// contenido y contenido con los delimitadores, a partir de las posiciones de los delimitadores
+fn push_pair(candidates: &mut Vec<Range>, pair: Option<Range>) {
+  if let Some((open, close)) = pair {
+    candidates.push((CursorPos { x: open.x + 1, y: open.y }, close));
+    candidates.push((open, CursorPos { x: close.x + 1, y: close.y }));
+  }
+}
+
+// posición en chars desde el principio del documento
+fn offset(lines: &[String], pos: CursorPos) -> usize {
+  lines[..pos.y].iter().map(|l| line_len_chars(l) + 1).sum::<usize>() + pos.x
+}
+
+// Comillas de la misma linea que encierran la selección: (abre, cierra)
+fn enclosing_quotes(lines: &[String], (from, to): Range) -> Option<Range> {
+  if from.y != to.y {
+    return None;
+  }
+
+  let chars: Vec<char> = lines[from.y].chars().collect();
+  let mut open: Option<usize> = None;
+  let mut i = 0;
+  while i < chars.len() {
+    match open {
+      Some(_) if chars[i] == '\\' => i += 1,
+      Some(start) if chars[i] == chars[start] => {
+        if start < from.x && to.x <= i {
+          return Some((CursorPos { x: start, y: from.y }, CursorPos { x: i, y: from.y }));
+        }
+        open = None;
+      }
+      None if QUOTES.contains(&chars[i]) => open = Some(i),
+      _ => {}
+    }
+    i += 1;
+  }
+  None
+}
+
+// Paréntesis, corchetes o llaves más cercanos que encierran la selección: (abre, cierra)
+fn enclosing_brackets(lines: &[String], (from, to): Range) -> Option<Range> {
+  let mut depth = [0usize; 3];
+  let mut y = from.y;
+  let mut chars: Vec<char> = lines[y].chars().collect();
+  let mut x = from.x.min(chars.len());
+
+  loop {
+    while x == 0 {
+      y = y.checked_sub(1)?;
+      chars = lines[y].chars().collect();
+      x = chars.len() + 1;
+    }
+    x -= 1;
+    let Some(&c) = chars.get(x) else { continue };
+
+    if let Some(kind) = PAIRS.iter().position(|&(_, close)| close == c) {
+      depth[kind] += 1;
+    } else if let Some(kind) = PAIRS.iter().position(|&(open, _)| open == c) {
+      if depth[kind] > 0 {
+        depth[kind] -= 1;
+        continue;
+      }
+      let open = CursorPos { x, y };
+      if let Some(close) = matching_close(lines, open, PAIRS[kind])
+        && offset(lines, close) >= offset(lines, to)
+      {
+        return Some((open, close));
+      }
+    }
+  }
+}
+
+// cierre que corresponde a la apertura en `open`, contando solo los del mismo tipo
+fn matching_close(lines: &[String], open: CursorPos, (open_char, close_char): (char, char)) -> Option<CursorPos> {
+  let mut depth = 0;
+  for (y, line) in lines.iter().enumerate().skip(open.y) {
+    let skip = if y == open.y { open.x } else { 0 };
+    for (x, c) in line.chars().enumerate().skip(skip) {
+      if c == open_char {
+        depth += 1;
+      } else if c == close_char {
+        depth -= 1;
+        if depth == 0 {
+          return Some(CursorPos { x, y });
+        }
+      }
+    }
+  }
+  None
+}
+
+// Lineas enteras alrededor de la selección con al menos su indentación (las vacías
+// del medio también)
+fn indented_block(lines: &[String], current: Range) -> Range {
+  let indent_of = |line: &str| line.chars().take_while(|c| c.is_whitespace()).count();
+  let is_blank = |line: &str| line.trim().is_empty();
+  let level = (current.0.y..=current.1.y)
+    .filter(|&y| !is_blank(&lines[y]))
+    .map(|y| indent_of(&lines[y]))
+    .min()
+    .unwrap_or(0);
+  let inside = |y: usize| is_blank(&lines[y]) || indent_of(&lines[y]) >= level;
+
+  let mut first = current.0.y;
+  while first > 0 && inside(first - 1) {
+    first -= 1;
+  }
+  let mut last = current.1.y;
+  while last + 1 < lines.len() && inside(last + 1) {
+    last += 1;
+  }
+  // sin las vacías de los bordes
+  while first < last && is_blank(&lines[first]) {
+    first += 1;
+  }
+  while last > first && is_blank(&lines[last]) {
+    last -= 1;
+  }
+  (CursorPos { x: 0, y: first }, line_end(lines, last))
+}
+
+// Lineas no vacías seguidas alrededor de la selección
+fn paragraph(lines: &[String], current: Range) -> Range {
+  let filled = |y: usize| !lines[y].trim().is_empty();
+  let mut first = current.0.y;
+  while first > 0 && filled(first - 1) {
+    first -= 1;
+  }
+  let mut last = current.1.y;
+  while last + 1 < lines.len() && filled(last + 1) {
+    last += 1;
+  }
+  (CursorPos { x: 0, y: first }, line_end(lines, last))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::selection::get_selected_text;
+
+  fn doc(lines: &[&str], x: usize, y: usize) -> Document {
+    let mut doc = Document::new();
+    doc.lines = lines.iter().map(|l| l.to_string()).collect();
+    doc.cursor = CursorPos { x, y };
+    doc
+  }
+
+  fn selected(doc: &Document) -> String {
+    get_selected_text(&doc.lines, &doc.cursor, &doc.selection_start).unwrap_or_default()
+  }
+
+  fn pos(x: usize, y: usize) -> CursorPos {
+    CursorPos { x, y }
+  }
+
+  fn range((open, close): Range) -> ((usize, usize), (usize, usize)) {
+    ((open.x, open.y), (close.x, close.y))
+  }
+
+  #[test]
+  fn quotes_around_the_cursor() {
+    let lines = vec![r#"a = "x \" y" + 'z'"#.to_string()];
+    assert_eq!(enclosing_quotes(&lines, (pos(6, 0), pos(6, 0))).map(range), Some(((4, 0), (11, 0))));
+    assert_eq!(enclosing_quotes(&lines, (pos(16, 0), pos(17, 0))).map(range), Some(((15, 0), (17, 0))));
+    // entre dos strings no hay comillas que encierren
+    assert_eq!(enclosing_quotes(&lines, (pos(13, 0), pos(13, 0))).map(range), None);
+  }
+
+  #[test]
+  fn brackets_around_the_cursor() {
+    let lines: Vec<String> = ["f(a[1], {", "  b)", "}"].iter().map(|l| l.to_string()).collect();
+    assert_eq!(enclosing_brackets(&lines, (pos(4, 0), pos(4, 0))).map(range), Some(((3, 0), (5, 0))));
+    // el ')' de otro tipo no cierra la llave
+    assert_eq!(enclosing_brackets(&lines, (pos(2, 1), pos(2, 1))).map(range), Some(((8, 0), (0, 2))));
+    assert_eq!(enclosing_brackets(&lines, (pos(6, 0), pos(6, 0))).map(range), Some(((1, 0), (3, 1))));
+    assert_eq!(enclosing_brackets(&lines, (pos(1, 2), pos(1, 2))).map(range), None);
+  }
+
+  #[test]
+  fn select_line_grows_downwards() {
+    let lines: Vec<String> = ["a", "b", "c"].iter().map(|l| l.to_string()).collect();
+    let (mut cursor, mut selection) = (pos(1, 1), None);
+    select_line(&lines, &mut cursor, &mut selection);
+    assert_eq!(get_selected_text(&lines, &cursor, &selection).as_deref(), Some("b\n"));
+    select_line(&lines, &mut cursor, &mut selection);
+    select_line(&lines, &mut cursor, &mut selection);
+    assert_eq!(get_selected_text(&lines, &cursor, &selection).as_deref(), Some("b\nc"));
+  }
+
+  #[test]
+  fn select_brackets_then_with_delimiters() {
+    let lines = vec!["f(x, [y])".to_string()];
+    let (mut cursor, mut selection) = (pos(6, 0), None);
+    let mut steps = Vec::new();
+    while select_brackets(&lines, &mut cursor, &mut selection) {
+      steps.push(get_selected_text(&lines, &cursor, &selection).unwrap_or_default());
+    }
+    assert_eq!(steps, ["y", "[y]", "x, [y]", "(x, [y])"]);
+  }
+
+  #[test]
+  fn expand_and_shrink_by_steps() {
+    let mut doc = doc(&["fn main() {", "  let s = foo(\"a b\", x);", "  y();", "}"], 15, 1);
+    let mut steps = Vec::new();
+    while expand(&mut doc, &[]) {
+      steps.push(selected(&doc));
+    }
+    assert_eq!(steps, [
+      "a",
+      "a b",
+      "\"a b\"",
+      "\"a b\", x",
+      "(\"a b\", x)",
+      "  let s = foo(\"a b\", x);\n  y();\n",
+      "\n  let s = foo(\"a b\", x);\n  y();\n",
+      "{\n  let s = foo(\"a b\", x);\n  y();\n}",
+      "fn main() {\n  let s = foo(\"a b\", x);\n  y();\n}",
+    ]);
+
+    for expected in steps.iter().rev().skip(1) {
+      assert!(shrink(&mut doc));
+      assert_eq!(&selected(&doc), expected);
+    }
+    assert!(shrink(&mut doc));
+    assert_eq!((doc.cursor.x, doc.selection_start.is_none()), (15, true));
+    assert!(!shrink(&mut doc));
+  }
+
+  #[test]
+  fn moving_the_cursor_forgets_the_steps() {
+    let mut doc = doc(&["foo bar"], 1, 0);
+    assert!(expand(&mut doc, &[]));
+    doc.cursor.x = 2;
+    assert!(!shrink(&mut doc));
+  }
+}
@@ -1,6 +1,56 @@
 use crate::CursorPos;
 use crate::char_to_byte_idx;
 
+// Un cursor con su selección: el documento tiene el principal en cursor /
+// selection_start y los demás (varios cursores) como Selection
+#[derive(Clone, Copy, PartialEq)]
+pub struct Selection {
+  pub cursor: CursorPos,
+  pub start: Option<CursorPos>,
+}
+
+impl Selection {
+  // principio y fin en el orden del documento
+  pub fn range(&self) -> (CursorPos, CursorPos) {
+    let start = self.start.unwrap_or(self.cursor);
+    if (start.y, start.x) <= (self.cursor.y, self.cursor.x) { (start, self.cursor) } else { (self.cursor, start) }
+  }
+}
+
+// Ordena las selecciones y junta las que se pisan (o los cursores en el mismo lugar).
+// `primary` es el índice de la principal; devuelve su nuevo índice.
+pub fn merge_selections(selections: &mut Vec<Selection>, primary: usize) -> usize {
+  let primary_range = selections[primary].range();
+  selections.sort_by_key(|s| { let (a, _) = s.range(); (a.y, a.x) });
+
+  let mut merged: Vec<Selection> = Vec::with_capacity(selections.len());
+  let mut new_primary = 0;
+  for sel in selections.drain(..) {
+    let (a, b) = sel.range();
+    let is_primary = sel.range() == primary_range;
+    match merged.last_mut() {
+      Some(last) if overlaps(last.range(), (a, b)) => {
+        let (la, lb) = last.range();
+        let end = if (b.y, b.x) > (lb.y, lb.x) { b } else { lb };
+        *last = Selection { cursor: end, start: if la == end { None } else { Some(la) } };
+      }
+      _ => merged.push(sel),
+    }
+    if is_primary {
+      new_primary = merged.len() - 1;
+    }
+  }
+
+  *selections = merged;
+  new_primary
+}
+
+// se pisan si comparten algún caracter; dos cursores sin selección, si están en el mismo lugar
+fn overlaps((a1, b1): (CursorPos, CursorPos), (a2, b2): (CursorPos, CursorPos)) -> bool {
+  let key = |p: CursorPos| (p.y, p.x);
+  key(a2) < key(b1) || key(a2) == key(a1) || (key(a2) == key(b1) && (a1 == b1 || a2 == b2))
+}
+
 pub fn start_selection_if_needed(selection: &mut Option<CursorPos>, cursor: CursorPos) { 
   if selection.is_none() {
     *selection = Some(cursor);
@@ -27,6 +77,20 @@ pub fn is_selected(pos: CursorPos, start: CursorPos, end: CursorPos) -> bool {
   true
 }
 
+// Lineas afectadas: las de la selección (sin la última si la selección termina en su
+// columna 0) o la del cursor
+pub fn line_range(cursor: &CursorPos, selection: &Option<CursorPos>) -> (usize, usize) {
+  let Some(start) = selection else {
+    return (cursor.y, cursor.y);
+  };
+  let (first, last) = if start.y <= cursor.y { (*start, *cursor) } else { (*cursor, *start) };
+  if last.y > first.y && last.x == 0 {
+    (first.y, last.y - 1)
+  } else {
+    (first.y, last.y)
+  }
+}
+
 pub fn has_selection(selection: &Option<CursorPos>, cursor: &CursorPos) -> bool {
   if let Some(start) = selection {
     start.x != cursor.x || start.y != cursor.y
@@ -71,7 +135,14 @@ pub fn delete_selection(lines: &mut Vec<String>, cursor: &mut CursorPos, selecti
   *selection = None;
 }
 
-pub fn get_selected_text(lines: &Vec<String>, cursor: &CursorPos, selection: &Option<CursorPos>) -> Option<String> {
+// Borra la selección y devuelve el texto borrado (None si no había nada)
+pub fn take_selection(lines: &mut Vec<String>, cursor: &mut CursorPos, selection: &mut Option<CursorPos>) -> Option<String> {
+  let text = get_selected_text(lines, cursor, selection).filter(|text| !text.is_empty());
+  delete_selection(lines, cursor, selection);
+  text
+}
+
+pub fn get_selected_text(lines: &[String], cursor: &CursorPos, selection: &Option<CursorPos>) -> Option<String> {
   let Some(start) = selection else { return None };
   let mut start = *start;
   let mut end = *cursor;
@@ -97,8 +168,8 @@ pub fn get_selected_text(lines: &Vec<String>, cursor: &CursorPos, selection: &Op
     }
 
     // líneas intermedias
-    for y in (start.y + 1)..end.y {
-      result.push_str(&lines[y]);
+    for line in &lines[(start.y + 1)..end.y] {
+      result.push_str(line);
       result.push('\n');
     }
 
@@ -130,8 +201,8 @@ pub fn paste_text(lines: &mut Vec<String>, cursor: &mut CursorPos, selection: &m
     let tail = lines[cursor.y].split_off(byte_idx);
     lines[cursor.y].push_str(parts[0]);
 
-    for i in 1..parts.len() {
-      lines.insert(cursor.y + i, parts[i].to_string());
+    for (i, part) in parts.iter().enumerate().skip(1) {
+      lines.insert(cursor.y + i, part.to_string());
     }
 
     let last = cursor.y + parts.len() - 1;
@@ -142,4 +213,59 @@ pub fn paste_text(lines: &mut Vec<String>, cursor: &mut CursorPos, selection: &m
   }
 
   *selection = None;
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn sel(start: Option<(usize, usize)>, cursor: (usize, usize)) -> Selection {
+    let pos = |(x, y)| CursorPos { x, y };
+    Selection { cursor: pos(cursor), start: start.map(pos) }
+  }
+
+  // ((desde x, y), (hasta x, y)) de cada selección
+  fn ranges(selections: &[Selection]) -> Vec<((usize, usize), (usize, usize))> {
+    selections.iter()
+      .map(|s| { let (a, b) = s.range(); ((a.x, a.y), (b.x, b.y)) })
+      .collect()
+  }
+
+  #[test]
+  fn merge_sorts_and_joins_overlapping() {
+    let mut selections = vec![
+      sel(Some((5, 0)), (2, 0)),
+      sel(Some((0, 1)), (3, 1)),
+      sel(Some((0, 0)), (3, 0)),
+      sel(None, (2, 1)),
+    ];
+    let primary = merge_selections(&mut selections, 3);
+    assert_eq!(ranges(&selections), [((0, 0), (5, 0)), ((0, 1), (3, 1))]);
+    assert_eq!(primary, 1);
+  }
+
+  #[test]
+  fn merge_keeps_touching_selections_apart() {
+    let mut selections = vec![sel(Some((0, 0)), (2, 0)), sel(Some((2, 0)), (4, 0))];
+    assert_eq!(merge_selections(&mut selections, 1), 1);
+    assert_eq!(selections.len(), 2);
+
+    // pero dos cursores en el mismo lugar son uno solo
+    let mut selections = vec![sel(None, (2, 0)), sel(None, (2, 0)), sel(Some((0, 0)), (2, 0))];
+    merge_selections(&mut selections, 0);
+    assert_eq!(ranges(&selections), [((0, 0), (2, 0))]);
+  }
+
+  #[test]
+  fn selected_text_and_paste() {
+    let mut lines = vec!["uno".to_string(), "dos".to_string()];
+    let mut cursor = CursorPos { x: 1, y: 1 };
+    let mut selection = Some(CursorPos { x: 2, y: 0 });
+    assert_eq!(get_selected_text(&lines, &cursor, &selection).as_deref(), Some("o\nd"));
+
+    paste_text(&mut lines, &mut cursor, &mut selection, "X\nY");
+    assert_eq!(lines, ["unX", "Yos"]);
+    assert_eq!((cursor.x, cursor.y), (1, 1));
+    assert!(selection.is_none());
+  }
+}
@@ -0,0 +1,744 @@
+// Gramáticas TextMate (.tmLanguage, .tmLanguage.json) y Sublime (.sublime-syntax)
+// cargadas desde disco. Las dos se convierten al mismo modelo: contextos con reglas
+// que al coincidir pueden apilar (push), desapilar (pop) o reemplazar (set) contextos.
+// Un begin/end de TextMate es un push a un contexto cuya primera regla es el end.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use fancy_regex::{ Captures, Regex };
+use yaml_rust2::{ Yaml, YamlLoader };
+
+use super::Scope;
+
+pub struct Grammar {
+  pub name: String,
+  extensions: Vec<String>,
+  first_line: Option<Regex>,
+  contexts: Vec<Context>,
+  rules: Vec<Rule>,
+  main: usize,
+}
+
+#[derive(Default)]
+struct Context {
+  meta_scope: Option<Scope>,         // todo el contexto, incluidos los matches de entrada y salida
+  meta_content_scope: Option<Scope>, // solo lo de adentro
+  items: Vec<Item>,
+  flat: Vec<usize>,                  // reglas con los include ya expandidos
+  end_rule: Option<usize>,           // end de TextMate con \1..\9 del begin
+  end_pattern: Option<String>,
+  include_prototype: bool,
+}
+
+enum Item {
+  Rule(usize),
+  Include(String),
+}
+
+struct Rule {
+  regex: Option<Regex>,
+  scope: Option<Scope>,
+  captures: Vec<(usize, Scope)>,
+  action: Action,
+}
+
+enum Action {
+  None,
+  Push(Vec<usize>),
+  Set(Vec<usize>),
+  Pop(usize),
+}
+
+// Un nivel de la pila; `end` es el end de TextMate ya armado con los grupos del begin
+#[derive(Clone)]
+pub struct Frame {
+  ctx: usize,
+  end: Option<Regex>,
+}
+
+// pasos máximos por linea (por si una gramática apila y desapila sin avanzar)
+const MAX_STEPS_PER_CHAR: usize = 8;
+
+// Carga todas las gramáticas de `dir`; devuelve las que se pudieron leer y los errores
+pub fn load_dir(dir: &Path) -> (Vec<Grammar>, Vec<String>) {
+  let mut grammars = Vec::new();
+  let mut errors = Vec::new();
+
+  let Ok(entries) = fs::read_dir(dir) else { return (grammars, errors) };
+  let mut paths: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+  paths.sort();
+
+  for path in paths {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let result = if file_name.ends_with(".sublime-syntax") {
+      read_yaml(&path).and_then(|yaml| Grammar::from_sublime(&yaml))
+    } else if file_name.ends_with(".tmLanguage.json") || file_name.ends_with(".tmLanguage.yaml") {
+      read_yaml(&path).and_then(|yaml| Grammar::from_textmate(&yaml))
+    } else if file_name.ends_with(".tmLanguage") || file_name.ends_with(".plist") {
+      plist::Value::from_file(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|value| Grammar::from_textmate(&plist_to_yaml(value)))
+    } else {
+      continue;
+    };
+
+    match result {
+      Ok(grammar) => grammars.push(grammar),
+      Err(e) => errors.push(format!("{}: {}", file_name, e)),
+    }
+  }
+
+  (grammars, errors)
+}
+
+// YAML también lee JSON, así que sirve para .sublime-syntax y .tmLanguage.json
+fn read_yaml(path: &Path) -> Result<Yaml, String> {
+  let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+  let mut docs = YamlLoader::load_from_str(&text).map_err(|e| e.to_string())?;
+  if docs.is_empty() {
+    return Err("archivo vacío".to_string());
+  }
+  Ok(docs.swap_remove(0))
+}
+
+fn plist_to_yaml(value: plist::Value) -> Yaml {
+  match value {
+    plist::Value::Dictionary(dict) => Yaml::Hash(
+      dict.into_iter().map(|(k, v)| (Yaml::String(k), plist_to_yaml(v))).collect()
+    ),
+    plist::Value::Array(items) => Yaml::Array(items.into_iter().map(plist_to_yaml).collect()),
+    plist::Value::String(s) => Yaml::String(s),
+    plist::Value::Boolean(b) => Yaml::Boolean(b),
+    plist::Value::Integer(n) => n.as_signed().map_or(Yaml::Null, Yaml::Integer),
+    _ => Yaml::Null,
+  }
+}
+
+// Scope propio para un nombre de TextMate ("string.quoted.double.rust").
+// Si hay varios separados por espacios gana el último que se reconozca.
+pub fn map_scope(names: &str) -> Option<Scope> {
+  const PREFIXES: &[(&str, Scope)] = &[
+    ("comment", Scope::Comment),
+    ("punctuation.definition.comment", Scope::Comment),
+    ("markup.quote", Scope::Comment),
+    ("string", Scope::String),
+    ("punctuation.definition.string", Scope::String),
+    ("constant.numeric", Scope::Number),
+    ("constant", Scope::Constant),
+    ("keyword.operator", Scope::Plain),
+    ("keyword", Scope::Keyword),
+    ("storage", Scope::Keyword),
+    ("entity.name.tag", Scope::Keyword),
+    ("entity.name.function", Scope::Function),
+    ("support.function", Scope::Function),
+    ("variable.function", Scope::Function),
+    ("entity.name.section", Scope::Heading),
+    ("markup.heading", Scope::Heading),
+    ("entity.name", Scope::Type),
+    ("entity.other.inherited-class", Scope::Type),
+    ("support.type.property-name", Scope::Key),
+    ("meta.mapping.key", Scope::Key),
+    ("support.type", Scope::Type),
+    ("support.class", Scope::Type),
+    ("entity.other.attribute-name", Scope::Attribute),
+    ("meta.attribute", Scope::Attribute),
+    ("meta.annotation", Scope::Attribute),
+    ("variable", Scope::Variable),
+    ("markup.bold", Scope::Emphasis),
+    ("markup.italic", Scope::Emphasis),
+    ("markup.raw", Scope::Code),
+    ("markup.inline.raw", Scope::Code),
+    ("markup.underline.link", Scope::Link),
+  ];
+
+  names.split_whitespace().rev().find_map(|name| {
+    PREFIXES.iter()
+      .find(|(prefix, _)| name == *prefix || name.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('.')))
+      .map(|(_, scope)| *scope)
+  })
+}
+
+// Si la expresión usa algo de Oniguruma que fancy-regex no acepta, la regla se ignora
+// como en Oniguruma, ^ y $ valen al principio y al final de la linea (que termina en \n)
+fn compile(pattern: &str) -> Option<Regex> {
+  Regex::new(&format!("(?m){}", pattern)).ok()
+}
+
+fn has_backrefs(pattern: &str) -> bool {
+  let bytes = pattern.as_bytes();
+  let mut i = 0;
+  while i + 1 < bytes.len() {
+    if bytes[i] == b'\\' {
+      if bytes[i + 1].is_ascii_digit() {
+        return true;
+      }
+      i += 2;
+    } else {
+      i += 1;
+    }
+  }
+  false
+}
+
+// Reemplaza \1..\9 del end por el texto (escapado) que capturó el begin
+fn fill_backrefs(pattern: &str, captures: &Captures) -> String {
+  let mut out = String::new();
+  let mut chars = pattern.chars().peekable();
+  while let Some(c) = chars.next() {
+    if c == '\\' {
+      match chars.peek() {
+        Some(d) if d.is_ascii_digit() => {
+          let group = d.to_digit(10).unwrap_or(0) as usize;
+          chars.next();
+          out.push_str(&fancy_regex::escape(captures.get(group).map_or("", |m| m.as_str())));
+        }
+        Some(_) => {
+          out.push(c);
+          out.extend(chars.next());
+        }
+        None => out.push(c),
+      }
+    } else {
+      out.push(c);
+    }
+  }
+  out
+}
+
+fn yaml_str(yaml: &Yaml, key: &str) -> Option<String> {
+  yaml[key].as_str().map(|s| s.to_string())
+}
+
+fn yaml_captures(yaml: &Yaml) -> Vec<(usize, Scope)> {
+  let Some(hash) = yaml.as_hash() else { return Vec::new() };
+  hash.iter()
+    .filter_map(|(key, value)| {
+      let group = match key {
+        Yaml::Integer(n) => *n as usize,
+        Yaml::String(s) => s.parse().ok()?,
+        _ => return None,
+      };
+      // TextMate: { "1": { "name": "..." } }, Sublime: { 1: "..." }
+      let name = value.as_str().map(|s| s.to_string()).or_else(|| yaml_str(value, "name"))?;
+      Some((group, map_scope(&name)?))
+    })
+    .collect()
+}
+
+// Arma la gramática mientras se leen los contextos con nombre
+struct Builder {
+  contexts: Vec<Context>,
+  rules: Vec<Rule>,
+  names: HashMap<String, usize>,
+}
+
+impl Builder {
+  fn new() -> Self {
+    Builder { contexts: Vec::new(), rules: Vec::new(), names: HashMap::new() }
+  }
+
+  fn add_context(&mut self, context: Context) -> usize {
+    self.contexts.push(context);
+    self.contexts.len() - 1
+  }
+
+  fn add_rule(&mut self, rule: Rule) -> usize {
+    self.rules.push(rule);
+    self.rules.len() - 1
+  }
+
+  // expande los include de cada contexto a una lista plana de reglas
+  fn finish(mut self, name: String, extensions: Vec<String>, first_line: Option<Regex>) -> Result<Grammar, String> {
+    let Some(&main) = self.names.get("main") else {
+      return Err("la gramática no tiene contexto main".to_string());
+    };
+
+    let prototype = self.names.get("prototype").copied();
+    for ctx in 0..self.contexts.len() {
+      let mut flat = Vec::new();
+      let mut visited = vec![false; self.contexts.len()];
+      if let Some(prototype) = prototype
+        && self.contexts[ctx].include_prototype && ctx != prototype {
+        self.flatten(prototype, &mut flat, &mut visited);
+      }
+      self.flatten(ctx, &mut flat, &mut visited);
+      self.contexts[ctx].flat = flat;
+    }
+
+    Ok(Grammar { name, extensions, first_line, contexts: self.contexts, rules: self.rules, main })
+  }
+
+  fn flatten(&self, ctx: usize, flat: &mut Vec<usize>, visited: &mut [bool]) {
+    if visited[ctx] {
+      return;
+    }
+    visited[ctx] = true;
+
+    for item in &self.contexts[ctx].items {
+      match item {
+        Item::Rule(rule) => flat.push(*rule),
+        Item::Include(name) => {
+          if let Some(&included) = self.names.get(name.as_str()) {
+            self.flatten(included, flat, visited);
+          }
+        }
+      }
+    }
+  }
+
+  // --- TextMate ---
+
+  fn textmate_patterns(&mut self, patterns: &Yaml) -> Vec<Item> {
+    let mut items = Vec::new();
+    for pattern in patterns.as_vec().map(|v| v.as_slice()).unwrap_or_default() {
+      items.extend(self.textmate_rule(pattern));
+    }
+    items
+  }
+
+  fn textmate_rule(&mut self, rule: &Yaml) -> Option<Item> {
+    if let Some(include) = yaml_str(rule, "include") {
+      // "#nombre" del repositorio, "$self"/"$base" la gramática entera; otras gramáticas no
+      let name = match include.as_str() {
+        "$self" | "$base" => "main".to_string(),
+        _ => include.strip_prefix('#')?.to_string(),
+      };
+      return Some(Item::Include(name));
+    }
+
+    let scope = yaml_str(rule, "name").and_then(|n| map_scope(&n));
+
+    if let Some(pattern) = yaml_str(rule, "match") {
+      let captures = yaml_captures(&rule["captures"]);
+      let rule = self.add_rule(Rule { regex: compile(&pattern), scope, captures, action: Action::None });
+      return Some(Item::Rule(rule));
+    }
+
+    if let Some(begin) = yaml_str(rule, "begin") {
+      // "while" se aproxima como un end que corta en la primera linea que no lo cumple
+      let end = yaml_str(rule, "end")
+        .or_else(|| yaml_str(rule, "while").map(|w| format!("^(?!{})", w)))
+        .unwrap_or_else(|| "$^".to_string());
+      let fallback = yaml_captures(&rule["captures"]);
+      let begin_captures = Some(yaml_captures(&rule["beginCaptures"])).filter(|c| !c.is_empty()).unwrap_or(fallback.clone());
+      let end_captures = Some(yaml_captures(&rule["endCaptures"])).filter(|c| !c.is_empty()).unwrap_or(fallback);
+
+      let end_rule = self.add_rule(Rule {
+        regex: compile(&end),
+        scope: None,
+        captures: end_captures,
+        action: Action::Pop(1),
+      });
+      let ctx = self.add_context(Context {
+        meta_scope: scope,
+        meta_content_scope: yaml_str(rule, "contentName").and_then(|n| map_scope(&n)),
+        end_rule: Some(end_rule),
+        end_pattern: has_backrefs(&end).then_some(end),
+        ..Context::default()
+      });
+      let mut items = vec![Item::Rule(end_rule)];
+      items.extend(self.textmate_patterns(&rule["patterns"]));
+      self.contexts[ctx].items = items;
+
+      let rule = self.add_rule(Rule { regex: compile(&begin), scope: None, captures: begin_captures, action: Action::Push(vec![ctx]) });
+      return Some(Item::Rule(rule));
+    }
+
+    // solo "patterns": un grupo de reglas
+    if !rule["patterns"].is_badvalue() {
+      let items = self.textmate_patterns(&rule["patterns"]);
+      let ctx = self.add_context(Context { items, ..Context::default() });
+      let name = format!("#anonimo{}", ctx);
+      self.names.insert(name.clone(), ctx);
+      return Some(Item::Include(name));
+    }
+
+    None
+  }
+
+  // --- Sublime ---
+
+  fn sublime_context(&mut self, entries: &Yaml, variables: &HashMap<String, String>) -> usize {
+    let ctx = self.add_context(Context { include_prototype: true, ..Context::default() });
+    let mut items = Vec::new();
+
+    for entry in entries.as_vec().map(|v| v.as_slice()).unwrap_or_default() {
+      if let Some(scope) = yaml_str(entry, "meta_scope") {
+        self.contexts[ctx].meta_scope = map_scope(&scope);
+      }
+      if let Some(scope) = yaml_str(entry, "meta_content_scope") {
+        self.contexts[ctx].meta_content_scope = map_scope(&scope);
+      }
+      if entry["meta_include_prototype"].as_bool() == Some(false) {
+        self.contexts[ctx].include_prototype = false;
+      }
+
+      if let Some(include) = yaml_str(entry, "include") {
+        items.push(Item::Include(include));
+        continue;
+      }
+
+      let Some(pattern) = yaml_str(entry, "match") else { continue };
+      let action = if let Some(pop) = entry["pop"].as_bool().filter(|p| *p).map(|_| 1).or(entry["pop"].as_i64().map(|n| n as usize)) {
+        Action::Pop(pop)
+      } else if !entry["push"].is_badvalue() {
+        Action::Push(self.sublime_targets(&entry["push"], variables))
+      } else if !entry["set"].is_badvalue() {
+        Action::Set(self.sublime_targets(&entry["set"], variables))
+      } else {
+        Action::None
+      };
+
+      let rule = self.add_rule(Rule {
+        regex: compile(&expand_variables(&pattern, variables)),
+        scope: yaml_str(entry, "scope").and_then(|s| map_scope(&s)),
+        captures: yaml_captures(&entry["captures"]),
+        action,
+      });
+      items.push(Item::Rule(rule));
+    }
+
+    self.contexts[ctx].items = items;
+    ctx
+  }
+
+  // destino de push/set: un nombre, una lista de nombres o un contexto anónimo
+  fn sublime_targets(&mut self, target: &Yaml, variables: &HashMap<String, String>) -> Vec<usize> {
+    match target {
+      Yaml::String(name) => vec![self.context_by_name(name)],
+      Yaml::Array(items) if items.first().is_some_and(|i| i.as_hash().is_some()) => {
+        vec![self.sublime_context(target, variables)]
+      }
+      Yaml::Array(items) => items.iter()
+        .filter_map(|i| i.as_str())
+        .map(|name| self.context_by_name(name))
+        .collect(),
+      _ => Vec::new(),
+    }
+  }
+
+  // los contextos con nombre se crean vacíos y se llenan al leerlos (pueden usarse antes)
+  fn context_by_name(&mut self, name: &str) -> usize {
+    if let Some(&ctx) = self.names.get(name) {
+      return ctx;
+    }
+    let ctx = self.add_context(Context { include_prototype: true, ..Context::default() });
+    self.names.insert(name.to_string(), ctx);
+    ctx
+  }
+}
+
+// {{variable}} de Sublime, que a su vez pueden usar otras variables
+fn expand_variables(pattern: &str, variables: &HashMap<String, String>) -> String {
+  let mut out = pattern.to_string();
+  for _ in 0..10 {
+    if !out.contains("{{") {
+      break;
+    }
+    for (name, value) in variables {
+      out = out.replace(&format!("{{{{{}}}}}", name), value);
+    }
+  }
+  out
+}
+
+impl Grammar {
+  pub fn from_textmate(yaml: &Yaml) -> Result<Grammar, String> {
+    let name = yaml_str(yaml, "name")
+      .or_else(|| yaml_str(yaml, "scopeName"))
+      .ok_or("falta name o scopeName")?;
+    let extensions = string_list(&yaml["fileTypes"]);
+    let first_line = yaml_str(yaml, "firstLineMatch").and_then(|p| compile(&p));
+
+    let mut builder = Builder::new();
+    let main = builder.add_context(Context::default());
+    builder.names.insert("main".to_string(), main);
+
+    // cada entrada del repositorio es un contexto al que se llega con include "#nombre"
+    if let Some(repository) = yaml["repository"].as_hash() {
+      for (key, rule) in repository {
+        let Some(key) = key.as_str() else { continue };
+        let ctx = builder.add_context(Context::default());
+        builder.names.insert(key.to_string(), ctx);
+
+        let items = if rule["match"].is_badvalue() && rule["begin"].is_badvalue() && rule["include"].is_badvalue() {
+          builder.textmate_patterns(&rule["patterns"])
+        } else {
+          builder.textmate_rule(rule).into_iter().collect()
+        };
+        builder.contexts[ctx].items = items;
+      }
+    }
+
+    builder.contexts[main].items = builder.textmate_patterns(&yaml["patterns"]);
+    builder.finish(name, extensions, first_line)
+  }
+
+  pub fn from_sublime(yaml: &Yaml) -> Result<Grammar, String> {
+    let name = yaml_str(yaml, "name")
+      .or_else(|| yaml_str(yaml, "scope"))
+      .ok_or("falta name o scope")?;
+    let extensions = string_list(&yaml["file_extensions"]);
+
+    let mut variables = HashMap::new();
+    if let Some(vars) = yaml["variables"].as_hash() {
+      for (key, value) in vars {
+        if let (Some(key), Some(value)) = (key.as_str(), value.as_str()) {
+          variables.insert(key.to_string(), value.to_string());
+        }
+      }
+    }
+    let first_line = yaml_str(yaml, "first_line_match").and_then(|p| compile(&expand_variables(&p, &variables)));
+
+    let contexts = yaml["contexts"].as_hash().ok_or("falta contexts")?;
+    let mut builder = Builder::new();
+    for (key, entries) in contexts {
+      let Some(key) = key.as_str() else { continue };
+      let ctx = builder.context_by_name(key);
+      // se lee en un contexto nuevo y se mueve al lugar reservado para el nombre
+      let filled = builder.sublime_context(entries, &variables);
+      builder.contexts.swap(ctx, filled);
+    }
+    if let Some(&prototype) = builder.names.get("prototype") {
+      builder.contexts[prototype].include_prototype = false;
+    }
+
+    builder.finish(name, extensions, first_line)
+  }
+
+  pub fn matches(&self, file_name: &str, extension: &str, first_line: Option<&str>) -> bool {
+    self.extensions.iter().any(|e| e == extension || e == file_name)
+      || first_line.zip(self.first_line.as_ref())
+        .is_some_and(|(line, regex)| regex.is_match(line).unwrap_or(false))
+  }
+
+  pub fn initial_state(&self) -> Vec<Frame> {
+    vec![Frame { ctx: self.main, end: None }]
+  }
+
+  // scope del texto con la pila actual; `content` incluye el meta_content_scope del tope
+  fn stack_scope(&self, stack: &[Frame], content: bool) -> Scope {
+    let mut scope = Scope::Plain;
+    for (i, frame) in stack.iter().enumerate() {
+      let ctx = &self.contexts[frame.ctx];
+      if let Some(s) = ctx.meta_scope {
+        scope = s;
+      }
+      if (content || i + 1 < stack.len())
+        && let Some(s) = ctx.meta_content_scope {
+        scope = s;
+      }
+    }
+    scope
+  }
+
+  fn push(&self, stack: &mut Vec<Frame>, targets: &[usize], captures: &Captures) {
+    for &ctx in targets {
+      let end = self.contexts[ctx].end_pattern.as_ref()
+        .and_then(|pattern| compile(&fill_backrefs(pattern, captures)));
+      stack.push(Frame { ctx, end });
+    }
+  }
+
+  // Scope de cada caracter de la linea; `stack` queda con el estado para la siguiente
+  pub fn highlight_line(&self, line: &str, stack: &mut Vec<Frame>) -> Vec<Scope> {
+    // las gramáticas esperan el fin de linea ($, \n)
+    let text = format!("{}\n", line);
+    let mut byte_scopes = vec![Scope::Plain; text.len()];
+    let mut pos = 0;
+    let mut steps = 0;
+    let max_steps = (line.len() + 1) * MAX_STEPS_PER_CHAR;
+
+    while pos < text.len() && steps < max_steps {
+      steps += 1;
+      if stack.is_empty() {
+        *stack = self.initial_state();
+      }
+      let frame = stack.last().cloned().unwrap_or(Frame { ctx: self.main, end: None });
+      let ctx = &self.contexts[frame.ctx];
+      let content = self.stack_scope(stack, true);
+
+      // la regla que coincide más a la izquierda; a igual posición, la primera
+      let mut best: Option<(usize, Captures)> = None;
+      for &r in &ctx.flat {
+        let regex = match &frame.end {
+          Some(end) if ctx.end_rule == Some(r) => Some(end),
+          _ => self.rules[r].regex.as_ref(),
+        };
+        let Some(regex) = regex else { continue };
+        let Ok(Some(captures)) = regex.captures_from_pos(&text, pos) else { continue };
+        let start = captures.get(0).map_or(usize::MAX, |m| m.start());
+        if best.as_ref().is_none_or(|(_, b)| start < b.get(0).map_or(usize::MAX, |m| m.start())) {
+          let at_pos = start == pos;
+          best = Some((r, captures));
+          if at_pos {
+            break;
+          }
+        }
+      }
+
+      let Some((r, captures)) = best else {
+        byte_scopes[pos..].fill(content);
+        break;
+      };
+      let Some(whole) = captures.get(0) else { break };
+      byte_scopes[pos..whole.start()].fill(content);
+
+      let rule = &self.rules[r];
+      let depth = stack.len();
+      let match_scope = match &rule.action {
+        Action::None => content,
+        Action::Push(targets) => {
+          self.push(stack, targets, &captures);
+          self.stack_scope(stack, false)
+        }
+        Action::Set(targets) => {
+          let scope = self.stack_scope(stack, false);
+          stack.pop();
+          self.push(stack, targets, &captures);
+          scope
+        }
+        Action::Pop(n) => {
+          let scope = self.stack_scope(stack, false);
+          let keep = stack.len().saturating_sub(*n).max(1);
+          stack.truncate(keep);
+          scope
+        }
+      };
+
+      byte_scopes[whole.start()..whole.end()].fill(rule.scope.unwrap_or(match_scope));
+      for (group, scope) in &rule.captures {
+        if let Some(m) = captures.get(*group) {
+          byte_scopes[m.start()..m.end()].fill(*scope);
+        }
+      }
+
+      if whole.end() > pos {
+        pos = whole.end();
+      } else if stack.len() == depth && matches!(rule.action, Action::None) {
+        // match vacío que no cambia nada: avanzar un caracter
+        let next = text[pos..].chars().next().map_or(1, |c| c.len_utf8());
+        pos += next;
+      }
+    }
+
+    line.char_indices().map(|(i, _)| byte_scopes[i]).collect()
+  }
+}
+
+fn string_list(yaml: &Yaml) -> Vec<String> {
+  yaml.as_vec()
+    .map(|items| items.iter().filter_map(|i| i.as_str()).map(|s| s.trim_start_matches('.').to_string()).collect())
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn yaml(text: &str) -> Yaml {
+    YamlLoader::load_from_str(text).unwrap().remove(0)
+  }
+
+  // una letra por char: . texto, k palabra clave, s string, / comentario, n número
+  fn highlight(grammar: &Grammar, lines: &[&str]) -> Vec<String> {
+    let mut stack = grammar.initial_state();
+    lines.iter()
+      .map(|line| {
+        grammar.highlight_line(line, &mut stack).iter()
+          .map(|scope| match scope {
+            Scope::Keyword => 'k',
+            Scope::String => 's',
+            Scope::Comment => '/',
+            Scope::Number => 'n',
+            _ => '.',
+          })
+          .collect()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn map_scope_prefixes() {
+    assert_eq!(map_scope("string.quoted.double.rust"), Some(Scope::String));
+    assert_eq!(map_scope("constant.numeric.integer"), Some(Scope::Number));
+    assert_eq!(map_scope("constant.language"), Some(Scope::Constant));
+    assert_eq!(map_scope("keyword.operator.arithmetic"), Some(Scope::Plain));
+    // gana el último nombre que se reconozca
+    assert_eq!(map_scope("string.quoted comment.line"), Some(Scope::Comment));
+    assert_eq!(map_scope("stringy"), None);
+    assert_eq!(map_scope("meta.block"), None);
+  }
+
+  #[test]
+  fn backrefs_are_filled_escaped() {
+    assert!(has_backrefs("^\\1$"));
+    assert!(!has_backrefs("\\\\1"));
+    assert!(!has_backrefs("\\d+"));
+
+    let regex = Regex::new("<<(\\S+)").unwrap();
+    let captures = regex.captures("<<E.O.F").unwrap().unwrap();
+    assert_eq!(fill_backrefs("^\\1$\\n", &captures), "^E\\.O\\.F$\\n");
+  }
+
+  #[test]
+  fn sublime_variables_and_contexts() {
+    let grammar = Grammar::from_sublime(&yaml(r#"
+name: Prueba
+file_extensions: [pr]
+variables:
+  ident: '[a-z]+'
+contexts:
+  main:
+    - match: '\b(if|else)\b'
+      scope: keyword.control
+    - match: '"'
+      push: string
+    - match: '#.*$'
+      scope: comment.line
+  string:
+    - meta_scope: string.quoted
+    - match: '"'
+      pop: true
+    - match: '{{ident}}'
+"#)).unwrap();
+
+    assert!(grammar.matches("x.pr", "pr", None));
+    assert_eq!(highlight(&grammar, &["if \"a\" # c"]), vec!["kk.sss.///"]);
+    // el string sigue abierto en la linea siguiente
+    assert_eq!(highlight(&grammar, &["x \"ab", "c\" else"]), vec!["..sss", "ss.kkkk"]);
+  }
+
+  #[test]
+  fn textmate_begin_end_with_backrefs() {
+    let grammar = Grammar::from_textmate(&yaml(r#"
+name: Heredoc
+fileTypes: [hd]
+firstLineMatch: '^#!.*heredoc'
+patterns:
+  - include: '#heredoc'
+  - match: '\d+'
+    name: constant.numeric
+repository:
+  heredoc:
+    begin: '<<(\w+)'
+    end: '^\1$'
+    name: string.unquoted.heredoc
+"#)).unwrap();
+
+    assert!(grammar.matches("", "", Some("#!/bin/heredoc")));
+    assert_eq!(
+      highlight(&grammar, &["1 <<FIN", "FINAL 2", "FIN", "3"]),
+      vec!["n.sssss", "sssssss", "sss", "n"],
+    );
+  }
+
+  #[test]
+  fn missing_names_are_errors() {
+    assert!(Grammar::from_sublime(&yaml("contexts: {main: []}")).is_err());
+    assert!(Grammar::from_sublime(&yaml("name: x")).is_err());
+    assert!(Grammar::from_textmate(&yaml("patterns: []")).is_err());
+  }
+}
@@ -0,0 +1,199 @@
+// Definiciones de los lenguajes incluidos en el editor
+
+use super::{ LanguageDef, StringDef };
+use super::markdown;
+
+// valores por defecto: sin comentarios, strings ni palabras clave
+const BASE: LanguageDef = LanguageDef {
+  name: "",
+  extensions: &[],
+  file_names: &[],
+  shebangs: &[],
+  line_comments: &[],
+  block_comment: None,
+  nested_comments: false,
+  strings: &[],
+  keywords: &[],
+  types: &[],
+  constants: &[],
+  capitalized_types: false,
+  functions: false,
+  lifetimes: false,
+  variable_prefix: None,
+  attributes: &[],
+  key_separator: None,
+  section_headers: false,
+  custom: None,
+  word_chars: &[],
+};
+
+const fn string(open: &'static str, close: &'static str, escape: bool, multiline: bool) -> StringDef {
+  StringDef { open, close, escape, multiline }
+}
+
+pub static LANGUAGES: &[LanguageDef] = &[
+  LanguageDef {
+    name: "Rust",
+    extensions: &["rs"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    nested_comments: true,
+    strings: &[
+      string("r#\"", "\"#", false, true),
+      string("r\"", "\"", false, true),
+      string("b\"", "\"", true, true),
+      string("\"", "\"", true, true),
+    ],
+    keywords: &[
+      "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+      "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+      "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "type",
+      "unsafe", "use", "where", "while", "yield",
+    ],
+    types: &[
+      "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32",
+      "i64", "i128", "isize", "f32", "f64",
+    ],
+    constants: &["true", "false", "None", "Some", "Ok", "Err"],
+    capitalized_types: true,
+    functions: true,
+    lifetimes: true,
+    attributes: &["#![", "#["],
+    ..BASE
+  },
+  LanguageDef {
+    name: "TOML",
+    extensions: &["toml"],
+    file_names: &["Cargo.lock"],
+    line_comments: &["#"],
+    strings: &[
+      string("\"\"\"", "\"\"\"", true, true),
+      string("'''", "'''", false, true),
+      string("\"", "\"", true, false),
+      string("'", "'", false, false),
+    ],
+    constants: &["true", "false", "inf", "nan"],
+    key_separator: Some('='),
+    section_headers: true,
+    ..BASE
+  },
+  LanguageDef {
+    name: "JSON",
+    extensions: &["json", "jsonc", "json5"],
+    file_names: &[".prettierrc", ".eslintrc"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    strings: &[string("\"", "\"", true, false)],
+    constants: &["true", "false", "null"],
+    key_separator: Some(':'),
+    ..BASE
+  },
+  LanguageDef {
+    name: "Markdown",
+    extensions: &["md", "markdown", "mdown"],
+    custom: Some(markdown::highlight_line),
+    ..BASE
+  },
+  LanguageDef {
+    name: "Python",
+    extensions: &["py", "pyw", "pyi"],
+    shebangs: &["python"],
+    line_comments: &["#"],
+    strings: &[
+      string("\"\"\"", "\"\"\"", true, true),
+      string("'''", "'''", true, true),
+      string("\"", "\"", true, false),
+      string("'", "'", true, false),
+    ],
+    keywords: &[
+      "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+      "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is",
+      "lambda", "match", "case", "nonlocal", "not", "or", "pass", "raise", "return", "try",
+      "while", "with", "yield", "self",
+    ],
+    types: &["int", "float", "str", "bool", "list", "dict", "set", "tuple", "bytes", "object"],
+    constants: &["True", "False", "None"],
+    capitalized_types: true,
+    functions: true,
+    attributes: &["@"],
+    ..BASE
+  },
+  LanguageDef {
+    name: "JavaScript",
+    extensions: &["js", "mjs", "cjs", "jsx", "ts", "tsx", "mts", "cts"],
+    shebangs: &["node", "deno", "bun"],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    strings: &[
+      string("`", "`", true, true),
+      string("\"", "\"", true, false),
+      string("'", "'", true, false),
+    ],
+    keywords: &[
+      "async", "await", "break", "case", "catch", "class", "const", "continue", "debugger",
+      "default", "delete", "do", "else", "export", "extends", "finally", "for", "from",
+      "function", "if", "import", "in", "instanceof", "let", "new", "of", "return", "static",
+      "super", "switch", "this", "throw", "try", "typeof", "var", "void", "while", "with",
+      "yield", "interface", "type", "enum", "implements", "readonly", "as",
+    ],
+    types: &["string", "number", "boolean", "any", "unknown", "never", "object", "symbol", "bigint"],
+    constants: &["true", "false", "null", "undefined", "NaN", "Infinity"],
+    capitalized_types: true,
+    functions: true,
+    attributes: &["@"],
+    word_chars: &['$'],
+    ..BASE
+  },
+  LanguageDef {
+    name: "CSS",
+    extensions: &["css", "scss", "less"],
+    block_comment: Some(("/*", "*/")),
+    strings: &[
+      string("\"", "\"", true, false),
+      string("'", "'", true, false),
+    ],
+    constants: &["inherit", "initial", "unset", "none", "auto", "important"],
+    functions: true,
+    attributes: &["@"],
+    word_chars: &['-'],
+    ..BASE
+  },
+  LanguageDef {
+    name: "Lisp",
+    extensions: &["lisp", "lsp", "cl", "el", "scm", "ss", "rkt", "clj", "cljs", "cljc", "edn"],
+    file_names: &[".emacs"],
+    shebangs: &["sbcl", "racket", "guile", "clojure"],
+    line_comments: &[";"],
+    block_comment: Some(("#|", "|#")),
+    nested_comments: true,
+    strings: &[string("\"", "\"", true, true)],
+    keywords: &[
+      "defun", "defmacro", "defvar", "defparameter", "defconstant", "define", "defn", "def",
+      "lambda", "fn", "let", "letrec", "if", "when", "unless", "cond", "case", "and",
+      "or", "not", "progn", "begin", "do", "loop", "quote", "setq", "setf", "require", "ns",
+    ],
+    constants: &["t", "nil", "true", "false"],
+    word_chars: &['-', '?', '!', '*', '+', '<', '>', '=', '/'],
+    ..BASE
+  },
+  LanguageDef {
+    name: "Shell",
+    extensions: &["sh", "bash", "zsh", "ksh"],
+    file_names: &[".bashrc", ".bash_profile", ".profile", ".zshrc"],
+    shebangs: &["sh", "bash", "zsh", "ksh", "dash"],
+    line_comments: &["#"],
+    strings: &[
+      string("\"", "\"", true, true),
+      string("'", "'", false, true),
+    ],
+    keywords: &[
+      "if", "then", "else", "elif", "fi", "case", "esac", "for", "while", "until", "do",
+      "done", "in", "function", "return", "local", "export", "readonly", "declare", "set",
+      "unset", "shift", "exit", "source", "alias", "echo", "cd", "eval", "exec", "trap",
+    ],
+    constants: &["true", "false"],
+    variable_prefix: Some('$'),
+    word_chars: &['$'],
+    ..BASE
+  },
+];
@@ -0,0 +1,98 @@
+// Markdown no encaja en el tokenizador genérico: se resalta por bloques (titulos,
+// citas, listas, bloques ```) y adentro de cada linea por marcas (`codigo`, *enfasis*, [links](url))
+
+use super::{ LineState, Scope, mark };
+
+pub fn highlight_line(line: &str, state: LineState) -> (Vec<Scope>, LineState) {
+  let chars: Vec<char> = line.chars().collect();
+  let trimmed = line.trim_start();
+  let indent = chars.len() - trimmed.chars().count();
+
+  // bloques ``` o ~~~
+  let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+  if state == LineState::CodeBlock {
+    let next = if is_fence { LineState::Normal } else { LineState::CodeBlock };
+    return (vec![Scope::Code; chars.len()], next);
+  }
+  if is_fence {
+    return (vec![Scope::Code; chars.len()], LineState::CodeBlock);
+  }
+
+  // titulos
+  if trimmed.starts_with('#') {
+    return (vec![Scope::Heading; chars.len()], LineState::Normal);
+  }
+
+  // separadores: --- *** ___
+  let rule: Vec<char> = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+  if rule.len() >= 3 && matches!(rule[0], '-' | '*' | '_') && rule.iter().all(|c| *c == rule[0]) {
+    return (vec![Scope::Comment; chars.len()], LineState::Normal);
+  }
+
+  // citas
+  if trimmed.starts_with('>') {
+    return (vec![Scope::Comment; chars.len()], LineState::Normal);
+  }
+
+  let mut scopes = vec![Scope::Plain; chars.len()];
+  let mut i = indent;
+
+  // marcadores de lista: "- ", "* ", "+ ", "1. ", "1) "
+  let digits = chars[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+  let marker_len = match (chars.get(i), chars.get(i + 1)) {
+    (Some('-' | '*' | '+'), Some(' ')) => 1,
+    _ if digits > 0 && matches!(chars.get(i + digits), Some('.' | ')')) && chars.get(i + digits + 1) == Some(&' ') => digits + 1,
+    _ => 0,
+  };
+  for s in scopes.iter_mut().skip(i).take(marker_len) {
+    *s = Scope::Keyword;
+  }
+  i += marker_len;
+
+  while i < chars.len() {
+    let c = chars[i];
+
+    // `codigo`
+    if c == '`'
+      && let Some(end) = find(&chars, i + 1, '`') {
+      mark(&mut scopes, i, end + 1, Scope::Code);
+      i = end + 1;
+      continue;
+    }
+
+    // **fuerte**, *enfasis*, _enfasis_
+    if (c == '*' || c == '_') && chars.get(i + 1).is_some_and(|n| !n.is_whitespace()) {
+      let double = chars.get(i + 1) == Some(&c);
+      let start = if double { i + 2 } else { i + 1 };
+      let end = if double {
+        (start..chars.len().saturating_sub(1)).find(|&j| chars[j] == c && chars[j + 1] == c).map(|j| j + 2)
+      } else {
+        find(&chars, start, c).map(|j| j + 1)
+      };
+      if let Some(end) = end {
+        mark(&mut scopes, i, end, Scope::Emphasis);
+        i = end;
+        continue;
+      }
+    }
+
+    // [texto](url)
+    if c == '['
+      && let Some(close) = find(&chars, i + 1, ']')
+      && chars.get(close + 1) == Some(&'(')
+      && let Some(end) = find(&chars, close + 2, ')') {
+      mark(&mut scopes, i, close + 1, Scope::Link);
+      mark(&mut scopes, close + 1, end + 1, Scope::String);
+      i = end + 1;
+      continue;
+    }
+
+    i += 1;
+  }
+
+  (scopes, LineState::Normal)
+}
+
+fn find(chars: &[char], from: usize, target: char) -> Option<usize> {
+  chars.iter().skip(from).position(|&c| c == target).map(|p| p + from)
+}
@@ -0,0 +1,605 @@
+// Resaltado de sintaxis: cada lenguaje se describe con datos (LanguageDef) y un
+// tokenizador genérico los recorre linea por linea. El estado al final de cada linea
+// (comentario de bloque o string sin cerrar) pasa a la siguiente.
+// Además se pueden cargar gramáticas TextMate/Sublime desde ~/.camelCaseEditor/syntaxes.
+
+mod grammar;
+mod languages;
+mod markdown;
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{ Hash, Hasher };
+use std::path::Path;
+use std::sync::OnceLock;
+
+pub use grammar::Grammar;
+pub use languages::LANGUAGES;
+
+// gramáticas cargadas al iniciar; viven todo el programa
+static GRAMMARS: OnceLock<Vec<Grammar>> = OnceLock::new();
+
+// Lenguaje de un documento: incluido en el editor o gramática cargada de disco
+#[derive(Clone, Copy)]
+pub enum Syntax {
+  Builtin(&'static LanguageDef),
+  Grammar(&'static Grammar),
+}
+
+// Estado al empezar una linea
+#[derive(Clone)]
+enum State {
+  Builtin(LineState),
+  Grammar(Vec<grammar::Frame>),
+}
+
+// Estados ya calculados de cada linea, para no retokenizar todo el archivo en cada
+// dibujo. Se guarda un hash de cada linea: el estado de la linea i sirve mientras
+// ninguna de las anteriores haya cambiado.
+#[derive(Default)]
+pub struct HighlightCache {
+  hashes: Vec<u64>,   // hash de la linea i
+  states: Vec<State>, // estado al empezar la linea i
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Scope {
+  Plain,
+  Keyword,
+  Type,
+  Constant,
+  Number,
+  String,
+  Comment,
+  Function,
+  Attribute,
+  Key,
+  Variable,
+  Heading,
+  Emphasis,
+  Code,
+  Link,
+}
+
+// Lo que queda abierto al terminar una linea
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LineState {
+  #[default]
+  Normal,
+  Comment(u8),  // profundidad del comentario de bloque
+  String(u8),   // indice en `strings` del string multilinea abierto
+  CodeBlock,    // bloque ``` de Markdown
+}
+
+// Resalta una linea a partir del estado en que terminó la anterior
+pub type Tokenizer = fn(&str, LineState) -> (Vec<Scope>, LineState);
+
+pub struct StringDef {
+  pub open: &'static str,
+  pub close: &'static str,
+  pub escape: bool,    // '\' escapa el siguiente caracter
+  pub multiline: bool, // puede seguir en la linea siguiente
+}
+
+pub struct LanguageDef {
+  pub name: &'static str,
+  pub extensions: &'static [&'static str],
+  pub file_names: &'static [&'static str],
+  pub shebangs: &'static [&'static str], // programas de "#!" (sin version: "python", no "python3")
+  pub line_comments: &'static [&'static str],
+  pub block_comment: Option<(&'static str, &'static str)>,
+  pub nested_comments: bool,
+  pub strings: &'static [StringDef],
+  pub keywords: &'static [&'static str],
+  pub types: &'static [&'static str],
+  pub constants: &'static [&'static str],
+  pub capitalized_types: bool,         // Identificadores en MayusculaInicial son tipos
+  pub functions: bool,                 // identificador seguido de '(' (o '!' en Rust)
+  pub lifetimes: bool,                 // 'a en Rust no abre un caracter
+  pub variable_prefix: Option<char>,   // $VAR en shell
+  pub attributes: &'static [&'static str], // "#[" hasta ']' o "@" seguido de un nombre
+  pub key_separator: Option<char>,     // clave = valor (TOML), "clave": valor (JSON)
+  pub section_headers: bool,           // [seccion] al principio de la linea (TOML)
+  pub custom: Option<Tokenizer>,       // tokenizador propio (Markdown)
+  pub word_chars: &'static [char],     // puntuación que es parte de las palabras (ver word.rs)
+}
+
+// Carga las gramáticas de `dir` (una sola vez, al iniciar). Devuelve los errores.
+pub fn load_grammars(dir: &Path) -> Vec<String> {
+  let (grammars, errors) = grammar::load_dir(dir);
+  let _ = GRAMMARS.set(grammars);
+  errors
+}
+
+fn grammars() -> &'static [Grammar] {
+  GRAMMARS.get().map(|g| g.as_slice()).unwrap_or_default()
+}
+
+// Lenguaje de un archivo por nombre, extension o "#!" de la primera linea.
+// Las gramáticas cargadas tienen prioridad sobre los lenguajes incluidos.
+pub fn detect(path: Option<&str>, first_line: Option<&str>) -> Option<Syntax> {
+  let (file_name, extension) = match path.map(Path::new) {
+    Some(path) => (
+      path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+      path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default(),
+    ),
+    None => (String::new(), String::new()),
+  };
+
+  if let Some(grammar) = grammars().iter().find(|g| g.matches(&file_name, &extension, first_line)) {
+    return Some(Syntax::Grammar(grammar));
+  }
+
+  let found = LANGUAGES.iter().find(|def| def.file_names.contains(&file_name.as_str()))
+    .or_else(|| LANGUAGES.iter().find(|def| !extension.is_empty() && def.extensions.contains(&extension.as_str())));
+  if let Some(def) = found {
+    return Some(Syntax::Builtin(def));
+  }
+
+  let program = shebang_program(first_line?)?;
+  LANGUAGES.iter()
+    .find(|def| def.shebangs.iter().any(|s| program_matches(&program, s)))
+    .map(Syntax::Builtin)
+}
+
+impl Syntax {
+  pub fn name(&self) -> &'static str {
+    match self {
+      Syntax::Builtin(def) => def.name,
+      Syntax::Grammar(grammar) => &grammar.name,
+    }
+  }
+
+  pub fn word_chars(&self) -> &'static [char] {
+    match self {
+      Syntax::Builtin(def) => def.word_chars,
+      Syntax::Grammar(_) => &[],
+    }
+  }
+
+  fn initial_state(&self) -> State {
+    match self {
+      Syntax::Builtin(_) => State::Builtin(LineState::Normal),
+      Syntax::Grammar(grammar) => State::Grammar(grammar.initial_state()),
+    }
+  }
+
+  fn highlight(&self, line: &str, state: &State) -> (Vec<Scope>, State) {
+    match (self, state) {
+      (Syntax::Builtin(def), State::Builtin(state)) => {
+        let (scopes, next) = highlight_line(def, line, *state);
+        (scopes, State::Builtin(next))
+      }
+      (Syntax::Grammar(grammar), State::Grammar(stack)) => {
+        let mut stack = stack.clone();
+        let scopes = grammar.highlight_line(line, &mut stack);
+        (scopes, State::Grammar(stack))
+      }
+      // el cache era de otro lenguaje
+      _ => self.highlight(line, &self.initial_state()),
+    }
+  }
+
+  // Scopes de lines[from..from + count]. Las lineas anteriores solo se tokenizan si
+  // cambiaron desde el último llamado.
+  pub fn highlight_lines(&self, lines: &[String], from: usize, count: usize, cache: &mut HighlightCache) -> Vec<Vec<Scope>> {
+    let end = (from + count).min(lines.len());
+
+    let mut valid = 0;
+    while valid < cache.hashes.len() && valid < end && cache.hashes[valid] == hash_line(&lines[valid]) {
+      valid += 1;
+    }
+    cache.hashes.truncate(valid);
+    cache.states.truncate(valid + 1);
+    if cache.states.is_empty() {
+      cache.states.push(self.initial_state());
+    }
+
+    let mut out = Vec::with_capacity(end.saturating_sub(from));
+    for (i, line) in lines.iter().enumerate().take(end).skip(from.min(valid)) {
+      let (scopes, next) = self.highlight(line, &cache.states[i]);
+      if i >= cache.hashes.len() {
+        cache.hashes.push(hash_line(line));
+        cache.states.push(next);
+      }
+      if i >= from {
+        out.push(scopes);
+      }
+    }
+    out
+  }
+}
+
+fn hash_line(line: &str) -> u64 {
+  let mut hasher = DefaultHasher::new();
+  line.hash(&mut hasher);
+  hasher.finish()
+}
+
+// "#!/usr/bin/env -S python3 -u" -> "python3"
+fn shebang_program(line: &str) -> Option<String> {
+  let command = line.strip_prefix("#!")?;
+  let mut words = command.split_whitespace();
+  let mut program = words.next()?.rsplit('/').next()?;
+  if program == "env" {
+    program = words.find(|w| !w.starts_with('-') && !w.contains('='))?;
+  }
+  Some(program.to_string())
+}
+
+// "python3.11" coincide con "python"
+fn program_matches(program: &str, name: &str) -> bool {
+  program.strip_prefix(name)
+    .is_some_and(|rest| rest.chars().all(|c| c.is_ascii_digit() || c == '.'))
+}
+
+// Scope de cada caracter de la linea y estado para la siguiente
+pub fn highlight_line(def: &LanguageDef, line: &str, state: LineState) -> (Vec<Scope>, LineState) {
+  if let Some(custom) = def.custom {
+    return custom(line, state);
+  }
+
+  let chars: Vec<char> = line.chars().collect();
+  let mut scopes = vec![Scope::Plain; chars.len()];
+  let mut state = state;
+  let mut i = 0;
+
+  while i < chars.len() {
+    match state {
+      LineState::Comment(depth) => {
+        let (end, depth) = scan_block_comment(def, &chars, i, depth);
+        mark(&mut scopes, i, end, Scope::Comment);
+        i = end;
+        state = if depth == 0 { LineState::Normal } else { LineState::Comment(depth) };
+      }
+      LineState::String(idx) => {
+        let string = &def.strings[idx as usize];
+        let (end, closed) = scan_string(string, &chars, i);
+        mark(&mut scopes, i, end, Scope::String);
+        i = end;
+        if closed {
+          state = LineState::Normal;
+        }
+      }
+      LineState::Normal | LineState::CodeBlock => {
+        let (end, scope, next) = normal_token(def, &chars, i);
+        mark(&mut scopes, i, end, scope);
+        i = end.max(i + 1);
+        state = next;
+      }
+    }
+  }
+
+  // un string de una sola linea sin cerrar no sigue en la proxima
+  if let LineState::String(idx) = state
+    && !def.strings[idx as usize].multiline {
+    state = LineState::Normal;
+  }
+
+  (scopes, state)
+}
+
+fn mark(scopes: &mut [Scope], from: usize, to: usize, scope: Scope) {
+  for s in scopes.iter_mut().take(to).skip(from) {
+    *s = scope;
+  }
+}
+
+fn starts_with_at(chars: &[char], i: usize, pat: &str) -> bool {
+  pat.chars().enumerate().all(|(k, p)| chars.get(i + k) == Some(&p))
+}
+
+pub fn is_ident_char(c: char) -> bool {
+  c.is_alphanumeric() || c == '_'
+}
+
+// Un token a partir de `i` fuera de comentarios y strings: (fin, scope, estado siguiente)
+fn normal_token(def: &LanguageDef, chars: &[char], i: usize) -> (usize, Scope, LineState) {
+  let c = chars[i];
+  let normal = LineState::Normal;
+  let at_line_start = chars[..i].iter().all(|c| c.is_whitespace());
+
+  // variables de shell: $x, ${x}, $1, $#
+  if def.variable_prefix == Some(c) && i + 1 < chars.len() {
+    let end = if chars[i + 1] == '{' {
+      chars[i..].iter().position(|&c| c == '}').map_or(chars.len(), |p| i + p + 1)
+    } else if is_ident_char(chars[i + 1]) {
+      i + 1 + chars[i + 1..].iter().take_while(|c| is_ident_char(**c)).count()
+    } else {
+      i + 2
+    };
+    return (end, Scope::Variable, normal);
+  }
+
+  // [seccion] de TOML
+  if def.section_headers && at_line_start && c == '[' {
+    let end = chars.iter().rposition(|&c| c == ']').map_or(chars.len(), |p| p + 1).max(i + 1);
+    return (end, Scope::Heading, normal);
+  }
+
+  for comment in def.line_comments {
+    if starts_with_at(chars, i, comment) {
+      return (chars.len(), Scope::Comment, normal);
+    }
+  }
+
+  if let Some((open, _)) = def.block_comment
+    && starts_with_at(chars, i, open) {
+    let start = i + open.chars().count();
+    let (end, depth) = scan_block_comment(def, chars, start, 1);
+    let state = if depth == 0 { normal } else { LineState::Comment(depth) };
+    return (end, Scope::Comment, state);
+  }
+
+  for attribute in def.attributes {
+    if starts_with_at(chars, i, attribute) {
+      let start = i + attribute.chars().count();
+      let end = if attribute.ends_with('[') {
+        matching_close(chars, start, '[', ']')
+      } else {
+        start + chars[start..].iter().take_while(|c| is_ident_char(**c) || **c == '.').count()
+      };
+      if end > start {
+        return (end, Scope::Attribute, normal);
+      }
+    }
+  }
+
+  // caracter de Rust ('a', '\n') o lifetime ('a)
+  if def.lifetimes && c == '\'' {
+    let char_end = if chars.get(i + 1) == Some(&'\\') {
+      chars[i + 2..].iter().position(|&c| c == '\'').map(|p| i + 2 + p + 1)
+    } else if chars.get(i + 2) == Some(&'\'') {
+      Some(i + 3)
+    } else {
+      None
+    };
+    return match char_end {
+      Some(end) => (end, Scope::String, normal),
+      None => {
+        let end = i + 1 + chars[i + 1..].iter().take_while(|c| is_ident_char(**c)).count();
+        (end, Scope::Type, normal)
+      }
+    };
+  }
+
+  for (idx, string) in def.strings.iter().enumerate() {
+    if starts_with_at(chars, i, string.open) {
+      let start = i + string.open.chars().count();
+      let (end, closed) = scan_string(string, chars, start);
+      let state = if closed { normal } else { LineState::String(idx as u8) };
+      let scope = if closed && is_key(def, chars, i, end) { Scope::Key } else { Scope::String };
+      return (end, scope, state);
+    }
+  }
+
+  // numeros: 42, 0xff, 1_000, 3.14, 1e10
+  if c.is_ascii_digit() && (i == 0 || !is_ident_char(chars[i - 1])) {
+    let mut end = i + 1;
+    while end < chars.len()
+      && (is_ident_char(chars[end])
+        || (chars[end] == '.' && chars.get(end + 1).is_some_and(|c| c.is_ascii_digit()))) {
+      end += 1;
+    }
+    return (end, Scope::Number, normal);
+  }
+
+  if is_ident_char(c) {
+    let mut end = i + 1 + chars[i + 1..].iter().take_while(|c| is_ident_char(**c)).count();
+    // claves de TOML con guiones o puntos: nombre-largo.sub = 1
+    if def.key_separator == Some('=') {
+      end += chars[end..].iter().take_while(|c| is_ident_char(**c) || **c == '-' || **c == '.').count();
+    }
+    let word: String = chars[i..end].iter().collect();
+    let next = chars[end..].iter().find(|c| !c.is_whitespace());
+
+    let scope = if is_key(def, chars, i, end) {
+      Scope::Key
+    } else if def.keywords.contains(&word.as_str()) {
+      Scope::Keyword
+    } else if def.constants.contains(&word.as_str()) {
+      Scope::Constant
+    } else if def.types.contains(&word.as_str())
+      || (def.capitalized_types && c.is_uppercase()) {
+      Scope::Type
+    } else if def.functions && next == Some(&'(') {
+      Scope::Function
+    } else if def.lifetimes && chars.get(end) == Some(&'!') {
+      // macro de Rust: println!
+      end += 1;
+      Scope::Function
+    } else {
+      Scope::Plain
+    };
+    return (end, scope, normal);
+  }
+
+  (i + 1, Scope::Plain, normal)
+}
+
+// chars[start..end] es la clave de "clave = valor" (TOML) o "clave": valor (JSON).
+// Con '=' solo cuenta al principio de la linea o dentro de una tabla { a = 1, b = 2 }
+fn is_key(def: &LanguageDef, chars: &[char], start: usize, end: usize) -> bool {
+  let Some(separator) = def.key_separator else { return false };
+  let after = chars[end..].iter().find(|c| !c.is_whitespace());
+  let before = chars[..start].iter().rev().find(|c| !c.is_whitespace());
+  after == Some(&separator) && (separator != '=' || matches!(before, None | Some('{') | Some(',')))
+}
+
+// Avanza hasta el cierre de un string; devuelve (fin, si se cerró)
+fn scan_string(string: &StringDef, chars: &[char], from: usize) -> (usize, bool) {
+  let mut j = from;
+  while j < chars.len() {
+    if string.escape && chars[j] == '\\' {
+      j += 2;
+      continue;
+    }
+    if starts_with_at(chars, j, string.close) {
+      return (j + string.close.chars().count(), true);
+    }
+    j += 1;
+  }
+  (chars.len(), false)
+}
+
+// Avanza dentro de un comentario de bloque; devuelve (fin, profundidad restante)
+fn scan_block_comment(def: &LanguageDef, chars: &[char], from: usize, depth: u8) -> (usize, u8) {
+  let Some((open, close)) = def.block_comment else { return (chars.len(), 0) };
+  let mut depth = depth;
+  let mut j = from;
+  while j < chars.len() {
+    if starts_with_at(chars, j, close) {
+      j += close.chars().count();
+      depth -= 1;
+      if depth == 0 {
+        return (j, 0);
+      }
+    } else if def.nested_comments && starts_with_at(chars, j, open) {
+      j += open.chars().count();
+      depth = depth.saturating_add(1);
+    } else {
+      j += 1;
+    }
+  }
+  (chars.len(), depth)
+}
+
+// Posición después del `close` que cierra el `open` ya abierto antes de `from`
+fn matching_close(chars: &[char], from: usize, open: char, close: char) -> usize {
+  let mut depth = 1;
+  for (j, &c) in chars.iter().enumerate().skip(from) {
+    if c == open {
+      depth += 1;
+    } else if c == close {
+      depth -= 1;
+      if depth == 0 {
+        return j + 1;
+      }
+    }
+  }
+  chars.len()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn language(name: &str) -> &'static LanguageDef {
+    LANGUAGES.iter().find(|def| def.name == name).unwrap()
+  }
+
+  // una letra por char: . texto, k palabra clave, t tipo, c constante, n número,
+  // s string, / comentario, f función, a atributo, y clave, v variable, h título,
+  // m el resto de Markdown
+  fn letters(scopes: &[Scope]) -> String {
+    scopes.iter()
+      .map(|scope| match scope {
+        Scope::Plain => '.',
+        Scope::Keyword => 'k',
+        Scope::Type => 't',
+        Scope::Constant => 'c',
+        Scope::Number => 'n',
+        Scope::String => 's',
+        Scope::Comment => '/',
+        Scope::Function => 'f',
+        Scope::Attribute => 'a',
+        Scope::Key => 'y',
+        Scope::Variable => 'v',
+        Scope::Heading => 'h',
+        Scope::Emphasis | Scope::Code | Scope::Link => 'm',
+      })
+      .collect()
+  }
+
+  fn highlight(name: &str, line: &str, state: LineState) -> (String, LineState) {
+    let (scopes, state) = highlight_line(language(name), line, state);
+    (letters(&scopes), state)
+  }
+
+  #[test]
+  fn detect_by_name_extension_and_shebang() {
+    let name = |path: Option<&str>, first: Option<&str>| detect(path, first).map(|s| s.name());
+    assert_eq!(name(Some("src/main.rs"), None), Some("Rust"));
+    assert_eq!(name(Some("README.MD"), None), Some("Markdown"));
+    assert_eq!(name(Some("Cargo.lock"), None), Some("TOML"));
+    assert_eq!(name(Some("script"), Some("#!/usr/bin/env -S python3.11 -u")), Some("Python"));
+    assert_eq!(name(None, Some("#!/bin/bash")), Some("Shell"));
+    assert_eq!(name(Some("estilo.scss"), None), Some("CSS"));
+    assert_eq!(name(Some("/home/ana/.emacs"), None), Some("Lisp"));
+    assert_eq!(name(Some("notas.txt"), Some("hola")), None);
+  }
+
+  #[test]
+  fn shebang_program_skips_env_options() {
+    assert_eq!(shebang_program("#!/usr/bin/env -S FOO=1 node").as_deref(), Some("node"));
+    assert_eq!(shebang_program("#!/bin/sh").as_deref(), Some("sh"));
+    assert_eq!(shebang_program("# no"), None);
+    assert!(program_matches("python3.11", "python"));
+    assert!(!program_matches("pythonista", "python"));
+  }
+
+  #[test]
+  fn lisp_tokens_and_word_chars() {
+    assert_eq!(highlight("Lisp", "(defun mi-f () nil) ; fin", LineState::Normal).0, ".kkkkk.........ccc../////");
+    assert!(language("Lisp").word_chars.contains(&'-'));
+    assert_eq!(language("CSS").word_chars, ['-']);
+  }
+
+  #[test]
+  fn rust_tokens() {
+    assert_eq!(highlight("Rust", "let x = 42;", LineState::Normal).0, "kkk.....nn.");
+    assert_eq!(highlight("Rust", "fn f(s: &str) -> Option<u8>", LineState::Normal).0, "kk.f.....ttt.....tttttt.tt.");
+    assert_eq!(highlight("Rust", "x(\"a\\\"b\") // fin", LineState::Normal).0, "f.ssssss..//////");
+    assert_eq!(highlight("Rust", "#[test] 'a'", LineState::Normal).0, "aaaaaaa.sss");
+  }
+
+  #[test]
+  fn rust_lifetimes_are_not_chars() {
+    let (line, _) = highlight("Rust", "&'a str", LineState::Normal);
+    assert_eq!(&line[4..], "ttt");
+    assert!(!line.contains('s'));
+  }
+
+  #[test]
+  fn block_comments_and_strings_continue_on_the_next_line() {
+    let (line, state) = highlight("Rust", "a /* b /* c */", LineState::Normal);
+    assert_eq!(line, "..////////////");
+    assert_eq!(state, LineState::Comment(1));
+    let (line, state) = highlight("Rust", "d */ e", state);
+    assert_eq!(line, "////..");
+    assert_eq!(state, LineState::Normal);
+
+    let (line, state) = highlight("Rust", "let s = \"uno", LineState::Normal);
+    assert_eq!(line, "kkk.....ssss");
+    let (line, state) = highlight("Rust", "dos\";", state);
+    assert_eq!((line.as_str(), state), ("ssss.", LineState::Normal));
+  }
+
+  #[test]
+  fn single_line_strings_do_not_continue() {
+    let (_, state) = highlight("JSON", "{\"a", LineState::Normal);
+    assert_eq!(state, LineState::Normal);
+  }
+
+  #[test]
+  fn toml_keys_and_sections() {
+    assert_eq!(highlight("TOML", "[package]", LineState::Normal).0, "hhhhhhhhh");
+    assert_eq!(highlight("TOML", "name = \"x\" # c", LineState::Normal).0, "yyyy...sss.///");
+  }
+
+  #[test]
+  fn highlight_lines_cache_follows_edits() {
+    let syntax = Syntax::Builtin(language("Rust"));
+    let mut lines: Vec<String> = ["a", "/* b", "c */", "d"].iter().map(|l| l.to_string()).collect();
+    let mut cache = HighlightCache::default();
+    let fresh = |lines: &[String]| syntax.highlight_lines(lines, 0, lines.len(), &mut HighlightCache::default());
+
+    let visible = syntax.highlight_lines(&lines, 3, 1, &mut cache);
+    assert_eq!(letters(&visible[0]), ".");
+
+    // cerrar el comentario antes cambia las lineas de abajo aunque ya estuvieran guardadas
+    lines[1] = "/* b */".to_string();
+    let visible = syntax.highlight_lines(&lines, 2, 2, &mut cache);
+    assert_eq!(visible.iter().map(|s| letters(s)).collect::<Vec<_>>(), vec!["....", "."]);
+    assert_eq!(syntax.highlight_lines(&lines, 0, 4, &mut cache), fresh(&lines));
+  }
+}
@@ -0,0 +1,684 @@
+use std::fs;
+use std::io::stdout;
+use std::path::PathBuf;
+
+use crossterm::execute;
+use crossterm::style::{ Attribute, Attributes, Color, ResetColor, SetAttribute, SetAttributes, SetBackgroundColor, SetForegroundColor };
+
+use crate::file::app_dir;
+use crate::syntax::Scope;
+
+// Temas de colores: un estilo para cada parte de la interfaz y cada scope del
+// resaltado. Los temas incluidos están escritos en el mismo formato que los del
+// usuario (~/.camelCaseEditor/themes/<nombre>.toml), una "clave = estilo" por linea:
+//
+//   base = dark                  # tema del que parte (por defecto dark)
+//   title = black on red bold    # [color] [on color] [bold dim italic underline reverse]
+//   keyword = #c678dd            # nombre, #rrggbb o numero 0-255
+//
+// Los colores se adaptan a lo que soporta la terminal y con NO_COLOR solo se usan
+// atributos (negrita, invertido...).
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct Style {
+  pub fg: Option<Color>,
+  pub bg: Option<Color>,
+  pub attrs: Attributes,
+}
+
+impl Style {
+  // el próximo reset() lo deshace
+  pub fn apply(&self) -> std::io::Result<()> {
+    if let Some(fg) = self.fg {
+      execute!(stdout(), SetForegroundColor(fg))?;
+    }
+    if let Some(bg) = self.bg {
+      execute!(stdout(), SetBackgroundColor(bg))?;
+    }
+    if !self.attrs.is_empty() {
+      execute!(stdout(), SetAttributes(self.attrs))?;
+    }
+    Ok(())
+  }
+
+  pub fn is_plain(&self) -> bool {
+    *self == Style::default()
+  }
+}
+
+pub fn reset() -> std::io::Result<()> {
+  execute!(stdout(), ResetColor, SetAttribute(Attribute::Reset))
+}
+
+// cantidad de variantes de Scope
+const SCOPES: usize = 15;
+
+const SCOPE_KEYS: [(&str, Scope); SCOPES] = [
+  ("text", Scope::Plain),
+  ("keyword", Scope::Keyword),
+  ("type", Scope::Type),
+  ("constant", Scope::Constant),
+  ("number", Scope::Number),
+  ("string", Scope::String),
+  ("comment", Scope::Comment),
+  ("function", Scope::Function),
+  ("attribute", Scope::Attribute),
+  ("key", Scope::Key),
+  ("variable", Scope::Variable),
+  ("heading", Scope::Heading),
+  ("emphasis", Scope::Emphasis),
+  ("code", Scope::Code),
+  ("link", Scope::Link),
+];
+
+#[derive(Clone, Default)]
+pub struct Theme {
+  pub name: String,
+  pub title: Style,           // barra de título
+  pub status: Style,          // linea de estado
+  pub tab_active: Style,
+  pub tab_inactive: Style,
+  pub pane_focused: Style,    // título del panel enfocado
+  pub pane_inactive: Style,
+  pub border: Style,          // separadores y bordes de popups
+  pub scroll_marker: Style,   // indicadores < > de texto oculto
+  pub selection: Style,
+  pub cursor: Style,          // cursores además del principal
+  pub bracket_match: Style,   // paréntesis junto al cursor y su pareja
+  pub bracket_unmatched: Style,
+  pub popup_selected: Style,  // item elegido en las listas
+  pub message_info: Style,
+  pub message_warning: Style,
+  pub message_error: Style,
+  pub line_number: Style,
+  pub line_number_current: Style,
+  pub added: Style,           // marcas de cambios en el margen
+  pub modified: Style,
+  pub deleted: Style,
+  pub ruler: Style,           // guía de largo máximo de linea
+  scopes: [Style; SCOPES],
+}
+
+const DARK: &str = "
+title = black on red
+status = black on red
+tab_active = black on white
+tab_inactive = darkgrey
+pane_focused = black on red
+pane_inactive = black on darkgrey
+border = darkred
+scroll_marker = black on darkred
+selection = black on darkgrey
+cursor = black on white
+bracket_match = white on darkblue
+bracket_unmatched = white on darkred
+popup_selected = black on white
+message_info = white
+message_warning = black on yellow
+message_error = white on darkred
+line_number = darkgrey
+line_number_current = white
+added = green
+modified = yellow
+deleted = red
+ruler = darkgrey
+keyword = magenta
+type = yellow
+constant = cyan
+number = cyan
+string = green
+comment = darkgrey
+function = blue
+attribute = darkyellow
+key = blue
+variable = darkcyan
+heading = red
+emphasis = darkyellow
+code = green
+link = blue
+";
+
+// para terminales de fondo claro
+const LIGHT: &str = "
+title = white on darkblue
+status = white on darkblue
+tab_active = white on darkblue
+tab_inactive = darkgrey
+pane_focused = white on darkblue
+pane_inactive = black on grey
+border = darkblue
+scroll_marker = white on darkblue
+selection = black on grey
+cursor = white on black
+bracket_match = black on cyan
+bracket_unmatched = white on red
+popup_selected = white on darkblue
+message_info = black
+message_warning = black on yellow
+message_error = white on darkred
+line_number = darkgrey
+line_number_current = black bold
+added = darkgreen
+modified = darkyellow
+deleted = darkred
+ruler = grey
+keyword = darkmagenta
+type = darkyellow
+constant = darkcyan
+number = darkcyan
+string = darkgreen
+comment = darkgrey
+function = darkblue
+attribute = darkyellow
+key = darkblue
+variable = darkcyan
+heading = darkred bold
+emphasis = darkmagenta
+code = darkgreen
+link = darkblue underline
+";
+
+const HIGH_CONTRAST: &str = "
+title = black on yellow bold
+status = black on yellow bold
+tab_active = black on white bold
+tab_inactive = white
+pane_focused = black on yellow bold
+pane_inactive = black on white
+border = yellow
+scroll_marker = black on yellow
+selection = black on cyan
+cursor = black on white bold
+bracket_match = black on magenta
+bracket_unmatched = white on red bold
+popup_selected = black on yellow
+message_info = white bold
+message_warning = black on yellow bold
+message_error = white on red bold
+line_number = white
+line_number_current = yellow bold
+added = green bold
+modified = yellow bold
+deleted = red bold
+ruler = white
+keyword = yellow bold
+type = cyan bold
+constant = magenta bold
+number = magenta bold
+string = green
+comment = cyan
+function = white bold
+attribute = yellow
+key = cyan
+variable = white underline
+heading = yellow bold
+emphasis = white bold
+code = green
+link = cyan underline
+";
+
+// sin colores, solo atributos; también se usa con NO_COLOR
+const MONOCHROME: &str = "
+title = reverse
+status = reverse
+tab_active = reverse
+tab_inactive = dim
+pane_focused = reverse
+pane_inactive = dim
+border = none
+scroll_marker = reverse
+selection = reverse
+cursor = reverse
+bracket_match = bold underline
+bracket_unmatched = reverse bold
+popup_selected = reverse
+message_info = bold
+message_warning = reverse
+message_error = reverse bold
+line_number = dim
+line_number_current = bold
+added = bold
+modified = none
+deleted = bold
+ruler = dim
+keyword = bold
+type = none
+constant = none
+number = none
+string = none
+comment = dim
+function = none
+attribute = none
+key = none
+variable = none
+heading = bold
+emphasis = italic
+code = none
+link = underline
+";
+
+const BUNDLED: [(&str, &str); 4] = [
+  ("dark", DARK),
+  ("light", LIGHT),
+  ("high-contrast", HIGH_CONTRAST),
+  ("monochrome", MONOCHROME),
+];
+
+impl Theme {
+  pub fn scope(&self, scope: Scope) -> Style {
+    self.scopes[scope as usize]
+  }
+
+  // Tema por nombre: primero ~/.camelCaseEditor/themes/<nombre>.toml, después los incluidos
+  pub fn load(name: &str) -> Result<Theme, String> {
+    let depth = ColorDepth::detect();
+    if depth == ColorDepth::None {
+      return Ok(Theme::parse("monochrome", MONOCHROME, None)?.adapt(depth));
+    }
+
+    let file = themes_dir().join(format!("{}.toml", name));
+    let theme = if file.is_file() {
+      let content = fs::read_to_string(&file).map_err(|e| format!("{}: {}", file.display(), e))?;
+      Theme::parse(name, &content, Some("dark")).map_err(|e| format!("{}: {}", file.display(), e))?
+    } else {
+      let (_, content) = BUNDLED.iter().find(|(n, _)| *n == name)
+        .ok_or_else(|| format!("tema desconocido '{}'", name))?;
+      Theme::parse(name, content, None)?
+    };
+
+    Ok(theme.adapt(depth))
+  }
+
+  // Temas disponibles: los incluidos y después los del usuario, por nombre
+  pub fn names() -> Vec<String> {
+    let mut names: Vec<String> = BUNDLED.iter().map(|(n, _)| n.to_string()).collect();
+
+    let mut user: Vec<String> = fs::read_dir(themes_dir()).into_iter().flatten().flatten()
+      .map(|entry| entry.path())
+      .filter(|path| path.extension().is_some_and(|e| e == "toml"))
+      .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+      .filter(|name| !names.contains(name))
+      .collect();
+    user.sort();
+
+    names.extend(user);
+    names
+  }
+
+  // El tema que sigue a éste en names(); los que fallan al cargar se saltean
+  pub fn next(&self) -> Result<Theme, String> {
+    let names = Theme::names();
+    let current = names.iter().position(|n| *n == self.name).unwrap_or(names.len() - 1);
+    let mut first_error = None;
+
+    for offset in 1..=names.len() {
+      match Theme::load(&names[(current + offset) % names.len()]) {
+        Ok(theme) => return Ok(theme),
+        Err(e) => { first_error.get_or_insert(e); }
+      }
+    }
+    Err(first_error.unwrap_or_else(|| "no hay temas".to_string()))
+  }
+
+  // `base`: tema del que se parte si el archivo no dice otro (None = vacío)
+  fn parse(name: &str, content: &str, base: Option<&str>) -> Result<Theme, String> {
+    let mut lines: Vec<(usize, &str, &str)> = Vec::new();
+    let mut base = base;
+
+    for (n, raw_line) in content.lines().enumerate() {
+      let line = strip_comment(raw_line).trim();
+      if line.is_empty() || line.starts_with('[') {
+        continue;
+      }
+      let (key, value) = line.split_once('=')
+        .ok_or_else(|| format!("linea {}: se esperaba clave = estilo", n + 1))?;
+      let (key, value) = (key.trim(), value.trim().trim_matches('"'));
+
+      if key == "base" {
+        base = Some(value);
+      } else {
+        lines.push((n, key, value));
+      }
+    }
+
+    let mut theme = match base {
+      Some(base) => {
+        let (_, base_content) = BUNDLED.iter().find(|(n, _)| *n == base)
+          .ok_or_else(|| format!("tema base desconocido '{}'", base))?;
+        Theme::parse(base, base_content, None)?
+      }
+      None => Theme::default(),
+    };
+    theme.name = name.to_string();
+
+    for (n, key, value) in lines {
+      let style = parse_style(value).map_err(|e| format!("linea {}: {}", n + 1, e))?;
+      let slot = theme.slot(key).ok_or_else(|| format!("linea {}: clave desconocida '{}'", n + 1, key))?;
+      *slot = style;
+    }
+
+    Ok(theme)
+  }
+
+  fn slot(&mut self, key: &str) -> Option<&mut Style> {
+    let slot = match key {
+      "title" => &mut self.title,
+      "status" => &mut self.status,
+      "tab_active" => &mut self.tab_active,
+      "tab_inactive" => &mut self.tab_inactive,
+      "pane_focused" => &mut self.pane_focused,
+      "pane_inactive" => &mut self.pane_inactive,
+      "border" => &mut self.border,
+      "scroll_marker" => &mut self.scroll_marker,
+      "selection" => &mut self.selection,
+      "cursor" => &mut self.cursor,
+      "bracket_match" => &mut self.bracket_match,
+      "bracket_unmatched" => &mut self.bracket_unmatched,
+      "popup_selected" => &mut self.popup_selected,
+      "message_info" => &mut self.message_info,
+      "message_warning" => &mut self.message_warning,
+      "message_error" => &mut self.message_error,
+      "line_number" => &mut self.line_number,
+      "line_number_current" => &mut self.line_number_current,
+      "added" => &mut self.added,
+      "modified" => &mut self.modified,
+      "deleted" => &mut self.deleted,
+      "ruler" => &mut self.ruler,
+      _ => {
+        let (_, scope) = SCOPE_KEYS.iter().find(|(k, _)| *k == key)?;
+        &mut self.scopes[*scope as usize]
+      }
+    };
+    Some(slot)
+  }
+
+  fn styles_mut(&mut self) -> impl Iterator<Item = &mut Style> {
+    [
+      &mut self.title, &mut self.status, &mut self.tab_active, &mut self.tab_inactive,
+      &mut self.pane_focused, &mut self.pane_inactive, &mut self.border, &mut self.scroll_marker,
+      &mut self.selection, &mut self.cursor, &mut self.bracket_match, &mut self.bracket_unmatched,
+      &mut self.popup_selected, &mut self.message_info,
+      &mut self.message_warning, &mut self.message_error, &mut self.line_number,
+      &mut self.line_number_current, &mut self.added, &mut self.modified, &mut self.deleted,
+      &mut self.ruler,
+    ].into_iter().chain(self.scopes.iter_mut())
+  }
+
+  // colores que la terminal no soporta se cambian por el más parecido
+  fn adapt(mut self, depth: ColorDepth) -> Theme {
+    for style in self.styles_mut() {
+      style.fg = style.fg.and_then(|c| depth.convert(c));
+      style.bg = style.bg.and_then(|c| depth.convert(c));
+    }
+    self
+  }
+}
+
+fn themes_dir() -> PathBuf {
+  app_dir().join("themes")
+}
+
+// '#' abre un comentario al principio o seguido de un espacio; "#rrggbb" es un color
+fn strip_comment(line: &str) -> &str {
+  let bytes = line.as_bytes();
+  for (i, &b) in bytes.iter().enumerate() {
+    if b == b'#' && (i == 0 || bytes.get(i + 1).is_none_or(|n| n.is_ascii_whitespace())) {
+      return &line[..i];
+    }
+  }
+  line
+}
+
+// "[color] [on color] [atributos...]"; "none" no cambia el color de la terminal
+fn parse_style(value: &str) -> Result<Style, String> {
+  let mut style = Style::default();
+  let mut words = value.split_whitespace();
+  let mut fg_set = false;
+
+  while let Some(word) = words.next() {
+    let word = word.to_lowercase();
+    let attribute = match word.as_str() {
+      "bold" => Some(Attribute::Bold),
+      "dim" => Some(Attribute::Dim),
+      "italic" => Some(Attribute::Italic),
+      "underline" => Some(Attribute::Underlined),
+      "reverse" => Some(Attribute::Reverse),
+      _ => None,
+    };
+
+    if let Some(attribute) = attribute {
+      style.attrs.set(attribute);
+    } else if word == "on" {
+      let color = words.next().ok_or("falta el color despues de 'on'")?;
+      style.bg = parse_color(color)?;
+    } else if !fg_set {
+      style.fg = parse_color(&word)?;
+      fg_set = true;
+    } else {
+      return Err(format!("no se entiende '{}'", word));
+    }
+  }
+
+  Ok(style)
+}
+
+fn parse_color(value: &str) -> Result<Option<Color>, String> {
+  let value = value.to_lowercase();
+  if let Some(hex) = value.strip_prefix('#')
+    && hex.len() == 6
+    && let Ok(rgb) = u32::from_str_radix(hex, 16) {
+    return Ok(Some(Color::Rgb { r: (rgb >> 16) as u8, g: (rgb >> 8) as u8, b: rgb as u8 }));
+  }
+  if let Ok(n) = value.parse::<u8>() {
+    return Ok(Some(Color::AnsiValue(n)));
+  }
+
+  let color = match value.replace(['_', '-'], "").as_str() {
+    "none" | "default" => return Ok(None),
+    "black" => Color::Black,
+    "darkgrey" | "darkgray" => Color::DarkGrey,
+    "red" => Color::Red,
+    "darkred" => Color::DarkRed,
+    "green" => Color::Green,
+    "darkgreen" => Color::DarkGreen,
+    "yellow" => Color::Yellow,
+    "darkyellow" => Color::DarkYellow,
+    "blue" => Color::Blue,
+    "darkblue" => Color::DarkBlue,
+    "magenta" => Color::Magenta,
+    "darkmagenta" => Color::DarkMagenta,
+    "cyan" => Color::Cyan,
+    "darkcyan" => Color::DarkCyan,
+    "white" => Color::White,
+    "grey" | "gray" => Color::Grey,
+    _ => return Err(format!("color invalido '{}'", value)),
+  };
+  Ok(Some(color))
+}
+
+// Colores que soporta la terminal
+#[derive(Clone, Copy, PartialEq)]
+pub enum ColorDepth {
+  None,      // NO_COLOR o terminal "dumb"
+  Ansi16,
+  Ansi256,
+  TrueColor,
+}
+
+// los 16 colores básicos con su valor aproximado, en orden ANSI
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+  (Color::Black, (0, 0, 0)),
+  (Color::DarkRed, (128, 0, 0)),
+  (Color::DarkGreen, (0, 128, 0)),
+  (Color::DarkYellow, (128, 128, 0)),
+  (Color::DarkBlue, (0, 0, 128)),
+  (Color::DarkMagenta, (128, 0, 128)),
+  (Color::DarkCyan, (0, 128, 128)),
+  (Color::Grey, (192, 192, 192)),
+  (Color::DarkGrey, (128, 128, 128)),
+  (Color::Red, (255, 0, 0)),
+  (Color::Green, (0, 255, 0)),
+  (Color::Yellow, (255, 255, 0)),
+  (Color::Blue, (0, 0, 255)),
+  (Color::Magenta, (255, 0, 255)),
+  (Color::Cyan, (0, 255, 255)),
+  (Color::White, (255, 255, 255)),
+];
+
+impl ColorDepth {
+  pub fn detect() -> ColorDepth {
+    let var = |name: &str| std::env::var(name).unwrap_or_default();
+
+    // https://no-color.org: cualquier valor no vacío
+    if !var("NO_COLOR").is_empty() || var("TERM") == "dumb" {
+      return ColorDepth::None;
+    }
+
+    let colorterm = var("COLORTERM").to_lowercase();
+    if colorterm == "truecolor" || colorterm == "24bit" || !var("WT_SESSION").is_empty() {
+      ColorDepth::TrueColor
+    } else if var("TERM").contains("256color") {
+      ColorDepth::Ansi256
+    } else {
+      ColorDepth::Ansi16
+    }
+  }
+
+  fn convert(self, color: Color) -> Option<Color> {
+    match (self, color) {
+      (ColorDepth::None, _) => None,
+      (ColorDepth::TrueColor, _) => Some(color),
+      (ColorDepth::Ansi256, Color::Rgb { r, g, b }) => Some(Color::AnsiValue(nearest_256(r, g, b))),
+      (ColorDepth::Ansi16, Color::Rgb { r, g, b }) => Some(nearest_16(r, g, b)),
+      (ColorDepth::Ansi16, Color::AnsiValue(n)) => {
+        let (r, g, b) = ansi_to_rgb(n);
+        Some(nearest_16(r, g, b))
+      }
+      _ => Some(color),
+    }
+  }
+}
+
+// cubo de 6x6x6 (16-231) o escala de grises (232-255), lo que quede más cerca
+fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+  let level = |v: u8| ((v as u16 * 5 + 127) / 255) as u8;
+  let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+
+  let average = (r as u16 + g as u16 + b as u16) / 3;
+  let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+  if distance((r, g, b), ansi_to_rgb(gray)) < distance((r, g, b), ansi_to_rgb(cube)) {
+    gray
+  } else {
+    cube
+  }
+}
+
+fn nearest_16(r: u8, g: u8, b: u8) -> Color {
+  ANSI16.iter()
+    .min_by_key(|(_, rgb)| distance((r, g, b), *rgb))
+    .map(|(color, _)| *color)
+    .unwrap_or(Color::Reset)
+}
+
+fn ansi_to_rgb(n: u8) -> (u8, u8, u8) {
+  match n {
+    0..=15 => ANSI16[n as usize].1,
+    16..=231 => {
+      let step = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+      let n = n - 16;
+      (step(n / 36), step(n / 6 % 6), step(n % 6))
+    }
+    _ => {
+      let v = 8 + (n - 232) * 10;
+      (v, v, v)
+    }
+  }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+  let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+  d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn style(fg: Option<Color>, bg: Option<Color>, attrs: &[Attribute]) -> Style {
+    let mut style = Style { fg, bg, ..Style::default() };
+    for &attr in attrs {
+      style.attrs.set(attr);
+    }
+    style
+  }
+
+  #[test]
+  fn parse_style_words() {
+    assert!(parse_style("black on red bold").unwrap() == style(Some(Color::Black), Some(Color::Red), &[Attribute::Bold]));
+    assert!(parse_style("on dark_blue").unwrap() == style(None, Some(Color::DarkBlue), &[]));
+    assert!(parse_style("reverse underline").unwrap() == style(None, None, &[Attribute::Reverse, Attribute::Underlined]));
+    assert!(parse_style("none on 236").unwrap() == style(None, Some(Color::AnsiValue(236)), &[]));
+    assert!(parse_style("").unwrap().is_plain());
+  }
+
+  #[test]
+  fn parse_style_errors() {
+    assert!(parse_style("red blue").is_err());
+    assert!(parse_style("red on").is_err());
+    assert!(parse_style("rojo").is_err());
+  }
+
+  #[test]
+  fn parse_color_formats() {
+    assert!(parse_color("#C678dd").unwrap() == Some(Color::Rgb { r: 0xc6, g: 0x78, b: 0xdd }));
+    assert!(parse_color("Dark-Grey").unwrap() == Some(Color::DarkGrey));
+    assert!(parse_color("default").unwrap().is_none());
+    assert!(parse_color("#12345").is_err());
+    assert!(parse_color("256").is_err());
+  }
+
+  #[test]
+  fn comments_but_not_colors() {
+    assert_eq!(strip_comment("keyword = #c678dd # violeta"), "keyword = #c678dd ");
+    assert_eq!(strip_comment("# todo"), "");
+    assert_eq!(strip_comment("title = red #"), "title = red ");
+  }
+
+  #[test]
+  fn parse_theme_from_base() {
+    let dark = Theme::parse("dark", BUNDLED.iter().find(|(n, _)| *n == "dark").unwrap().1, None).unwrap();
+    let theme = Theme::parse("mio", "[colores]\nbase = \"dark\"\nkeyword = \"bold\"\n", None).unwrap();
+    assert_eq!(theme.name, "mio");
+    assert!(theme.scope(Scope::Keyword) == style(None, None, &[Attribute::Bold]));
+    // lo que no se cambia queda como en la base
+    assert!(theme.selection == dark.selection);
+    assert!(theme.scope(Scope::String) == dark.scope(Scope::String));
+  }
+
+  #[test]
+  fn parse_theme_errors_name_the_line() {
+    let error = |content: &str| Theme::parse("x", content, Some("dark")).err().unwrap_or_default();
+    assert_eq!(error("title = red\ncolor"), "linea 2: se esperaba clave = estilo");
+    assert_eq!(error("nada = red"), "linea 1: clave desconocida 'nada'");
+    assert_eq!(error("\ntitle = rojo"), "linea 2: color invalido 'rojo'");
+    assert_eq!(error("base = nada"), "tema base desconocido 'nada'");
+  }
+
+  #[test]
+  fn bundled_themes_parse() {
+    for (name, content) in BUNDLED {
+      assert!(Theme::parse(name, content, None).is_ok(), "{}", name);
+    }
+  }
+
+  #[test]
+  fn colors_adapt_to_the_terminal() {
+    assert_eq!(nearest_256(255, 0, 0), 196);
+    assert_eq!(nearest_256(128, 128, 128), 244);
+    assert!(nearest_16(250, 10, 10) == Color::Red);
+    assert!(ColorDepth::Ansi16.convert(Color::AnsiValue(196)) == Some(Color::Red));
+    assert!(ColorDepth::None.convert(Color::Red).is_none());
+    assert!(ColorDepth::TrueColor.convert(Color::Rgb { r: 1, g: 2, b: 3 }) == Some(Color::Rgb { r: 1, g: 2, b: 3 }));
+  }
+}
@@ -1,3 +1,6 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
 pub fn char_to_byte_idx(s: &str, char_idx: usize) -> usize {
   s.char_indices()
     .nth(char_idx)
@@ -27,4 +30,117 @@ pub fn get_windows_clipboard() -> Option<String> {
 #[cfg(not(windows))]
 pub fn get_windows_clipboard() -> Option<String> {
   None
-}
\ No newline at end of file
+}
+
+// Grafemas y columnas de pantalla
+
+// Ancho en pantalla de un cluster de grafemas (é, 👍🏽, 中) que empieza en la columna
+// `col`: los tabs llegan hasta el próximo tab stop y lo que no ocupa lugar vale 1,
+// porque se dibuja con un reemplazo visible
+pub fn grapheme_width(g: &str, col: usize, tab_width: usize) -> usize {
+  if g == "\t" {
+    let tab_width = tab_width.max(1);
+    tab_width - col % tab_width
+  } else {
+    g.width().max(1)
+  }
+}
+
+// Clusters de la linea con el índice (en chars) donde empieza cada uno
+pub fn graphemes(s: &str) -> impl Iterator<Item = (usize, &str)> {
+  s.graphemes(true).scan(0, |char_idx, g| {
+    let start = *char_idx;
+    *char_idx += g.chars().count();
+    Some((start, g))
+  })
+}
+
+// Columna de pantalla del char `char_idx`
+pub fn display_col(s: &str, char_idx: usize, tab_width: usize) -> usize {
+  graphemes(s)
+    .take_while(|(start, _)| *start < char_idx)
+    .fold(0, |col, (_, g)| col + grapheme_width(g, col, tab_width))
+}
+
+// Char donde empieza el cluster que ocupa la columna `col` (o el final de la linea)
+pub fn col_to_char(s: &str, col: usize, tab_width: usize) -> usize {
+  let mut current = 0;
+  for (start, g) in graphemes(s) {
+    current += grapheme_width(g, current, tab_width);
+    if current > col {
+      return start;
+    }
+  }
+  line_len_chars(s)
+}
+
+// Principio del cluster siguiente a `x` (o el final de la linea)
+pub fn next_grapheme(s: &str, x: usize) -> usize {
+  graphemes(s).map(|(start, _)| start).find(|start| *start > x).unwrap_or_else(|| line_len_chars(s))
+}
+
+// Principio del cluster anterior a `x`
+pub fn prev_grapheme(s: &str, x: usize) -> usize {
+  graphemes(s).map(|(start, _)| start).take_while(|start| *start < x).last().unwrap_or(0)
+}
+
+// `x` llevado al principio del cluster que lo contiene, para que el cursor no quede
+// entre una letra y su acento
+pub fn grapheme_floor(s: &str, x: usize) -> usize {
+  graphemes(s).map(|(start, _)| start).take_while(|start| *start <= x).last().unwrap_or(0)
+}
+
+// `x` llevado al final del cluster que lo contiene
+pub fn grapheme_ceil(s: &str, x: usize) -> usize {
+  let floor = grapheme_floor(s, x);
+  if floor == x { x } else { next_grapheme(s, floor) }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // "e" + tilde combinante, un emoji con tono de piel y un ideograma de ancho 2
+  const LINE: &str = "ae\u{301}👍🏽中\tz";
+
+  #[test]
+  fn char_indexes() {
+    assert_eq!(char_to_byte_idx("añb", 2), 3);
+    assert_eq!(char_to_byte_idx("añb", 9), 4);
+    assert_eq!(line_len_chars(LINE), 8);
+  }
+
+  #[test]
+  fn clusters_start_at_char_indexes() {
+    let starts: Vec<(usize, &str)> = graphemes(LINE).collect();
+    assert_eq!(starts, [(0, "a"), (1, "e\u{301}"), (3, "👍🏽"), (5, "中"), (6, "\t"), (7, "z")]);
+  }
+
+  #[test]
+  fn widths_and_tab_stops() {
+    assert_eq!(grapheme_width("中", 0, 4), 2);
+    assert_eq!(grapheme_width("\t", 1, 4), 3);
+    assert_eq!(grapheme_width("\t", 4, 4), 4);
+    assert_eq!(grapheme_width("\t", 3, 0), 1);
+    // los de ancho cero se dibujan con un reemplazo de 1
+    assert_eq!(grapheme_width("\u{200B}", 0, 4), 1);
+  }
+
+  #[test]
+  fn columns_and_chars() {
+    let cols: Vec<usize> = [0, 1, 3, 5, 6, 7, 8].iter().map(|&x| display_col(LINE, x, 4)).collect();
+    assert_eq!(cols, [0, 1, 2, 4, 6, 8, 9]);
+
+    // una columna en el medio de un cluster ancho o de un tab da su principio
+    let chars: Vec<usize> = (0..10).map(|col| col_to_char(LINE, col, 4)).collect();
+    assert_eq!(chars, [0, 1, 3, 3, 5, 5, 6, 6, 7, 8]);
+  }
+
+  #[test]
+  fn cursor_snaps_to_clusters() {
+    assert_eq!((next_grapheme(LINE, 1), next_grapheme(LINE, 7), next_grapheme(LINE, 8)), (3, 8, 8));
+    assert_eq!((prev_grapheme(LINE, 3), prev_grapheme(LINE, 4), prev_grapheme(LINE, 0)), (1, 3, 0));
+    assert_eq!((grapheme_floor(LINE, 2), grapheme_floor(LINE, 3)), (1, 3));
+    assert_eq!((grapheme_ceil(LINE, 2), grapheme_ceil(LINE, 4), grapheme_ceil(LINE, 5)), (3, 5, 5));
+  }
+}
@@ -0,0 +1,176 @@
+// Clases de caracteres para moverse y borrar por palabras (Ctrl + Flechas). Sigue a
+// grandes rasgos las reglas de limites de palabra de Unicode (UAX #29): letras, números,
+// '_' y marcas combinantes forman palabras, un apóstrofo entre letras (l'été, don't) y un
+// punto entre dígitos (3.14) no cortan la palabra, y el resto de la puntuación (¿ ¡ « »
+// incluidos) forma grupos aparte. A diferencia de UAX #29, el punto y los dos puntos
+// entre letras sí cortan, porque en código separan (objeto.campo, std::fs).
+// Cada lenguaje puede sumar puntuación que une palabras ('-' en CSS, '$' en shell).
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CharClass {
+  Word,
+  Whitespace,
+  Punctuation,
+}
+
+pub fn char_class(c: char, word_chars: &[char]) -> CharClass {
+  if c.is_alphanumeric() || c == '_' || word_chars.contains(&c) {
+    CharClass::Word
+  } else if c.is_whitespace() {
+    CharClass::Whitespace
+  } else {
+    CharClass::Punctuation
+  }
+}
+
+// Marcas combinantes, selectores de variante y unión de emojis: van con el caracter
+// anterior, sea cual sea su clase (á escrita como a + ´, 👍🏽)
+fn is_extend(c: char) -> bool {
+  matches!(c as u32,
+    0x0300..=0x036F | 0x0483..=0x0489 | 0x0591..=0x05BD | 0x0610..=0x061A | 0x064B..=0x065F
+    | 0x0E31 | 0x0E34..=0x0E3A | 0x0E47..=0x0E4E | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF
+    | 0x200C..=0x200D | 0x20D0..=0x20FF | 0x302A..=0x302F | 0x3099..=0x309A
+    | 0xFE00..=0xFE0F | 0xFE20..=0xFE2F | 0x1F3FB..=0x1F3FF | 0xE0020..=0xE007F | 0xE0100..=0xE01EF)
+}
+
+// Clase de cada caracter de una linea, ya con las reglas que miran a los vecinos
+pub fn classes(chars: &[char], word_chars: &[char]) -> Vec<CharClass> {
+  let mut classes: Vec<CharClass> = Vec::with_capacity(chars.len());
+  for (i, &c) in chars.iter().enumerate() {
+    let class = if i > 0 && is_extend(c) { classes[i - 1] } else { char_class(c, word_chars) };
+    classes.push(class);
+  }
+
+  for i in 1..chars.len().saturating_sub(1) {
+    let (prev, next) = (chars[i - 1], chars[i + 1]);
+    let joins = match chars[i] {
+      '\'' | '’' | '·' => prev.is_alphabetic() && next.is_alphabetic(),
+      '.' => prev.is_numeric() && next.is_numeric(),
+      _ => false,
+    };
+    if joins {
+      classes[i] = CharClass::Word;
+    }
+  }
+
+  classes
+}
+
+// Palabra en `x` o justo antes (el cursor al final de una palabra también la toma):
+// (desde, hasta) en chars
+pub fn word_at(chars: &[char], x: usize, word_chars: &[char]) -> Option<(usize, usize)> {
+  let classes = classes(chars, word_chars);
+  let x = x.min(chars.len());
+  let at = if x < chars.len() && classes[x] == CharClass::Word { x } else { x.checked_sub(1)? };
+  if classes[at] != CharClass::Word {
+    return None;
+  }
+
+  let mut from = at;
+  while from > 0 && classes[from - 1] == CharClass::Word {
+    from -= 1;
+  }
+  let mut to = at + 1;
+  while to < chars.len() && classes[to] == CharClass::Word {
+    to += 1;
+  }
+  Some((from, to))
+}
+
+// Principio de la palabra (o grupo de puntuación) anterior a `x`, salteando espacios
+pub fn prev_word_start(chars: &[char], x: usize, word_chars: &[char]) -> usize {
+  let classes = classes(chars, word_chars);
+  let mut i = x.min(chars.len());
+
+  while i > 0 && classes[i - 1] == CharClass::Whitespace {
+    i -= 1;
+  }
+  if i > 0 {
+    let class = classes[i - 1];
+    while i > 0 && classes[i - 1] == class {
+      i -= 1;
+    }
+  }
+
+  i
+}
+
+// Principio de la palabra (o grupo de puntuación) siguiente a `x`
+pub fn next_word_start(chars: &[char], x: usize, word_chars: &[char]) -> usize {
+  let classes = classes(chars, word_chars);
+  let mut i = x.min(chars.len());
+
+  if i < chars.len() && classes[i] != CharClass::Whitespace {
+    let class = classes[i];
+    while i < chars.len() && classes[i] == class {
+      i += 1;
+    }
+  }
+  while i < chars.len() && classes[i] == CharClass::Whitespace {
+    i += 1;
+  }
+
+  i
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // W palabra, _ espacio, P puntuación
+  fn letters(text: &str, word_chars: &[char]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    classes(&chars, word_chars).iter()
+      .map(|class| match class {
+        CharClass::Word => 'W',
+        CharClass::Whitespace => '_',
+        CharClass::Punctuation => 'P',
+      })
+      .collect()
+  }
+
+  fn chars(text: &str) -> Vec<char> {
+    text.chars().collect()
+  }
+
+  #[test]
+  fn classes_join_apostrophes_and_decimals() {
+    assert_eq!(letters("don't 3.14", &[]), "WWWWW_WWWW");
+    assert_eq!(letters("a.b 'x'", &[]), "WPW_PWP");
+    assert_eq!(letters("¿qué?", &[]), "PWWWP");
+    // la tilde combinante va con la letra de antes
+    assert_eq!(letters("e\u{301}!", &[]), "WWP");
+  }
+
+  #[test]
+  fn word_chars_extend_words() {
+    assert_eq!(letters("$HOME-x", &[]), "PWWWWPW");
+    assert_eq!(letters("$HOME-x", &['$', '-']), "WWWWWWW");
+  }
+
+  #[test]
+  fn word_at_cursor() {
+    let line = chars("let foo_bar = 1;");
+    assert_eq!(word_at(&line, 5, &[]), Some((4, 11)));
+    // al final de la palabra también la toma
+    assert_eq!(word_at(&line, 11, &[]), Some((4, 11)));
+    assert_eq!(word_at(&line, 13, &[]), None);
+    assert_eq!(word_at(&[], 0, &[]), None);
+  }
+
+  #[test]
+  fn word_starts() {
+    let line = chars("std::fs::read(x)  y");
+    let mut forward = vec![0];
+    while *forward.last().unwrap() < line.len() {
+      forward.push(next_word_start(&line, *forward.last().unwrap(), &[]));
+    }
+    assert_eq!(forward, [0, 3, 5, 7, 9, 13, 14, 15, 18, 19]);
+
+    let mut backward = vec![line.len()];
+    while *backward.last().unwrap() > 0 {
+      backward.push(prev_word_start(&line, *backward.last().unwrap(), &[]));
+    }
+    assert_eq!(backward, [19, 18, 15, 14, 13, 9, 7, 5, 3, 0]);
+  }
+}